@@ -0,0 +1,549 @@
+//! `#[derive(ToSchema)]` and `#[operation]` for the [`oas`](https://docs.rs/oas) crate, which
+//! describe Rust types and handlers with schemas and operations. Use them through the `derive`
+//! feature of `oas`.
+
+mod operation;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, GenericParam, LitStr, Token,
+    Type,
+};
+
+/// Implements `oas::ToSchema` for a struct or an enum, following its `serde` attributes.
+///
+/// The schema is registered in `components/schemas` under the name of the type, or its
+/// `#[serde(rename)]`, except for generic types which are inlined. Doc comments become
+/// descriptions and `#[deprecated]` marks the schema or the property as deprecated.
+///
+/// These `serde` attributes are supported:
+/// - on containers: `rename`, `rename_all`, `rename_all_fields`, `tag`, `content`, `untagged`,
+///   `transparent`, `default` and `deny_unknown_fields`,
+/// - on variants: `rename`, `rename_all` and `skip`,
+/// - on fields: `rename`, `skip`, `skip_serializing`, `default`, `skip_serializing_if` and
+///   `flatten`.
+///
+/// Fields of type `Option`, with a default, or which may be skipped are not required.
+#[proc_macro_derive(ToSchema, attributes(serde))]
+pub fn derive_to_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Describes a handler with an `oas::Operation`, e.g.
+/// `#[operation(get, "/pets/{id}", tag = "pets")]`, for the `oas::OperationRegistry`.
+///
+/// The attribute takes the method, the path and optionally `tag = "..."`, which can be repeated,
+/// and `operation_id = "..."`, which defaults to the name of the handler. It implements
+/// `oas::DescribedOperation` for a struct with the name of the handler:
+/// - the arguments implement `oas::OperationArgument`, e.g. `id: InPath<u64>` or
+///   `pet: Json<Pet>`, and describe parameters named after them or the request body. Other
+///   arguments, e.g. the state of the application, are marked with `#[operation(skip)]`,
+/// - the return type implements `oas::OperationOutput`, e.g.
+///   `Result<Json<Pet>, WithStatus<404, Json<Error>>>`, and describes the responses,
+/// - the first paragraph of the doc comment is the summary and the rest is the description, and
+///   `#[deprecated]` marks the operation as deprecated.
+///
+/// The `InPath` arguments of primitive types must be named after the `{…}` placeholders of the
+/// path, and every placeholder needs one unless a struct describes the path parameters:
+///
+/// ```compile_fail
+/// # use oas::{operation, InPath};
+/// #[operation(get, "/pets/{id}")]
+/// fn get_pet(pet_id: InPath<u64>) {}
+/// ```
+#[proc_macro_attribute]
+pub fn operation(arguments: TokenStream, item: TokenStream) -> TokenStream {
+    let arguments = parse_macro_input!(arguments as operation::Arguments);
+    let function = parse_macro_input!(item as syn::ItemFn);
+    operation::expand(arguments, function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The `rename_all` rules of `serde`.
+#[derive(Clone, Copy)]
+enum Case {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl Case {
+    fn parse(value: &LitStr) -> syn::Result<Case> {
+        Ok(match value.value().as_str() {
+            "lowercase" => Case::Lower,
+            "UPPERCASE" => Case::Upper,
+            "PascalCase" => Case::Pascal,
+            "camelCase" => Case::Camel,
+            "snake_case" => Case::Snake,
+            "SCREAMING_SNAKE_CASE" => Case::ScreamingSnake,
+            "kebab-case" => Case::Kebab,
+            "SCREAMING-KEBAB-CASE" => Case::ScreamingKebab,
+            _ => return Err(syn::Error::new(value.span(), "unknown rename rule")),
+        })
+    }
+
+    /// Renames a variant, which is in PascalCase.
+    fn variant(self, name: &str) -> String {
+        let snake = || {
+            let mut snake = String::new();
+            for (index, c) in name.char_indices() {
+                if c.is_uppercase() && index > 0 {
+                    snake.push('_');
+                }
+                snake.push(c.to_ascii_lowercase());
+            }
+            snake
+        };
+        match self {
+            Case::Lower => name.to_ascii_lowercase(),
+            Case::Upper => name.to_ascii_uppercase(),
+            Case::Pascal => name.to_string(),
+            Case::Camel => lower_first(name),
+            Case::Snake => snake(),
+            Case::ScreamingSnake => snake().to_ascii_uppercase(),
+            Case::Kebab => snake().replace('_', "-"),
+            Case::ScreamingKebab => snake().replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+
+    /// Renames a field, which is in snake_case.
+    fn field(self, name: &str) -> String {
+        let pascal = || {
+            let mut pascal = String::new();
+            let mut capitalize = true;
+            for c in name.chars() {
+                match c {
+                    '_' => capitalize = true,
+                    c if capitalize => {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    }
+                    c => pascal.push(c),
+                }
+            }
+            pascal
+        };
+        match self {
+            Case::Lower | Case::Snake => name.to_string(),
+            Case::Upper | Case::ScreamingSnake => name.to_ascii_uppercase(),
+            Case::Pascal => pascal(),
+            Case::Camel => lower_first(&pascal()),
+            Case::Kebab => name.replace('_', "-"),
+            Case::ScreamingKebab => name.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+}
+
+/// Lowercases the first character of a name, which may be empty or start with a non-ASCII one.
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// The `serde` attributes of a container, a variant or a field.
+#[derive(Default)]
+struct Attributes {
+    rename: Option<String>,
+    rename_all: Option<Case>,
+    rename_all_fields: Option<Case>,
+    tag: Option<String>,
+    content: Option<String>,
+    untagged: bool,
+    transparent: bool,
+    default: bool,
+    deny_unknown_fields: bool,
+    skip: bool,
+    /// Whether the field may be absent from the serialized value.
+    skippable: bool,
+    flatten: bool,
+    description: Option<String>,
+    deprecated: bool,
+}
+
+impl Attributes {
+    fn parse(attributes: &[Attribute]) -> syn::Result<Attributes> {
+        let mut parsed = Attributes::default();
+        let mut docs = vec![];
+        for attribute in attributes {
+            if attribute.path().is_ident("doc") {
+                if let syn::Meta::NameValue(syn::MetaNameValue {
+                    value:
+                        Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Str(doc),
+                            ..
+                        }),
+                    ..
+                }) = &attribute.meta
+                {
+                    let doc = doc.value();
+                    docs.push(doc.strip_prefix(' ').unwrap_or(&doc).trim_end().to_string());
+                }
+                continue;
+            }
+            if attribute.path().is_ident("deprecated") {
+                parsed.deprecated = true;
+                continue;
+            }
+            if !attribute.path().is_ident("serde") {
+                continue;
+            }
+            attribute.parse_nested_meta(|meta| {
+                let path = &meta.path;
+                if path.is_ident("rename") {
+                    parsed.rename = Some(serialized(&meta)?.value());
+                } else if path.is_ident("rename_all") {
+                    parsed.rename_all = Some(Case::parse(&serialized(&meta)?)?);
+                } else if path.is_ident("rename_all_fields") {
+                    parsed.rename_all_fields = Some(Case::parse(&serialized(&meta)?)?);
+                } else if path.is_ident("tag") {
+                    parsed.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if path.is_ident("content") {
+                    parsed.content = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if path.is_ident("untagged") {
+                    parsed.untagged = true;
+                } else if path.is_ident("transparent") {
+                    parsed.transparent = true;
+                } else if path.is_ident("deny_unknown_fields") {
+                    parsed.deny_unknown_fields = true;
+                } else if path.is_ident("flatten") {
+                    parsed.flatten = true;
+                } else if path.is_ident("skip") || path.is_ident("skip_serializing") {
+                    parsed.skip = true;
+                } else if path.is_ident("default") {
+                    parsed.default = true;
+                    skip_value(&meta)?;
+                } else if path.is_ident("skip_serializing_if")
+                    || path.is_ident("skip_deserializing")
+                {
+                    parsed.skippable = true;
+                    skip_value(&meta)?;
+                } else {
+                    skip_value(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        let description = docs.join("\n").trim().to_string();
+        parsed.description = (!description.is_empty()).then_some(description);
+        Ok(parsed)
+    }
+
+    fn description(&self) -> TokenStream2 {
+        match &self.description {
+            Some(description) => quote!(::std::option::Option::Some(#description)),
+            None => quote!(::std::option::Option::None),
+        }
+    }
+}
+
+/// The value of `rename = "..."` or the serialized name of `rename(serialize = "...")`.
+fn serialized(meta: &syn::meta::ParseNestedMeta) -> syn::Result<LitStr> {
+    if meta.input.peek(Token![=]) {
+        return meta.value()?.parse();
+    }
+    let mut serialized = None;
+    meta.parse_nested_meta(|nested| {
+        let value = nested.value()?.parse::<LitStr>()?;
+        if nested.path.is_ident("serialize") {
+            serialized = Some(value);
+        }
+        Ok(())
+    })?;
+    serialized.ok_or_else(|| meta.error("expected a serialized name"))
+}
+
+/// Skips the value of an attribute which is not used, e.g. `= "path"` or `(...)`.
+fn skip_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.input.parse::<proc_macro2::Group>()?;
+    }
+    Ok(())
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let container = Attributes::parse(&input.attrs)?;
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    let generic = generics
+        .params
+        .iter()
+        .any(|param| matches!(param, GenericParam::Type(_)));
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::oas::ToSchema));
+    }
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    let name = container
+        .rename
+        .clone()
+        .unwrap_or_else(|| ident.unraw().to_string());
+    let schema_name = match generic {
+        true => quote!(::std::option::Option::None),
+        false => quote!(::std::option::Option::Some(::std::string::String::from(#name))),
+    };
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let mut body = fields(&data.fields, &container, container.rename_all)?;
+            if let (Some(tag), Fields::Named(_)) = (&container.tag, &data.fields) {
+                body = quote! {{
+                    let mut schema = #body;
+                    ::oas::__private::add_tag(&mut schema, #tag, #name);
+                    schema
+                }};
+            }
+            body
+        }
+        Data::Enum(data) => {
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| Ok((variant, Attributes::parse(&variant.attrs)?)))
+                .filter(|result| !matches!(result, Ok((_, attributes)) if attributes.skip))
+                .collect::<syn::Result<Vec<_>>>()?;
+            let names = variants
+                .iter()
+                .map(|(variant, attributes)| match &attributes.rename {
+                    Some(rename) => rename.clone(),
+                    None => {
+                        let name = variant.ident.unraw().to_string();
+                        match container.rename_all {
+                            Some(case) => case.variant(&name),
+                            None => name,
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+            let external = !container.untagged && container.tag.is_none();
+            if external
+                && variants
+                    .iter()
+                    .all(|(variant, _)| matches!(variant.fields, Fields::Unit))
+            {
+                quote!(::oas::__private::enumeration(&[#(#names),*]))
+            } else {
+                let schemas = variants
+                    .iter()
+                    .zip(&names)
+                    .map(|((variant, attributes), name)| {
+                        let case = attributes.rename_all.or(container.rename_all_fields);
+                        let schema = enum_variant(variant, name, &container, case)?;
+                        let description = attributes.description();
+                        let deprecated = attributes.deprecated;
+                        Ok(quote! {
+                            ::oas::__private::describe(
+                                ::oas::Referenceable::Data(#schema),
+                                #description,
+                                #deprecated,
+                            )
+                        })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                quote!(::oas::__private::one_of(::std::vec![#(#schemas),*]))
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                input.span(),
+                "unions cannot be described by a schema",
+            ))
+        }
+    };
+
+    let description = container.description();
+    let deprecated = container.deprecated;
+    Ok(quote! {
+        impl #impl_generics ::oas::ToSchema for #ident #type_generics #where_clause {
+            fn schema_name() -> ::std::option::Option<::std::string::String> {
+                #schema_name
+            }
+
+            fn schema(components: &mut ::oas::Components) -> ::oas::Schema {
+                let schema = #body;
+                ::oas::__private::inline(::oas::__private::describe(
+                    ::oas::Referenceable::Data(schema),
+                    #description,
+                    #deprecated,
+                ))
+            }
+        }
+    })
+}
+
+/// The schema of the fields of a struct or a variant.
+fn fields(
+    fields: &Fields,
+    container: &Attributes,
+    case: Option<Case>,
+) -> syn::Result<TokenStream2> {
+    let fields = fields
+        .iter()
+        .map(|field| Ok((field, Attributes::parse(&field.attrs)?)))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let fields = fields
+        .into_iter()
+        .filter(|(_, attributes)| !attributes.skip)
+        .collect::<Vec<_>>();
+    let named = fields.iter().all(|(field, _)| field.ident.is_some());
+    if container.transparent || (!named && fields.len() == 1) {
+        let Some((field, _)) = fields.first() else {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "transparent structs need a field",
+            ));
+        };
+        let ty = &field.ty;
+        return Ok(quote! {
+            ::oas::__private::inline(
+                ::oas::__private::field::<#ty>(components, ::std::option::Option::None, false),
+            )
+        });
+    }
+    if fields.is_empty() && !named {
+        return Ok(quote!(::oas::__private::null()));
+    }
+    if !named {
+        let items = fields
+            .iter()
+            .map(|(field, _)| field_schema(&field.ty, None));
+        return Ok(quote!(::oas::__private::tuple(::std::vec![#(#items),*])));
+    }
+
+    let mut properties = vec![];
+    let mut flattened = vec![];
+    for (field, attributes) in &fields {
+        let ty = &field.ty;
+        if attributes.flatten {
+            flattened.push(quote!(::oas::__private::flatten::<#ty>(&mut schema, components);));
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("the fields are named");
+        let name = match (&attributes.rename, case) {
+            (Some(rename), _) => rename.clone(),
+            (None, Some(case)) => case.field(&ident.unraw().to_string()),
+            (None, None) => ident.unraw().to_string(),
+        };
+        let schema = field_schema(ty, Some(attributes));
+        let optional = container.default || attributes.default || attributes.skippable;
+        properties.push(quote! {
+            ::oas::__private::Property {
+                name: #name,
+                schema: #schema,
+                required: !#optional && !<#ty as ::oas::ToSchema>::optional(),
+            }
+        });
+    }
+    let deny = container.deny_unknown_fields.then(|| {
+        quote! {
+            schema.additional_properties =
+                ::std::option::Option::Some(::oas::AdditionalProperties::Allowed(false));
+        }
+    });
+    Ok(quote! {{
+        #[allow(unused_mut)]
+        let mut schema = ::oas::__private::object(::std::vec![#(#properties),*]);
+        #(#flattened)*
+        #deny
+        schema
+    }})
+}
+
+fn field_schema(ty: &Type, attributes: Option<&Attributes>) -> TokenStream2 {
+    let description = attributes.map_or(quote!(::std::option::Option::None), |attributes| {
+        attributes.description()
+    });
+    let deprecated = attributes.is_some_and(|attributes| attributes.deprecated);
+    quote!(::oas::__private::field::<#ty>(components, #description, #deprecated))
+}
+
+/// The schema of a variant of an enum, according to the representation of the enum.
+fn enum_variant(
+    variant: &syn::Variant,
+    name: &str,
+    container: &Attributes,
+    case: Option<Case>,
+) -> syn::Result<TokenStream2> {
+    let inner = match &variant.fields {
+        Fields::Unit => None,
+        fields => Some(self::fields(fields, &Attributes::default(), case)?),
+    };
+    let property = |name: &str, schema: TokenStream2| {
+        quote! {
+            ::oas::__private::Property {
+                name: #name,
+                schema: ::oas::Referenceable::Data(#schema),
+                required: true,
+            }
+        }
+    };
+    let constant = |value: &str| quote!(::oas::__private::constant(#value));
+    Ok(
+        match (
+            &container.tag,
+            &container.content,
+            container.untagged,
+            inner,
+        ) {
+            (_, _, true, None) => quote!(::oas::__private::null()),
+            (_, _, true, Some(inner)) => inner,
+            (Some(tag), None, false, None) => {
+                let tag = property(tag, constant(name));
+                quote!(::oas::__private::object(::std::vec![#tag]))
+            }
+            (Some(tag), None, false, Some(inner)) => match &variant.fields {
+                Fields::Named(_) => quote! {{
+                    let mut schema = #inner;
+                    ::oas::__private::add_tag(&mut schema, #tag, #name);
+                    schema
+                }},
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let tag = property(tag, constant(name));
+                    let inner = field_schema(&fields.unnamed[0].ty, None);
+                    quote! {
+                        ::oas::__private::all_of(::std::vec![
+                            #inner,
+                            ::oas::Referenceable::Data(::oas::__private::object(::std::vec![#tag])),
+                        ])
+                    }
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        variant.span(),
+                        "internally tagged enums cannot hold tuple variants",
+                    ))
+                }
+            },
+            (Some(tag), Some(_), false, None) => {
+                let tag = property(tag, constant(name));
+                quote!(::oas::__private::object(::std::vec![#tag]))
+            }
+            (Some(tag), Some(content), false, Some(inner)) => {
+                let tag = property(tag, constant(name));
+                let content = property(content, inner);
+                quote!(::oas::__private::object(::std::vec![#tag, #content]))
+            }
+            (None, _, false, None) => constant(name),
+            (None, _, false, Some(inner)) => {
+                let inner = property(name, inner);
+                quote!(::oas::__private::object(::std::vec![#inner]))
+            }
+        },
+    )
+}
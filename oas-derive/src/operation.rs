@@ -0,0 +1,253 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{FnArg, Ident, ItemFn, LitStr, Pat, ReturnType, Token};
+
+use crate::Attributes;
+
+/// The arguments of `#[operation(get, "/pets/{id}", tag = "pets")]`.
+pub struct Arguments {
+    method: Ident,
+    path: LitStr,
+    tags: Vec<LitStr>,
+    operation_id: Option<LitStr>,
+}
+
+impl Parse for Arguments {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let method = input.parse::<Ident>()?;
+        const METHODS: [&str; 8] = [
+            "get", "put", "post", "delete", "options", "head", "patch", "trace",
+        ];
+        if !METHODS.contains(&method.to_string().as_str()) {
+            return Err(syn::Error::new(method.span(), "unknown HTTP method"));
+        }
+        input.parse::<Token![,]>()?;
+        let path = input.parse::<LitStr>()?;
+        let mut tags = vec![];
+        let mut operation_id = None;
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+            let value = input.parse::<LitStr>()?;
+            match key.to_string().as_str() {
+                "tag" => tags.push(value),
+                "operation_id" => operation_id = Some(value),
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "expected `tag` or `operation_id`",
+                    ))
+                }
+            }
+        }
+        Ok(Arguments {
+            method,
+            path,
+            tags,
+            operation_id,
+        })
+    }
+}
+
+/// Whether an argument has the `#[operation(skip)]` attribute, which is removed.
+fn skipped(argument: &mut syn::PatType) -> syn::Result<bool> {
+    let mut skip = false;
+    let mut result = Ok(());
+    argument.attrs.retain(|attribute| {
+        if !attribute.path().is_ident("operation") {
+            return true;
+        }
+        result = attribute.parse_nested_meta(|meta| match meta.path.is_ident("skip") {
+            true => {
+                skip = true;
+                Ok(())
+            }
+            false => Err(meta.error("expected `skip`")),
+        });
+        false
+    });
+    result.map(|()| skip)
+}
+
+/// The name of the parameters described by an argument: its identifier, without leading
+/// underscores, e.g. `id` for `id: InPath<u64>` or `InPath(_id): InPath<u64>`.
+fn argument_name(pattern: &Pat) -> Option<String> {
+    match pattern {
+        Pat::Ident(pattern) => Some(pattern.ident.to_string()),
+        Pat::TupleStruct(pattern) if pattern.elems.len() == 1 => argument_name(&pattern.elems[0]),
+        Pat::Reference(pattern) => argument_name(&pattern.pat),
+        _ => None,
+    }
+    .map(|name| name.trim_start_matches('_').to_string())
+}
+
+/// The names of the `{…}` placeholders of a path, e.g. `id` for `/pets/{id}`.
+fn placeholders(path: &str) -> Vec<&str> {
+    path.split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}'))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// The type of the parameter of an `InPath<T>` argument, e.g. `u64` for `InPath<u64>`.
+fn in_path(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(ty) = ty else {
+        return None;
+    };
+    let segment = ty.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match (segment.ident == "InPath", arguments.args.first()) {
+        (true, Some(syn::GenericArgument::Type(ty))) => Some(ty),
+        _ => None,
+    }
+}
+
+/// Whether a type is described by a single parameter, as opposed to structs which are described
+/// by one parameter per field and cannot be told apart from other types here.
+fn is_scalar(ty: &syn::Type) -> bool {
+    const SCALARS: [&str; 18] = [
+        "bool", "char", "str", "String", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16",
+        "u32", "u64", "u128", "usize", "f32", "f64",
+    ];
+    match ty {
+        syn::Type::Reference(ty) => is_scalar(&ty.elem),
+        syn::Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| SCALARS.iter().any(|scalar| segment.ident == scalar)),
+        _ => false,
+    }
+}
+
+pub fn expand(arguments: Arguments, mut function: ItemFn) -> syn::Result<TokenStream2> {
+    let attributes = Attributes::parse(&function.attrs)?;
+    let ident = &function.sig.ident;
+    let vis = &function.vis;
+    if !function.sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            function.sig.generics.span(),
+            "operations cannot be generic",
+        ));
+    }
+
+    let path = arguments.path.value();
+    let placeholders = placeholders(&path);
+    let mut path_parameters = vec![];
+    // structs describe one path parameter per field, so the placeholders may be covered by them
+    let mut structs = false;
+    let mut describe = vec![];
+    for argument in &mut function.sig.inputs {
+        let FnArg::Typed(argument) = argument else {
+            return Err(syn::Error::new(
+                argument.span(),
+                "operations cannot be methods",
+            ));
+        };
+        if skipped(argument)? {
+            continue;
+        }
+        let Some(name) = argument_name(&argument.pat) else {
+            return Err(syn::Error::new(
+                argument.pat.span(),
+                "the argument needs a name, or `#[operation(skip)]`",
+            ));
+        };
+        let ty = &argument.ty;
+        if let Some(parameter) = in_path(ty) {
+            match is_scalar(parameter) {
+                true if !placeholders.contains(&name.as_str()) => {
+                    return Err(syn::Error::new(
+                        argument.pat.span(),
+                        format!("`{name}` is not a parameter of the path `{path}`"),
+                    ));
+                }
+                true => path_parameters.push(name.clone()),
+                false => structs = true,
+            }
+        }
+        describe.push(quote! {
+            <#ty as ::oas::OperationArgument>::describe(#name, &mut operation, components);
+        });
+    }
+    if let Some(placeholder) = placeholders
+        .iter()
+        .find(|placeholder| !path_parameters.iter().any(|name| name == *placeholder))
+        .filter(|_| !structs)
+    {
+        return Err(syn::Error::new(
+            arguments.path.span(),
+            format!("the path parameter `{placeholder}` has no `InPath` argument"),
+        ));
+    }
+    let output = match &function.sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, ty) => quote!(#ty),
+    };
+
+    let (summary, description) = match &attributes.description {
+        Some(docs) => {
+            let (summary, description) = docs.split_once("\n\n").unwrap_or((docs, ""));
+            let summary = summary.lines().collect::<Vec<_>>().join(" ");
+            let description = description.trim();
+            let description = match description.is_empty() {
+                true => quote!(::std::option::Option::None),
+                false => quote!(::std::option::Option::Some(#description)),
+            };
+            (quote!(::std::option::Option::Some(#summary)), description)
+        }
+        None => (
+            quote!(::std::option::Option::None),
+            quote!(::std::option::Option::None),
+        ),
+    };
+    let deprecated = attributes.deprecated;
+    let method = format_ident!("{}", {
+        let method = arguments.method.to_string();
+        method[..1].to_uppercase() + &method[1..]
+    });
+    let path = &arguments.path;
+    let tags = &arguments.tags;
+    let operation_id = match &arguments.operation_id {
+        Some(operation_id) => operation_id.value(),
+        None => ident.to_string(),
+    };
+    let doc = format!("The description of the operation of [`{ident}()`].");
+
+    Ok(quote! {
+        #function
+
+        #[doc = #doc]
+        #[allow(non_camel_case_types)]
+        #vis struct #ident {}
+
+        impl ::oas::DescribedOperation for #ident {
+            const METHOD: ::oas::Method = ::oas::Method::#method;
+            const PATH: &'static str = #path;
+
+            fn operation(components: &mut ::oas::Components) -> ::oas::Operation {
+                let responses = <#output as ::oas::OperationOutput>::responses(components);
+                #[allow(unused_mut)]
+                let mut operation = ::oas::__private::operation(
+                    #operation_id,
+                    &[#(#tags),*],
+                    #summary,
+                    #description,
+                    #deprecated,
+                    responses,
+                );
+                #(#describe)*
+                operation
+            }
+        }
+    })
+}
@@ -0,0 +1,236 @@
+use oas::{Components, ToSchema};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+fn register<T: ToSchema>() -> (Value, Value) {
+    let mut components = Components::default();
+    let schema = components.register_schema::<T>();
+    (
+        serde_json::to_value(schema).unwrap(),
+        serde_json::to_value(components.schemas).unwrap(),
+    )
+}
+
+/// A pet.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+struct Pet {
+    /// The name of the pet.
+    pet_name: String,
+    age: Option<u8>,
+    #[serde(rename = "kind")]
+    species: Species,
+    #[serde(skip)]
+    secret: u64,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(flatten)]
+    owner: Owner,
+    children: Vec<Pet>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+#[allow(dead_code)]
+enum Species {
+    Cat,
+    Dog,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+struct Owner {
+    owner: String,
+}
+
+#[test]
+fn derives_structs() {
+    let (schema, schemas) = register::<Pet>();
+    assert_eq!(schema, json!({"$ref": "#/components/schemas/Pet"}));
+    assert_eq!(
+        schemas,
+        json!({
+            "Pet": {
+                "type": "object",
+                "description": "A pet.",
+                "properties": {
+                    "age": {"type": "integer", "format": "int32", "minimum": 0, "maximum": 255, "nullable": true},
+                    "children": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+                    "kind": {"$ref": "#/components/schemas/Species"},
+                    "owner": {"type": "string"},
+                    "petName": {"type": "string", "description": "The name of the pet."},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                },
+                "required": ["petName", "kind", "children", "owner"],
+            },
+            "Species": {"type": "string", "enum": ["cat", "dog"]},
+        })
+    );
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(dead_code)]
+enum Shape {
+    Circle { radius: f64 },
+    Named(Owner),
+    Empty,
+}
+
+#[test]
+fn derives_internally_tagged_enums() {
+    let (_, schemas) = register::<Shape>();
+    assert_eq!(
+        schemas["Shape"],
+        json!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": {
+                        "radius": {"type": "number", "format": "double"},
+                        "type": {"type": "string", "enum": ["circle"]},
+                    },
+                    "required": ["type", "radius"],
+                },
+                {
+                    "allOf": [
+                        {"$ref": "#/components/schemas/Owner"},
+                        {
+                            "type": "object",
+                            "properties": {"type": {"type": "string", "enum": ["named"]}},
+                            "required": ["type"],
+                        },
+                    ],
+                },
+                {
+                    "type": "object",
+                    "properties": {"type": {"type": "string", "enum": ["empty"]}},
+                    "required": ["type"],
+                },
+            ],
+        })
+    );
+}
+
+#[test]
+fn validates_internally_tagged_enums() {
+    let mut components = Components::default();
+    let schema = components.register_schema::<Shape>();
+    for shape in [Shape::Circle { radius: 1.0 }, Shape::Empty] {
+        let value = serde_json::to_value(shape).unwrap();
+        assert_eq!(schema.validate(&value, Some(&components)), Ok(()));
+    }
+    assert!(schema
+        .validate(&json!({"type": "circle"}), Some(&components))
+        .is_err());
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "t", content = "c")]
+#[allow(dead_code)]
+enum Adjacent {
+    Number(i32),
+    Unit,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum Untagged {
+    Number(i32),
+    Pair(String, bool),
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+enum External {
+    Number(i32),
+    Unit,
+}
+
+#[test]
+fn derives_other_enum_representations() {
+    let (_, schemas) = register::<Adjacent>();
+    assert_eq!(
+        schemas["Adjacent"]["oneOf"][0],
+        json!({
+            "type": "object",
+            "properties": {
+                "c": {"type": "integer", "format": "int32"},
+                "t": {"type": "string", "enum": ["Number"]},
+            },
+            "required": ["t", "c"],
+        })
+    );
+    let (_, schemas) = register::<Untagged>();
+    assert_eq!(
+        schemas["Untagged"],
+        json!({
+            "oneOf": [
+                {"type": "integer", "format": "int32"},
+                {
+                    "type": "array",
+                    "items": {"anyOf": [{"type": "string"}, {"type": "boolean"}]},
+                    "minItems": 2,
+                    "maxItems": 2,
+                },
+            ],
+        })
+    );
+    let (_, schemas) = register::<External>();
+    assert_eq!(
+        schemas["External"]["oneOf"],
+        json!([
+            {
+                "type": "object",
+                "properties": {"Number": {"type": "integer", "format": "int32"}},
+                "required": ["Number"],
+            },
+            {"type": "string", "enum": ["Unit"]},
+        ])
+    );
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+struct Page<T> {
+    items: Vec<T>,
+}
+
+#[test]
+fn inlines_generic_types() {
+    let (schema, schemas) = register::<Page<Owner>>();
+    assert_eq!(
+        schema,
+        json!({
+            "type": "object",
+            "properties": {"items": {"type": "array", "items": {"$ref": "#/components/schemas/Owner"}}},
+            "required": ["items"],
+        })
+    );
+    assert_eq!(schemas.as_object().unwrap().len(), 1);
+}
+
+#[derive(ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+enum Dessert {
+    Éclair,
+    ApplePie,
+}
+
+#[derive(ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+struct Unnamed {
+    __: u8,
+}
+
+#[test]
+fn renames_non_ascii_and_empty_names() {
+    let (_, schemas) = register::<Dessert>();
+    assert_eq!(schemas["Dessert"]["enum"], json!(["Éclair", "applePie"]));
+    let (_, schemas) = register::<Unnamed>();
+    assert_eq!(schemas["Unnamed"]["required"], json!([""]));
+}
@@ -0,0 +1,171 @@
+use oas::{operation, InPath, InQuery, Info, Json, OperationRegistry, ToSchema, WithStatus};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+struct Pet {
+    name: String,
+}
+
+#[derive(Serialize, ToSchema)]
+struct Error {
+    message: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[allow(dead_code)]
+struct Page {
+    limit: Option<u32>,
+    offset: u32,
+}
+
+struct State;
+
+/// Finds a pet.
+///
+/// Pets are found by their identifier.
+#[operation(get, "/pets/{id}", tag = "pets")]
+async fn get_pet(
+    #[operation(skip)] _state: &State,
+    InPath(id): InPath<u64>,
+) -> Result<Json<Pet>, WithStatus<404, Json<Error>>> {
+    match id {
+        1 => Ok(Json(Pet {
+            name: "Rex".to_string(),
+        })),
+        _ => Err(WithStatus(Json(Error {
+            message: "not found".to_string(),
+        }))),
+    }
+}
+
+#[allow(dead_code)]
+#[operation(get, "/pets", tag = "pets")]
+fn list_pets(_page: InQuery<Page>) -> Json<Vec<Pet>> {
+    Json(vec![])
+}
+
+#[allow(dead_code)]
+#[deprecated]
+#[operation(post, "/pets", operation_id = "addPet")]
+fn create_pet(_pet: Json<Pet>) -> WithStatus<201, ()> {
+    WithStatus(())
+}
+
+#[test]
+fn describes_operations() {
+    let mut registry = OperationRegistry::new();
+    #[allow(deprecated)]
+    registry
+        .register::<get_pet>()
+        .register::<list_pets>()
+        .register::<create_pet>();
+    let spec = registry.into_document(Info {
+        title: "Pets".to_string(),
+        description: None,
+        terms_of_service: None,
+        contact: None,
+        license: None,
+        version: "1.0.0".to_string(),
+        extras: None,
+    });
+    let spec = spec.to_value();
+    assert_eq!(
+        spec["paths"]["/pets/{id}"]["get"],
+        json!({
+            "tags": ["pets"],
+            "summary": "Finds a pet.",
+            "description": "Pets are found by their identifier.",
+            "operationId": "get_pet",
+            "parameters": [{
+                "name": "id",
+                "in": "path",
+                "required": true,
+                "schema": {"type": "integer", "format": "int64", "minimum": 0},
+            }],
+            "responses": {
+                "200": {
+                    "description": "OK",
+                    "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}},
+                },
+                "404": {
+                    "description": "Not Found",
+                    "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Error"}}},
+                },
+            },
+        })
+    );
+    assert_eq!(
+        spec["paths"]["/pets"]["get"]["parameters"],
+        json!([
+            {"name": "limit", "in": "query", "schema": {"type": "integer", "format": "int64", "minimum": 0, "maximum": 4294967295u64, "nullable": true}},
+            {"name": "offset", "in": "query", "required": true, "schema": {"type": "integer", "format": "int64", "minimum": 0, "maximum": 4294967295u64}},
+        ])
+    );
+    assert_eq!(
+        spec["paths"]["/pets"]["post"],
+        json!({
+            "operationId": "addPet",
+            "requestBody": {
+                "required": true,
+                "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Pet"}}},
+            },
+            "responses": {"201": {"description": "Created"}},
+            "deprecated": true,
+        })
+    );
+    let mut schemas = spec["components"]["schemas"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    schemas.sort();
+    assert_eq!(schemas, ["Error", "Pet"]);
+}
+
+#[allow(dead_code)]
+#[operation(put, "/pets/{id}")]
+fn replace_pet(InPath(_id): InPath<u64>, pet: Json<Pet>) -> Result<Json<Pet>, Json<Error>> {
+    Ok(pet)
+}
+
+#[test]
+fn merges_responses_with_the_same_status() {
+    let mut registry = OperationRegistry::new();
+    registry.register::<replace_pet>();
+    let responses = &registry.paths()["/pets/{id}"]
+        .put
+        .as_ref()
+        .unwrap()
+        .responses;
+    assert_eq!(
+        serde_json::to_value(responses).unwrap(),
+        json!({
+            "200": {
+                "description": "OK",
+                "content": {"application/json": {"schema": {"anyOf": [
+                    {"$ref": "#/components/schemas/Pet"},
+                    {"$ref": "#/components/schemas/Error"},
+                ]}}},
+            },
+        })
+    );
+}
+
+#[test]
+fn handlers_are_kept() {
+    let pet = ready(get_pet(&State, InPath(1)));
+    assert!(matches!(pet, Ok(Json(Pet { name })) if name == "Rex"));
+}
+
+/// Polls a future which is ready immediately.
+fn ready<F: std::future::Future>(future: F) -> F::Output {
+    let waker = std::task::Waker::noop();
+    let mut context = std::task::Context::from_waker(waker);
+    let mut future = std::pin::pin!(future);
+    match future.as_mut().poll(&mut context) {
+        std::task::Poll::Ready(output) => output,
+        std::task::Poll::Pending => panic!("the future is not ready"),
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+
+use mediatype::{MediaTypeBuf, Name, ReadParams};
+use serde_json::Value;
+
+use crate::{
+    Components, ContentNegotiation, Direction, FormValue, MediaType, RequestBody, ValidationError,
+};
+
+/// A request body ready to be sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedBody {
+    /// The value of the `Content-Type` header, including the boundary of multipart bodies.
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+/// The reasons why a body cannot be encoded or decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BodyError {
+    /// The request body does not describe the media type, or it cannot be encoded.
+    UnsupportedMediaType(String),
+    /// Form bodies can only be built from objects.
+    NotAnObject,
+    /// The `Content-Type` of a multipart body has no `boundary` parameter.
+    MissingBoundary,
+    /// The body cannot be parsed.
+    Malformed(String),
+    /// The value does not conform to the schema of the media type.
+    Invalid(Vec<ValidationError>),
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::UnsupportedMediaType(media_type) => {
+                write!(f, "unsupported media type `{media_type}`")
+            }
+            BodyError::NotAnObject => write!(f, "form bodies can only be built from objects"),
+            BodyError::MissingBoundary => write!(f, "missing multipart boundary"),
+            BodyError::Malformed(message) => write!(f, "malformed body: {message}"),
+            BodyError::Invalid(errors) => {
+                write!(f, "invalid body")?;
+                for error in errors {
+                    write!(f, "\n  {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for BodyError {}
+
+/// The kinds of bodies which can be encoded and decoded.
+enum BodyKind {
+    Json,
+    UrlEncoded,
+    Multipart,
+}
+
+impl BodyKind {
+    fn of(media_type: &MediaTypeBuf) -> Option<BodyKind> {
+        let essence = media_type.essence().to_string().to_ascii_lowercase();
+        match essence.as_str() {
+            "application/x-www-form-urlencoded" => Some(BodyKind::UrlEncoded),
+            "multipart/form-data" => Some(BodyKind::Multipart),
+            _ if media_type.subty() == "json"
+                || media_type.suffix().is_some_and(|suffix| suffix == "json") =>
+            {
+                Some(BodyKind::Json)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl RequestBody {
+    /// Validates a value against the schema of the given media type, then encodes it as
+    /// `multipart/form-data`, `application/x-www-form-urlencoded` or JSON according to the
+    /// [Encoding](crate::Encoding) of its properties. Multipart bodies get a random boundary.
+    pub fn encode(
+        &self,
+        content_type: &str,
+        value: &Value,
+        components: Option<&Components>,
+    ) -> Result<EncodedBody, BodyError> {
+        let (media_type, kind) = self.media_type(content_type)?;
+        validate(media_type, value, components)?;
+        match kind {
+            BodyKind::Json => Ok(EncodedBody {
+                content_type: content_type.to_string(),
+                body: serde_json::to_vec(value)
+                    .map_err(|error| BodyError::Malformed(error.to_string()))?,
+            }),
+            BodyKind::UrlEncoded => {
+                let object = value.as_object().ok_or(BodyError::NotAnObject)?;
+                Ok(EncodedBody {
+                    content_type: content_type.to_string(),
+                    body: media_type.encode_urlencoded(object).into_bytes(),
+                })
+            }
+            BodyKind::Multipart => {
+                let object = value.as_object().ok_or(BodyError::NotAnObject)?;
+                // a boundary must not appear within the parts, which is unlikely but possible
+                let text = value.to_string();
+                let boundary = std::iter::repeat_with(boundary)
+                    .find(|boundary| !text.contains(boundary.as_str()))
+                    .expect("the iterator is infinite");
+                let fields = object
+                    .iter()
+                    .map(|(name, value)| (name.clone(), FormValue::Value(value.clone())))
+                    .collect();
+                let multipart = media_type.encode_multipart(&fields, &boundary, components);
+                Ok(EncodedBody {
+                    content_type: multipart.content_type(),
+                    body: multipart.body,
+                })
+            }
+        }
+    }
+
+    /// Decodes a body sent with the given `Content-Type` header and validates the result against
+    /// the schema of the matching media type. The files of multipart bodies are decoded lossily
+    /// as UTF-8; use [MediaType::decode_multipart] to access their raw content.
+    pub fn decode(
+        &self,
+        content_type: &str,
+        body: &[u8],
+        components: Option<&Components>,
+    ) -> Result<Value, BodyError> {
+        let (media_type, kind) = self.media_type(content_type)?;
+        let value = match kind {
+            BodyKind::Json => serde_json::from_slice(body)
+                .map_err(|error| BodyError::Malformed(error.to_string()))?,
+            BodyKind::UrlEncoded => {
+                let body = std::str::from_utf8(body)
+                    .map_err(|error| BodyError::Malformed(error.to_string()))?;
+                media_type.decode_urlencoded(body, components)
+            }
+            BodyKind::Multipart => {
+                let content_type = content_type
+                    .parse::<MediaTypeBuf>()
+                    .map_err(|_| BodyError::UnsupportedMediaType(content_type.to_string()))?;
+                let boundary = content_type
+                    .get_param(Name::new_unchecked("boundary"))
+                    .ok_or(BodyError::MissingBoundary)?;
+                let fields =
+                    media_type.decode_multipart(body, &boundary.unquoted_str(), components)?;
+                fields
+                    .iter()
+                    .map(|(name, field)| (name.clone(), field.to_value()))
+                    .collect()
+            }
+        };
+        validate(media_type, &value, components)?;
+        Ok(value)
+    }
+
+    fn media_type(&self, content_type: &str) -> Result<(&MediaType, BodyKind), BodyError> {
+        let unsupported = || BodyError::UnsupportedMediaType(content_type.to_string());
+        let parsed = content_type
+            .parse::<MediaTypeBuf>()
+            .map_err(|_| unsupported())?;
+        let kind = BodyKind::of(&parsed).ok_or_else(unsupported)?;
+        let (_, media_type) = self
+            .content
+            .for_content_type(content_type)
+            .ok_or_else(unsupported)?;
+        Ok((media_type, kind))
+    }
+}
+
+fn validate(
+    media_type: &MediaType,
+    value: &Value,
+    components: Option<&Components>,
+) -> Result<(), BodyError> {
+    match &media_type.schema {
+        Some(schema) => schema
+            .validate_for(value, Direction::Request, components)
+            .map_err(BodyError::Invalid),
+        None => Ok(()),
+    }
+}
+
+/// A random multipart boundary, using the randomly seeded hasher of the standard library.
+pub(crate) fn boundary() -> String {
+    let random = || RandomState::new().build_hasher().finish();
+    format!("{:016x}{:016x}", random(), random())
+}
@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AdditionalProperties, Callback, Components, Header, Map, MediaRange, MediaType, Method,
+    OpenAPIV3, Operation, Parameter, ParameterIn, PathItem, Referenceable, RequestBody, Response,
+    Schema, Style, Type,
+};
+
+/// What [OpenAPIV3::canonicalize] does with the fields which have a default value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Defaults {
+    /// Leaves the fields as they are written.
+    #[default]
+    Keep,
+    /// Removes the fields set to their default value, e.g. `deprecated: false`.
+    Strip,
+    /// Sets the fields which are not written to their default value, where they apply, e.g. the
+    /// `style` of parameters or the `nullable` of schemas.
+    Explicit,
+}
+
+/// The options of [OpenAPIV3::canonicalize].
+#[derive(Debug, Clone, Default)]
+pub struct CanonicalOptions {
+    pub defaults: Defaults,
+}
+
+impl OpenAPIV3 {
+    /// Puts the document in a deterministic form, so that documents which only differ by the
+    /// order of their keys or by their defaults are written the same way, e.g. to hash them.
+    ///
+    /// The keys of all the maps are sorted, with the responses sorted by status code. The fields
+    /// of the objects are always written in the order of the specification, e.g. `openapi`,
+    /// `info`, `servers`, `paths` and `components`, with the methods of the path items in the
+    /// order `get`, `put`, `post`, `delete`, `options`, `head`, `patch` and `trace`. The order of
+    /// arrays is kept, as it is meaningful.
+    ///
+    /// The defaults handled are the ones of the specification: `required`, `deprecated`, `style`
+    /// and `explode` for parameters and headers, `allowEmptyValue` and `allowReserved` for query
+    /// parameters, `required` for request bodies, `deprecated` for operations, and `nullable`,
+    /// `readOnly`, `writeOnly`, `deprecated`, `exclusiveMinimum`, `exclusiveMaximum`,
+    /// `minLength`, `minItems`, `uniqueItems` and `minProperties` for schemas.
+    pub fn canonicalize(&mut self, options: &CanonicalOptions) {
+        // the maps keep the order of the document with the `preserve_order` feature
+        if cfg!(feature = "preserve_order") {
+            let mut value = self.to_value();
+            value.sort_all_objects();
+            *self = serde_json::from_value(value).expect("the sorted document is valid");
+        }
+        let normalizer = Normalizer(options.defaults);
+        for item in self.paths.values_mut() {
+            normalizer.path_item(item);
+        }
+        if let Some(components) = &mut self.components {
+            normalizer.components(components);
+        }
+    }
+
+    /// The canonical JSON text of the document, see [OpenAPIV3::canonicalize].
+    pub fn to_canonical_string(&self, options: &CanonicalOptions) -> String {
+        let mut canonical = self.clone();
+        canonical.canonicalize(options);
+        canonical.to_string()
+    }
+}
+
+struct Normalizer(Defaults);
+
+impl Normalizer {
+    /// Strips or sets a field with a default value.
+    fn apply<T: PartialEq>(&self, field: &mut Option<T>, default: T) {
+        match self.0 {
+            Defaults::Keep => {}
+            Defaults::Strip => {
+                if field.as_ref() == Some(&default) {
+                    *field = None;
+                }
+            }
+            Defaults::Explicit => {
+                field.get_or_insert(default);
+            }
+        }
+    }
+
+    fn components(&self, components: &mut Components) {
+        for (_, schema) in components.schemas.iter_mut().flatten() {
+            self.schema(schema);
+        }
+        for (_, response) in components.responses.iter_mut().flatten() {
+            self.response(response);
+        }
+        for (_, parameter) in components.parameters.iter_mut().flatten() {
+            self.parameter(parameter);
+        }
+        for (_, body) in components.request_bodies.iter_mut().flatten() {
+            self.request_body(body);
+        }
+        for (_, header) in components.headers.iter_mut().flatten() {
+            self.header(header);
+        }
+        for (_, callback) in components.callbacks.iter_mut().flatten() {
+            self.callback(callback);
+        }
+    }
+
+    fn path_item(&self, item: &mut PathItem) {
+        for parameter in item.parameters.iter_mut().flatten() {
+            self.parameter(parameter);
+        }
+        for method in Method::ALL {
+            if let Some(operation) = item.operation_mut(method) {
+                self.operation(operation);
+            }
+        }
+    }
+
+    fn operation(&self, operation: &mut Operation) {
+        self.apply(&mut operation.deprecated, false);
+        for parameter in operation.parameters.iter_mut().flatten() {
+            self.parameter(parameter);
+        }
+        if let Some(body) = &mut operation.request_body {
+            self.request_body(body);
+        }
+        let responses = &mut operation.responses;
+        for response in responses
+            .default
+            .iter_mut()
+            .chain(responses.data.values_mut())
+        {
+            self.response(response);
+        }
+        for (_, callback) in operation.callbacks.iter_mut().flatten() {
+            self.callback(callback);
+        }
+    }
+
+    fn callback(&self, callback: &mut Referenceable<Callback>) {
+        if let Referenceable::Data(callback) = callback {
+            for item in callback.data.values_mut() {
+                self.path_item(item);
+            }
+        }
+    }
+
+    fn parameter(&self, parameter: &mut Referenceable<Parameter>) {
+        let Referenceable::Data(parameter) = parameter else {
+            return;
+        };
+        // path parameters are always required
+        if parameter._in != ParameterIn::Path {
+            self.apply(&mut parameter.required, false);
+        }
+        self.apply(&mut parameter.deprecated, false);
+        if parameter._in == ParameterIn::Query {
+            self.apply(&mut parameter.allow_empty_value, false);
+        }
+        if let Some(schema) = &mut parameter.schema {
+            let style = match parameter._in {
+                ParameterIn::Query | ParameterIn::Cookie => Style::Form,
+                ParameterIn::Path | ParameterIn::Header => Style::Simple,
+            };
+            // `explode` defaults to `true` for the `form` style only
+            let explode = *parameter.style.as_ref().unwrap_or(&style) == Style::Form;
+            self.apply(&mut parameter.style, style);
+            self.apply(&mut parameter.explode, explode);
+            if parameter._in == ParameterIn::Query {
+                self.apply(&mut parameter.allow_reserved, false);
+            }
+            self.schema(schema);
+        }
+        if let Some(content) = &mut parameter.content {
+            self.content(content);
+        }
+    }
+
+    fn header(&self, header: &mut Referenceable<Header>) {
+        let Referenceable::Data(header) = header else {
+            return;
+        };
+        self.apply(&mut header.required, false);
+        self.apply(&mut header.deprecated, false);
+        if let Some(schema) = &mut header.schema {
+            self.apply(&mut header.style, Style::Simple);
+            self.apply(&mut header.explode, false);
+            self.schema(schema);
+        }
+        if let Some(content) = &mut header.content {
+            self.content(content);
+        }
+    }
+
+    fn request_body(&self, body: &mut Referenceable<RequestBody>) {
+        if let Referenceable::Data(body) = body {
+            self.apply(&mut body.required, false);
+            self.content(&mut body.content);
+        }
+    }
+
+    fn response(&self, response: &mut Referenceable<Response>) {
+        let Referenceable::Data(response) = response else {
+            return;
+        };
+        for (_, header) in response.headers.iter_mut().flatten() {
+            self.header(header);
+        }
+        if let Some(content) = &mut response.content {
+            self.content(content);
+        }
+    }
+
+    fn content(&self, content: &mut Map<MediaRange, MediaType>) {
+        for media_type in content.values_mut() {
+            if let Some(schema) = &mut media_type.schema {
+                self.schema(schema);
+            }
+            for encoding in media_type
+                .encoding
+                .iter_mut()
+                .flat_map(|encoding| encoding.values_mut())
+            {
+                for (_, header) in encoding.headers.iter_mut().flatten() {
+                    self.header(header);
+                }
+            }
+        }
+    }
+
+    fn schema(&self, schema: &mut Referenceable<Schema>) {
+        let Referenceable::Data(schema) = schema else {
+            return;
+        };
+        self.apply(&mut schema.nullable, false);
+        self.apply(&mut schema.read_only, false);
+        self.apply(&mut schema.write_only, false);
+        self.apply(&mut schema.deprecated, false);
+        if schema.minimum.is_some() {
+            self.apply(&mut schema.exclusive_minimum, false);
+        }
+        if schema.maximum.is_some() {
+            self.apply(&mut schema.exclusive_maximum, false);
+        }
+        match schema._type {
+            Some(Type::String) => self.apply(&mut schema.min_length, 0),
+            Some(Type::Array) => {
+                self.apply(&mut schema.min_items, 0);
+                self.apply(&mut schema.unique_items, false);
+            }
+            Some(Type::Object) => self.apply(&mut schema.min_properties, 0),
+            _ => {}
+        }
+
+        let schemas = schema
+            .all_of
+            .iter_mut()
+            .chain(&mut schema.one_of)
+            .chain(&mut schema.any_of)
+            .flatten()
+            .chain(schema.not.as_deref_mut())
+            .chain(schema.items.as_deref_mut())
+            .chain(
+                schema
+                    .properties
+                    .iter_mut()
+                    .flat_map(|properties| properties.values_mut()),
+            );
+        for schema in schemas {
+            self.schema(schema);
+        }
+        if let Some(AdditionalProperties::Schema(schema)) = &mut schema.additional_properties {
+            self.schema(schema);
+        }
+    }
+}
@@ -0,0 +1,797 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use super::pascal_case;
+use crate::{
+    AdditionalProperties, Components, Format, Map, MediaRange, MediaType, Method, OpenAPIV3,
+    ParameterIn, Referenceable, Schema, StatusCode, Style, Type,
+};
+
+/// A language independent description of the types of a document, from which the generators
+/// produce code. Every object, string enumeration and union gets a named type, including the
+/// inline ones, which are named after where they are defined, e.g. `PetOwner` for the `owner`
+/// property of `Pet`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Model {
+    pub types: Vec<TypeDef>,
+    pub operations: Vec<OperationDef>,
+    pub servers: Vec<ServerDef>,
+    /// The names of the types of `components/schemas`, by component name.
+    schemas: BTreeMap<String, String>,
+    names: BTreeSet<String>,
+}
+
+/// A named type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDef {
+    /// The name of the type, in PascalCase.
+    pub name: String,
+    /// The name of the schema in `components/schemas` the type is generated from, or `None` for
+    /// inline schemas.
+    pub component: Option<String>,
+    pub description: Option<String>,
+    pub deprecated: bool,
+    pub kind: TypeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeKind {
+    /// An object, with the properties of its `allOf` schemas merged in.
+    Struct {
+        fields: Vec<FieldDef>,
+        /// The type of the properties not listed in `properties`, when `additionalProperties`
+        /// is set.
+        additional_properties: Option<TypeRef>,
+    },
+    /// A string enumeration.
+    Enum { values: Vec<String> },
+    /// A `oneOf` or `anyOf`. With a tag, the variants are objects told apart by the value of the
+    /// tag property, which is the `propertyName` of the discriminator.
+    Union {
+        tag: Option<String>,
+        variants: Vec<VariantDef>,
+    },
+    /// Another name for a type.
+    Alias(TypeRef),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDef {
+    /// The name of the property.
+    pub name: String,
+    pub ty: TypeRef,
+    pub required: bool,
+    pub description: Option<String>,
+    pub deprecated: bool,
+    pub read_only: bool,
+    pub write_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantDef {
+    /// The name of the variant, in PascalCase.
+    pub name: String,
+    /// The value of the tag of the variant, for tagged unions.
+    pub tag: Option<String>,
+    pub ty: TypeRef,
+}
+
+/// An operation of a document, with the types of its parameters, body and responses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationDef {
+    /// The `operationId` of the operation, or a name made of its method and path.
+    pub name: String,
+    pub method: Method,
+    pub path: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub deprecated: bool,
+    pub tags: Vec<String>,
+    /// The parameters of the path item and of the operation.
+    pub parameters: Vec<ParameterDef>,
+    pub body: Option<BodyDef>,
+    /// The responses by status code, ending with the `default` response if any.
+    pub responses: Vec<ResponseDef>,
+    /// The name reserved for the type listing the responses of the operation.
+    pub response_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterDef {
+    pub name: String,
+    pub location: ParameterIn,
+    pub ty: TypeRef,
+    pub required: bool,
+    pub description: Option<String>,
+    pub style: Style,
+    pub explode: bool,
+    pub allow_reserved: bool,
+    /// The media type of parameters described with `content` rather than `schema`.
+    pub content_type: Option<String>,
+}
+
+/// The request body of an operation, for the preferred of its media types: JSON, then forms,
+/// then the first one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyDef {
+    pub content_type: String,
+    pub ty: TypeRef,
+    pub required: bool,
+    pub description: Option<String>,
+    /// The serialization of the properties of form bodies which have an `encoding`, by property
+    /// name. The other properties use the `form` style, exploded.
+    pub encodings: BTreeMap<String, EncodingDef>,
+}
+
+/// How a property of a form body is serialized, from its `encoding` in the media type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodingDef {
+    pub style: Style,
+    pub explode: bool,
+    pub allow_reserved: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseDef {
+    /// The status code of the response, or `None` for the `default` response.
+    pub status: Option<StatusCode>,
+    pub description: String,
+    /// The preferred media type of the response, JSON if any, and `None` without content.
+    pub content_type: Option<String>,
+    pub ty: Option<TypeRef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerDef {
+    /// The URL of the server with the default values of its variables, without a trailing slash.
+    pub url: String,
+    pub description: Option<String>,
+}
+
+/// A use of a type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeRef {
+    /// Any JSON value.
+    Any,
+    Boolean,
+    Int32,
+    Int64,
+    Float,
+    Double,
+    String,
+    DateTime,
+    Date,
+    Time,
+    Uuid,
+    Uri,
+    Named(String),
+    Array(Box<TypeRef>),
+    /// An object with arbitrary property names.
+    Map(Box<TypeRef>),
+    /// A type which also allows `null`.
+    Nullable(Box<TypeRef>),
+}
+
+impl TypeRef {
+    /// Collects the names of the named types the type is made of.
+    fn names(&self, names: &mut BTreeSet<String>) {
+        match self {
+            TypeRef::Named(name) => {
+                names.insert(name.clone());
+            }
+            TypeRef::Array(ty) | TypeRef::Map(ty) | TypeRef::Nullable(ty) => ty.names(names),
+            _ => {}
+        }
+    }
+
+    /// The type without `Nullable`.
+    pub fn non_null(&self) -> &TypeRef {
+        match self {
+            TypeRef::Nullable(ty) => ty.non_null(),
+            ty => ty,
+        }
+    }
+}
+
+impl Model {
+    /// The types of the schemas and the operations of a document.
+    pub fn new(spec: &OpenAPIV3) -> Model {
+        let components = spec.components.as_ref();
+        // the tags are removed once the operations are known, as they may use the variants
+        let mut model = components.map(Model::schemas).unwrap_or_default();
+        model.servers = spec
+            .servers
+            .iter()
+            .flatten()
+            .map(|server| ServerDef {
+                url: server
+                    .url_with(&BTreeMap::new())
+                    .trim_end_matches('/')
+                    .to_string(),
+                description: server.description.clone(),
+            })
+            .collect();
+        for (path, method, operation) in spec.operations() {
+            let name = operation
+                .operation_id
+                .clone()
+                .unwrap_or_else(|| format!("{method}_{path}").to_lowercase());
+            let response_type = model.reserve(&format!("{name}_response"));
+            let item = &spec.paths[path];
+            let parameters =
+                item.parameters_for(operation, components)
+                    .into_iter()
+                    .map(|parameter| {
+                        let content = parameter.content.iter().flatten().next();
+                        let schema = parameter.schema.as_ref().or_else(|| {
+                            content.and_then(|(_, media_type)| media_type.schema.as_ref())
+                        });
+                        let hint = format!("{name}_{}", parameter.name);
+                        ParameterDef {
+                            name: parameter.name.clone(),
+                            location: parameter._in,
+                            ty: schema.map_or(TypeRef::String, |schema| {
+                                model.type_ref(schema, &hint, components)
+                            }),
+                            required: parameter.required.unwrap_or(false)
+                                || parameter._in == ParameterIn::Path,
+                            description: parameter.description.clone(),
+                            style: parameter.style_or_default(),
+                            explode: parameter.explode_or_default(),
+                            allow_reserved: parameter.allow_reserved.unwrap_or(false),
+                            content_type: content.map(|(key, _)| key.to_string()),
+                        }
+                    })
+                    .collect();
+            let body = operation
+                .request_body
+                .as_ref()
+                .and_then(|body| body.resolve(components))
+                .and_then(|body| {
+                    let (key, media_type) = preferred(&body.content)?;
+                    Some(BodyDef {
+                        content_type: key.to_string(),
+                        ty: model.media_type(media_type, &format!("{name}_body"), components),
+                        required: body.required.unwrap_or(false),
+                        description: body.description.clone(),
+                        encodings: encodings(media_type),
+                    })
+                });
+            let responses = operation
+                .responses
+                .data
+                .iter()
+                .map(|(code, response)| (Some(code), response))
+                .chain(
+                    operation
+                        .responses
+                        .default
+                        .iter()
+                        .map(|response| (None, response)),
+                )
+                .filter_map(|(status, response)| {
+                    let response = response.resolve(components)?;
+                    let status_name = status.map_or("default".to_string(), StatusCode::to_string);
+                    let hint = format!("{name}_{status_name}_response");
+                    let content = response.content.as_ref().and_then(preferred);
+                    Some(ResponseDef {
+                        status: status.cloned(),
+                        description: response.description.clone(),
+                        content_type: content.map(|(key, _)| key.to_string()),
+                        ty: content
+                            .map(|(_, media_type)| model.media_type(media_type, &hint, components)),
+                    })
+                })
+                .collect();
+            model.operations.push(OperationDef {
+                name,
+                method,
+                path: path.to_string(),
+                summary: operation.summary.clone(),
+                description: operation.description.clone(),
+                deprecated: operation.deprecated.unwrap_or(false),
+                tags: operation.tags.clone().unwrap_or_default(),
+                parameters,
+                body,
+                responses,
+                response_type,
+            });
+        }
+        model.remove_tags();
+        model
+    }
+
+    fn media_type(
+        &mut self,
+        media_type: &MediaType,
+        hint: &str,
+        components: Option<&Components>,
+    ) -> TypeRef {
+        match &media_type.schema {
+            Some(schema) => self.type_ref(schema, hint, components),
+            None => TypeRef::Any,
+        }
+    }
+
+    /// The types of the schemas of the components, along with the inline types they define.
+    pub fn from_components(components: &Components) -> Model {
+        let mut model = Model::schemas(components);
+        model.remove_tags();
+        model
+    }
+
+    fn schemas(components: &Components) -> Model {
+        let mut model = Model::default();
+        let schemas = components.schemas.iter().flatten().collect::<Vec<_>>();
+        for (name, _) in &schemas {
+            let type_name = model.reserve(name);
+            model.schemas.insert(name.to_string(), type_name);
+        }
+        for (name, schema) in schemas {
+            let type_name = model.schemas[name].clone();
+            match schema {
+                Referenceable::Data(schema) => {
+                    model.define(type_name, Some(name), schema, Some(components))
+                }
+                reference => {
+                    let ty = model.type_ref(reference, &type_name, Some(components));
+                    model.push(type_name, Some(name), None, TypeKind::Alias(ty));
+                }
+            }
+        }
+        model
+    }
+
+    /// Looks up a type by name.
+    pub fn get(&self, name: &str) -> Option<&TypeDef> {
+        self.types.iter().find(|ty| ty.name == name)
+    }
+
+    /// The type of a schema of `components/schemas`, by component name.
+    pub fn component(&self, name: &str) -> Option<&TypeDef> {
+        self.get(self.schemas.get(name)?)
+    }
+
+    /// The use of a schema, defining named types for inline objects, enumerations and unions,
+    /// named after the hint.
+    pub(crate) fn type_ref(
+        &mut self,
+        schema: &Referenceable<Schema>,
+        hint: &str,
+        components: Option<&Components>,
+    ) -> TypeRef {
+        let schema = match schema {
+            Referenceable::Reference(reference) => {
+                return reference
+                    .component_name("schemas")
+                    .and_then(|name| self.schemas.get(&name))
+                    .map_or(TypeRef::Any, |name| TypeRef::Named(name.clone()));
+            }
+            Referenceable::Data(schema) => schema,
+        };
+        let ty = match is_named(schema) {
+            true => {
+                let name = self.reserve(hint);
+                self.define(name.clone(), None, schema, components);
+                TypeRef::Named(name)
+            }
+            false => self.unnamed(schema, hint, components),
+        };
+        match schema.nullable.unwrap_or(false) {
+            true => TypeRef::Nullable(Box::new(ty)),
+            false => ty,
+        }
+    }
+
+    /// A free name for a type, based on the hint.
+    pub(crate) fn reserve(&mut self, hint: &str) -> String {
+        let base = match pascal_case(hint) {
+            name if name.is_empty() => "Type".to_string(),
+            name => name,
+        };
+        let name = (1..)
+            .map(|index| match index {
+                1 => base.clone(),
+                index => format!("{base}{index}"),
+            })
+            .find(|name| !self.names.contains(name))
+            .expect("the iterator is infinite");
+        self.names.insert(name.clone());
+        name
+    }
+
+    fn push(
+        &mut self,
+        name: String,
+        component: Option<&str>,
+        schema: Option<&Schema>,
+        kind: TypeKind,
+    ) -> usize {
+        self.types.push(TypeDef {
+            name,
+            component: component.map(str::to_string),
+            description: schema
+                .and_then(|schema| schema.description.clone().or_else(|| schema.title.clone())),
+            deprecated: schema.is_some_and(|schema| schema.deprecated.unwrap_or(false)),
+            kind,
+        });
+        self.types.len() - 1
+    }
+
+    /// Defines a named type for a schema. The type is added before the inline types it defines.
+    fn define(
+        &mut self,
+        name: String,
+        component: Option<&str>,
+        schema: &Schema,
+        components: Option<&Components>,
+    ) {
+        let index = self.push(
+            name.clone(),
+            component,
+            Some(schema),
+            TypeKind::Alias(TypeRef::Any),
+        );
+        let kind = if let Some(values) = string_enum(schema) {
+            TypeKind::Enum { values }
+        } else if let Some(schemas) = schema
+            .one_of
+            .as_ref()
+            .or(schema.any_of.as_ref())
+            .filter(|schemas| !schemas.is_empty())
+        {
+            self.union(&name, schema, schemas, components)
+        } else if schema.properties.is_some() || schema.all_of.is_some() {
+            match schema.all_of.as_deref() {
+                Some([single]) if schema.properties.is_none() => {
+                    TypeKind::Alias(self.type_ref(single, &name, components))
+                }
+                _ => self.object(&name, schema, components),
+            }
+        } else {
+            TypeKind::Alias(self.unnamed(schema, &name, components))
+        };
+        self.types[index].kind = kind;
+    }
+
+    fn union(
+        &mut self,
+        name: &str,
+        schema: &Schema,
+        schemas: &[Referenceable<Schema>],
+        components: Option<&Components>,
+    ) -> TypeKind {
+        let discriminator = schema.discriminator.as_ref();
+        let mut variants: Vec<VariantDef> = vec![];
+        for (index, variant) in schemas.iter().enumerate() {
+            let ty = self.type_ref(variant, &format!("{name}Variant{}", index + 1), components);
+            let tag = match (discriminator, variant) {
+                (Some(discriminator), Referenceable::Reference(reference)) => {
+                    let component = reference.component_name("schemas");
+                    discriminator
+                        .mapping
+                        .iter()
+                        .flatten()
+                        .find(|(_, target)| {
+                            **target == reference._ref
+                                || Some(target.as_str()) == component.as_deref()
+                        })
+                        .map(|(value, _)| value.clone())
+                        .or(component)
+                }
+                _ => None,
+            };
+            let base = variant_name(&ty).unwrap_or_else(|| format!("Variant{}", index + 1));
+            let name = (1..)
+                .map(|index| match index {
+                    1 => base.clone(),
+                    index => format!("{base}{index}"),
+                })
+                .find(|name| variants.iter().all(|variant| variant.name != *name))
+                .expect("the iterator is infinite");
+            variants.push(VariantDef { name, tag, ty });
+        }
+        // a union can only be tagged when every variant has a tag value
+        let tagged = variants.iter().all(|variant| variant.tag.is_some());
+        TypeKind::Union {
+            tag: discriminator
+                .filter(|_| tagged)
+                .map(|discriminator| discriminator.property_name.clone()),
+            variants,
+        }
+    }
+
+    fn object(&mut self, name: &str, schema: &Schema, components: Option<&Components>) -> TypeKind {
+        let mut required = BTreeSet::new();
+        collect_required(schema, components, &mut required, 0);
+        let mut fields = vec![];
+        for (property, value) in schema.all_properties(components) {
+            let resolved = value.resolve(components);
+            let flag = |get: fn(&Schema) -> Option<bool>| resolved.and_then(get).unwrap_or(false);
+            let (read_only, write_only, deprecated) = (
+                flag(|schema| schema.read_only),
+                flag(|schema| schema.write_only),
+                flag(|schema| schema.deprecated),
+            );
+            // the description of a referenced schema belongs to its type
+            let description = match value {
+                Referenceable::Data(schema) => schema.description.clone(),
+                Referenceable::Reference(_) => None,
+            };
+            fields.push(FieldDef {
+                name: property.to_string(),
+                ty: self.type_ref(value, &format!("{name}_{property}"), components),
+                required: required.contains(property),
+                description,
+                deprecated,
+                read_only,
+                write_only,
+            });
+        }
+        let additional_properties = match &schema.additional_properties {
+            Some(AdditionalProperties::Schema(value)) => {
+                Some(self.type_ref(value, &format!("{name}Value"), components))
+            }
+            Some(AdditionalProperties::Allowed(true)) => Some(TypeRef::Any),
+            _ => None,
+        };
+        TypeKind::Struct {
+            fields,
+            additional_properties,
+        }
+    }
+
+    /// The use of a schema which does not need a named type.
+    fn unnamed(&mut self, schema: &Schema, hint: &str, components: Option<&Components>) -> TypeRef {
+        match &schema._type {
+            Some(Type::Boolean) => TypeRef::Boolean,
+            Some(Type::Integer) => match schema.format {
+                Some(Format::Int32) => TypeRef::Int32,
+                _ => TypeRef::Int64,
+            },
+            Some(Type::Number) => match schema.format {
+                Some(Format::Float) => TypeRef::Float,
+                _ => TypeRef::Double,
+            },
+            Some(Type::String) => match &schema.format {
+                Some(Format::DateTime) => TypeRef::DateTime,
+                Some(Format::Date) => TypeRef::Date,
+                Some(Format::Time) => TypeRef::Time,
+                Some(Format::Uuid) => TypeRef::Uuid,
+                Some(Format::Uri) | Some(Format::Iri) => TypeRef::Uri,
+                _ => TypeRef::String,
+            },
+            Some(Type::Array) => match &schema.items {
+                Some(items) => TypeRef::Array(Box::new(self.type_ref(
+                    items,
+                    &format!("{hint}Item"),
+                    components,
+                ))),
+                None => TypeRef::Array(Box::new(TypeRef::Any)),
+            },
+            Some(Type::Object) => {
+                match &schema.additional_properties {
+                    Some(AdditionalProperties::Schema(value)) => TypeRef::Map(Box::new(
+                        self.type_ref(value, &format!("{hint}Value"), components),
+                    )),
+                    _ => TypeRef::Map(Box::new(TypeRef::Any)),
+                }
+            }
+            Some(Type::Null) | Some(Type::Other(_)) | None => TypeRef::Any,
+        }
+    }
+
+    /// Removes the tag property from the variants of tagged unions, as it is written and read by
+    /// the union itself. The variants which are also used on their own, e.g. by a field or an
+    /// operation, keep their tag and the unions get a copy of them without it instead, named
+    /// after the union, e.g. `PetCat`.
+    pub(crate) fn remove_tags(&mut self) {
+        let mut used = BTreeSet::new();
+        // the variants of unions with different tags are copied for each union
+        let mut tags = BTreeMap::<&str, BTreeSet<&str>>::new();
+        for ty in &self.types {
+            match &ty.kind {
+                TypeKind::Struct {
+                    fields,
+                    additional_properties,
+                } => {
+                    for ty in fields
+                        .iter()
+                        .map(|field| &field.ty)
+                        .chain(additional_properties)
+                    {
+                        ty.names(&mut used);
+                    }
+                }
+                TypeKind::Union {
+                    tag: None,
+                    variants,
+                } => {
+                    for variant in variants {
+                        variant.ty.names(&mut used);
+                    }
+                }
+                TypeKind::Union {
+                    tag: Some(tag),
+                    variants,
+                } => {
+                    for variant in variants {
+                        if let TypeRef::Named(name) = &variant.ty {
+                            tags.entry(name).or_default().insert(tag);
+                        }
+                    }
+                }
+                TypeKind::Alias(ty) => ty.names(&mut used),
+                TypeKind::Enum { .. } => {}
+            }
+        }
+        used.extend(
+            tags.into_iter()
+                .filter(|(_, tags)| tags.len() > 1)
+                .map(|(name, _)| name.to_string()),
+        );
+        for operation in &self.operations {
+            let parameters = operation.parameters.iter().map(|parameter| &parameter.ty);
+            let body = operation.body.iter().map(|body| &body.ty);
+            let responses = operation
+                .responses
+                .iter()
+                .filter_map(|response| response.ty.as_ref());
+            for ty in parameters.chain(body).chain(responses) {
+                ty.names(&mut used);
+            }
+        }
+
+        for index in 0..self.types.len() {
+            let TypeKind::Union {
+                tag: Some(tag),
+                variants,
+            } = &self.types[index].kind
+            else {
+                continue;
+            };
+            let (union, tag, mut variants) = (
+                self.types[index].name.clone(),
+                tag.clone(),
+                variants.clone(),
+            );
+            for variant in &mut variants {
+                let TypeRef::Named(name) = &variant.ty else {
+                    continue;
+                };
+                let Some(position) = self.types.iter().position(|ty| ty.name == *name) else {
+                    continue;
+                };
+                let mut ty = self.types[position].clone();
+                let TypeKind::Struct { fields, .. } = &mut ty.kind else {
+                    continue;
+                };
+                fields.retain(|field| field.name != tag);
+                if used.contains(name) {
+                    ty.name = self.reserve(&format!("{union}_{name}"));
+                    ty.component = None;
+                    variant.ty = TypeRef::Named(ty.name.clone());
+                    self.types.push(ty);
+                } else {
+                    self.types[position] = ty;
+                }
+            }
+            if let TypeKind::Union { variants: old, .. } = &mut self.types[index].kind {
+                *old = variants;
+            }
+        }
+    }
+}
+
+/// The preferred media type of some content: JSON, then forms, then the first valid one.
+fn preferred(content: &Map<MediaRange, MediaType>) -> Option<(&MediaRange, &MediaType)> {
+    let rank = |key: &MediaRange| {
+        let Some(media_type) = key.media_type() else {
+            return 4;
+        };
+        let essence = media_type.essence().to_string().to_ascii_lowercase();
+        match essence.as_str() {
+            _ if media_type.subty() == "json"
+                || media_type.suffix().is_some_and(|suffix| suffix == "json") =>
+            {
+                0
+            }
+            "application/x-www-form-urlencoded" => 1,
+            "multipart/form-data" => 2,
+            _ => 3,
+        }
+    };
+    content.iter().min_by_key(|(key, _)| rank(key))
+}
+
+/// The serialization of the properties of a form body which differ from the default one.
+fn encodings(media_type: &MediaType) -> BTreeMap<String, EncodingDef> {
+    media_type
+        .encoding
+        .iter()
+        .flatten()
+        .map(|(name, encoding)| {
+            let style = encoding.style.clone().unwrap_or(Style::Form);
+            let encoding = EncodingDef {
+                explode: encoding.explode.unwrap_or_else(|| style.default_explode()),
+                allow_reserved: encoding.allow_reserved.unwrap_or(false),
+                style,
+            };
+            (name.clone(), encoding)
+        })
+        .filter(|(_, encoding)| {
+            *encoding
+                != EncodingDef {
+                    style: Style::Form,
+                    explode: true,
+                    allow_reserved: false,
+                }
+        })
+        .collect()
+}
+
+/// Whether a schema needs a named type.
+pub(crate) fn is_named(schema: &Schema) -> bool {
+    string_enum(schema).is_some()
+        || schema.properties.is_some()
+        || schema.all_of.is_some()
+        || schema
+            .one_of
+            .as_ref()
+            .is_some_and(|schemas| !schemas.is_empty())
+        || schema
+            .any_of
+            .as_ref()
+            .is_some_and(|schemas| !schemas.is_empty())
+}
+
+/// The values of the enumeration of a schema, if they are all strings.
+fn string_enum(schema: &Schema) -> Option<Vec<String>> {
+    let values = schema._enum.as_ref().filter(|values| !values.is_empty())?;
+    values
+        .iter()
+        .filter(|value| !value.is_null())
+        .map(|value| match value {
+            Value::String(value) => Some(value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn collect_required(
+    schema: &Schema,
+    components: Option<&Components>,
+    required: &mut BTreeSet<String>,
+    depth: usize,
+) {
+    required.extend(schema.required.iter().flatten().cloned());
+    if depth < 32 {
+        for schema in schema.all_of.iter().flatten() {
+            if let Some(schema) = schema.resolve(components) {
+                collect_required(schema, components, required, depth + 1);
+            }
+        }
+    }
+}
+
+/// The name of a union variant holding a type.
+fn variant_name(ty: &TypeRef) -> Option<String> {
+    let name = match ty {
+        TypeRef::Named(name) => name,
+        TypeRef::Boolean => "Boolean",
+        TypeRef::Int32 | TypeRef::Int64 => "Integer",
+        TypeRef::Float | TypeRef::Double => "Number",
+        TypeRef::String => "String",
+        TypeRef::DateTime => "DateTime",
+        TypeRef::Date => "Date",
+        TypeRef::Time => "Time",
+        TypeRef::Uuid => "Uuid",
+        TypeRef::Uri => "Uri",
+        TypeRef::Array(_) => "Array",
+        TypeRef::Map(_) => "Map",
+        TypeRef::Nullable(ty) => return variant_name(ty),
+        TypeRef::Any => return None,
+    };
+    Some(name.to_string())
+}
@@ -0,0 +1,209 @@
+mod ir;
+mod rust;
+mod rust_client;
+mod rust_server;
+mod typescript;
+pub use ir::*;
+pub use rust::*;
+pub use typescript::*;
+
+use std::collections::BTreeSet;
+
+use crate::StatusCode;
+
+/// The words of a name, split at non alphanumeric characters and at the start of capitalized
+/// words, e.g. `HTTPServer_id` is `HTTP`, `Server` and `id`.
+fn words(name: &str) -> Vec<String> {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+        let next = chars.get(index + 1);
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_numeric()
+                    || previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase())
+            });
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// A name in PascalCase, keeping the case of the letters after the first of each word, e.g.
+/// `pet-store` is `PetStore`. Names starting with a digit are prefixed with `_`.
+pub(crate) fn pascal_case(name: &str) -> String {
+    let mut result = String::new();
+    for word in name.split(|c: char| !c.is_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.push_str(chars.as_str());
+        }
+    }
+    match result.starts_with(|c: char| c.is_numeric()) {
+        true => format!("_{result}"),
+        false => result,
+    }
+}
+
+/// A name in snake_case, e.g. `petType` is `pet_type`. Names starting with a digit are prefixed
+/// with `_`.
+pub(crate) fn snake_case(name: &str) -> String {
+    let result = words(name)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    match result.starts_with(|c: char| c.is_numeric()) {
+        true => format!("_{result}"),
+        false => result,
+    }
+}
+
+/// A name in camelCase, e.g. `find-pets` is `findPets`.
+pub(crate) fn camel_case(name: &str) -> String {
+    let result = words(name)
+        .iter()
+        .enumerate()
+        .map(|(index, word)| match index {
+            0 => word.to_lowercase(),
+            _ => {
+                let mut chars = word.chars();
+                let first = chars.next().into_iter().flat_map(char::to_uppercase);
+                first.chain(chars.flat_map(char::to_lowercase)).collect()
+            }
+        })
+        .collect::<String>();
+    match result.starts_with(|c: char| c.is_numeric()) {
+        true => format!("_{result}"),
+        false => result,
+    }
+}
+
+/// The name, or the name with a numeric suffix if it is taken.
+fn unique(name: String, taken: &mut BTreeSet<String>) -> String {
+    let name = (1..)
+        .map(|index| match index {
+            1 => name.clone(),
+            index => format!("{name}_{index}"),
+        })
+        .find(|name| !taken.contains(name))
+        .expect("the iterator is infinite");
+    taken.insert(name.clone());
+    name
+}
+
+/// How a body is sent or received, based on its media type.
+#[derive(Clone, Copy, PartialEq)]
+enum Encoding {
+    Json,
+    Form,
+    Multipart,
+    Text,
+    Bytes,
+}
+
+impl Encoding {
+    pub(crate) fn of(content_type: &str) -> Encoding {
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match essence.as_str() {
+            "application/x-www-form-urlencoded" => Encoding::Form,
+            "multipart/form-data" => Encoding::Multipart,
+            _ if essence.ends_with("/json") || essence.ends_with("+json") => Encoding::Json,
+            _ if essence.starts_with("text/") => Encoding::Text,
+            _ => Encoding::Bytes,
+        }
+    }
+
+    /// The encoding of a response body, which is text for forms since decoding them needs their
+    /// schema.
+    pub(crate) fn of_response(content_type: &str) -> Encoding {
+        match Encoding::of(content_type) {
+            Encoding::Form => Encoding::Text,
+            encoding => encoding,
+        }
+    }
+}
+
+/// The name of the variant of a response type for a status code, e.g. `NotFound` for `404`,
+/// `Status4XX` for `4XX` and `Default` for the `default` response.
+fn status_variant(status: Option<&StatusCode>) -> String {
+    let code = match status {
+        None => return "Default".to_string(),
+        Some(StatusCode::Range(class)) => return format!("Status{class}XX"),
+        Some(StatusCode::Code(code)) => *code,
+    };
+    let name = match code {
+        200 => "Ok",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "NoContent",
+        301 => "MovedPermanently",
+        302 => "Found",
+        303 => "SeeOther",
+        304 => "NotModified",
+        307 => "TemporaryRedirect",
+        308 => "PermanentRedirect",
+        400 => "BadRequest",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "NotFound",
+        405 => "MethodNotAllowed",
+        406 => "NotAcceptable",
+        409 => "Conflict",
+        410 => "Gone",
+        412 => "PreconditionFailed",
+        415 => "UnsupportedMediaType",
+        422 => "UnprocessableEntity",
+        429 => "TooManyRequests",
+        500 => "InternalServerError",
+        501 => "NotImplemented",
+        502 => "BadGateway",
+        503 => "ServiceUnavailable",
+        504 => "GatewayTimeout",
+        code => return format!("Status{code}"),
+    };
+    name.to_string()
+}
+
+/// Whether the variant of a response also holds the actual status code, which is the case for
+/// ranges and the `default` response.
+fn holds_status(response: &ir::ResponseDef) -> bool {
+    !matches!(response.status, Some(StatusCode::Code(_)))
+}
+
+/// A name which is not the name of a type of the model.
+fn free_name(model: &ir::Model, base: &str) -> String {
+    (1..)
+        .map(|index| match index {
+            1 => base.to_string(),
+            index => format!("{base}{index}"),
+        })
+        .find(|name| {
+            model.get(name).is_none()
+                && model
+                    .operations
+                    .iter()
+                    .all(|operation| operation.response_type != *name)
+        })
+        .expect("the iterator is infinite")
+}
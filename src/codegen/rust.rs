@@ -0,0 +1,341 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{holds_status, pascal_case, snake_case, status_variant, unique, Encoding};
+use crate::{FieldDef, Model, OperationDef, ParameterDef, TypeDef, TypeKind, TypeRef};
+
+/// The keywords which cannot be used as identifiers, which are prefixed with `_` like in this
+/// crate, e.g. `_type`.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// The names of the local variables of the generated methods, which parameters must not shadow.
+const LOCALS: &[&str] = &[
+    "self",
+    "body",
+    "url",
+    "query",
+    "cookies",
+    "request",
+    "response",
+    "status",
+    "bytes",
+    "form",
+    "parts",
+    "fields",
+    "content_type",
+];
+
+/// Generates Rust code from a [Model].
+///
+/// Objects become structs deriving `serde::Serialize` and `serde::Deserialize`, with `Option`
+/// fields for properties which are not required or are nullable. String enumerations become
+/// enums, unions with a discriminator become internally tagged enums and other unions become
+/// untagged enums. Formats map to `chrono`, `uuid` and `url` types, so the generated code needs
+/// these crates with their `serde` feature, along with `serde` and `serde_json`.
+///
+/// The output is meant to be written by a build script and included with `include!`.
+#[derive(Debug, Clone, Default)]
+pub struct RustGenerator {
+    /// Derives added to every type, e.g. `schemars::JsonSchema`.
+    pub derives: Vec<String>,
+    /// Whether [RustGenerator::server] generates a trait per tag, named after the tag, instead of
+    /// a single `Api` trait. Operations go to the trait of their first tag, and untagged ones to
+    /// `DefaultApi`.
+    pub trait_per_tag: bool,
+}
+
+impl RustGenerator {
+    /// The definitions of the types of the model, followed by an enum per operation with a
+    /// variant per response, which is used by the client and the server.
+    pub fn types(&self, model: &Model) -> String {
+        let boxed = boxed_members(model);
+        let mut out = String::from("// Generated from an OpenAPI document, do not edit.\n");
+        for ty in &model.types {
+            out.push('\n');
+            self.type_def(&mut out, ty, &boxed);
+        }
+        for operation in &model.operations {
+            out.push('\n');
+            response_type(&mut out, operation);
+        }
+        out
+    }
+
+    fn type_def(&self, out: &mut String, ty: &TypeDef, boxed: &BTreeSet<(String, String)>) {
+        doc_comment(out, "", ty.description.as_deref());
+        if ty.deprecated {
+            out.push_str("///\n/// Deprecated.\n");
+        }
+        let is_boxed = |member: &str| boxed.contains(&(ty.name.clone(), member.to_string()));
+        match &ty.kind {
+            TypeKind::Struct {
+                fields,
+                additional_properties,
+            } => {
+                self.derive(out, &[]);
+                out.push_str(&format!("pub struct {} {{\n", ty.name));
+                let mut names = BTreeSet::new();
+                for field in fields {
+                    let name = unique(field_name(&field.name), &mut names);
+                    struct_field(out, field, &name, is_boxed(&field.name));
+                }
+                if let Some(value) = additional_properties {
+                    out.push_str(&format!(
+                        "    #[serde(flatten)]\n    pub {}: std::collections::BTreeMap<String, {}>,\n",
+                        unique("additional_properties".to_string(), &mut names),
+                        rust_type(value)
+                    ));
+                }
+                out.push_str("}\n");
+            }
+            TypeKind::Enum { values } => {
+                self.derive(out, &["Copy", "Eq", "Hash"]);
+                out.push_str(&format!("pub enum {} {{\n", ty.name));
+                let mut names = BTreeSet::new();
+                for value in values {
+                    let name = match pascal_case(value) {
+                        name if name.is_empty() => "Empty".to_string(),
+                        name => name,
+                    };
+                    let name = unique(name, &mut names);
+                    out.push_str(&format!("    #[serde(rename = {value:?})]\n    {name},\n"));
+                }
+                out.push_str("}\n");
+            }
+            TypeKind::Union { tag, variants } => {
+                self.derive(out, &[]);
+                match tag {
+                    Some(tag) => out.push_str(&format!("#[serde(tag = {tag:?})]\n")),
+                    None => out.push_str("#[serde(untagged)]\n"),
+                }
+                out.push_str(&format!("pub enum {} {{\n", ty.name));
+                for variant in variants {
+                    if let (Some(_), Some(value)) = (tag, &variant.tag) {
+                        out.push_str(&format!("    #[serde(rename = {value:?})]\n"));
+                    }
+                    let mut inner = rust_type(&variant.ty);
+                    if is_boxed(&variant.name) {
+                        inner = format!("Box<{inner}>");
+                    }
+                    out.push_str(&format!("    {}({inner}),\n", variant.name));
+                }
+                out.push_str("}\n");
+            }
+            TypeKind::Alias(target) => {
+                out.push_str(&format!("pub type {} = {};\n", ty.name, rust_type(target)));
+            }
+        }
+    }
+
+    fn derive(&self, out: &mut String, extra: &[&str]) {
+        let mut derives = vec!["Debug", "Clone"];
+        derives.extend(extra);
+        derives.push("PartialEq");
+        for derive in &self.derives {
+            if !derives.contains(&derive.as_str()) {
+                derives.push(derive);
+            }
+        }
+        derives.extend(["serde::Serialize", "serde::Deserialize"]);
+        out.push_str(&format!("#[derive({})]\n", derives.join(", ")));
+    }
+}
+
+/// An enum with a variant per response of an operation, holding its status code when it is a
+/// range or the default response, and its body.
+fn response_type(out: &mut String, operation: &OperationDef) {
+    out.push_str(&format!(
+        "/// The responses of `{}`.\n#[derive(Debug, Clone, PartialEq)]\npub enum {} {{\n",
+        operation.name, operation.response_type
+    ));
+    for response in &operation.responses {
+        doc_comment(out, "    ", Some(&response.description));
+        let mut fields = vec![];
+        if holds_status(response) {
+            fields.push("u16".to_string());
+        }
+        if let (Some(content_type), Some(ty)) = (&response.content_type, &response.ty) {
+            fields.push(Encoding::of_response(content_type).rust_type(ty));
+        }
+        let variant = status_variant(response.status.as_ref());
+        match fields.is_empty() {
+            true => out.push_str(&format!("    {variant},\n")),
+            false => out.push_str(&format!("    {variant}({}),\n", fields.join(", "))),
+        }
+    }
+    out.push_str("}\n");
+}
+
+fn struct_field(out: &mut String, field: &FieldDef, name: &str, boxed: bool) {
+    doc_comment(out, "    ", field.description.as_deref());
+    let mut ty = rust_type(field.ty.non_null());
+    if boxed {
+        ty = format!("Box<{ty}>");
+    }
+    if !field.required || matches!(field.ty, TypeRef::Nullable(_)) {
+        ty = format!("Option<{ty}>");
+    }
+    let mut attributes = vec![];
+    if name != field.name {
+        attributes.push(format!("rename = {:?}", field.name));
+    }
+    if !field.required {
+        attributes.push("default".to_string());
+        attributes.push("skip_serializing_if = \"Option::is_none\"".to_string());
+    }
+    if !attributes.is_empty() {
+        out.push_str(&format!("    #[serde({})]\n", attributes.join(", ")));
+    }
+    out.push_str(&format!("    pub {name}: {ty},\n"));
+}
+
+/// The Rust type of a use of a type.
+pub(crate) fn rust_type(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::Any => "serde_json::Value".to_string(),
+        TypeRef::Boolean => "bool".to_string(),
+        TypeRef::Int32 => "i32".to_string(),
+        TypeRef::Int64 => "i64".to_string(),
+        TypeRef::Float => "f32".to_string(),
+        TypeRef::Double => "f64".to_string(),
+        TypeRef::String => "String".to_string(),
+        TypeRef::DateTime => "chrono::DateTime<chrono::Utc>".to_string(),
+        TypeRef::Date => "chrono::NaiveDate".to_string(),
+        TypeRef::Time => "chrono::NaiveTime".to_string(),
+        TypeRef::Uuid => "uuid::Uuid".to_string(),
+        TypeRef::Uri => "url::Url".to_string(),
+        TypeRef::Named(name) => name.clone(),
+        TypeRef::Array(items) => format!("Vec<{}>", rust_type(items)),
+        TypeRef::Map(values) => {
+            format!("std::collections::BTreeMap<String, {}>", rust_type(values))
+        }
+        TypeRef::Nullable(ty) => format!("Option<{}>", rust_type(ty)),
+    }
+}
+
+/// The identifier of a field or a variable, in snake_case.
+pub(crate) fn field_name(name: &str) -> String {
+    match snake_case(name) {
+        name if name.is_empty() => "field".to_string(),
+        name if KEYWORDS.contains(&name.as_str()) => format!("_{name}"),
+        name => name,
+    }
+}
+
+/// The Rust identifiers of the parameters of an operation, in the order of [OperationDef::parameters].
+pub(crate) fn parameter_names(operation: &OperationDef) -> Vec<String> {
+    let mut taken = LOCALS.iter().map(|name| name.to_string()).collect();
+    operation
+        .parameters
+        .iter()
+        .map(|parameter| unique(field_name(&parameter.name), &mut taken))
+        .collect()
+}
+
+/// The Rust type of a parameter.
+pub(crate) fn parameter_type(parameter: &ParameterDef) -> String {
+    let ty = rust_type(parameter.ty.non_null());
+    match parameter.required {
+        true => ty,
+        false => format!("Option<{ty}>"),
+    }
+}
+
+impl Encoding {
+    /// The Rust type of a body with this encoding, which is the type of its schema for JSON and
+    /// forms.
+    pub(crate) fn rust_type(&self, ty: &TypeRef) -> String {
+        match self {
+            Encoding::Json | Encoding::Form => rust_type(ty),
+            Encoding::Multipart => "oas::EncodedBody".to_string(),
+            Encoding::Text => "String".to_string(),
+            Encoding::Bytes => "Vec<u8>".to_string(),
+        }
+    }
+
+    /// The Rust type of a request body with this encoding, which is also the type of its schema
+    /// for multipart bodies.
+    pub(crate) fn request_type(&self, ty: &TypeRef) -> String {
+        match self {
+            Encoding::Multipart => rust_type(ty),
+            encoding => encoding.rust_type(ty),
+        }
+    }
+}
+
+/// Writes a description as doc comments.
+pub(crate) fn doc_comment(out: &mut String, indent: &str, description: Option<&str>) {
+    for line in description
+        .into_iter()
+        .flat_map(|description| description.trim().lines())
+    {
+        match line.trim_end() {
+            "" => out.push_str(&format!("{indent}///\n")),
+            line => out.push_str(&format!("{indent}/// {line}\n")),
+        }
+    }
+}
+
+/// The fields and variants which must be boxed, as pairs of type and member names, because they
+/// hold a type which holds the type itself without an indirection such as a `Vec`.
+fn boxed_members(model: &Model) -> BTreeSet<(String, String)> {
+    let mut edges: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+    for ty in &model.types {
+        let members: Vec<(&str, &TypeRef)> = match &ty.kind {
+            TypeKind::Struct { fields, .. } => fields
+                .iter()
+                .map(|field| (field.name.as_str(), &field.ty))
+                .collect(),
+            TypeKind::Union { variants, .. } => variants
+                .iter()
+                .map(|variant| (variant.name.as_str(), &variant.ty))
+                .collect(),
+            TypeKind::Alias(target) => vec![("", target)],
+            TypeKind::Enum { .. } => vec![],
+        };
+        let members = members
+            .into_iter()
+            .filter_map(|(member, target)| match target.non_null() {
+                TypeRef::Named(target) => Some((member, target.as_str())),
+                _ => None,
+            })
+            .collect();
+        edges.insert(&ty.name, members);
+    }
+    let reaches = |from: &str, to: &str| {
+        let mut stack = vec![from];
+        let mut seen = BTreeSet::new();
+        while let Some(name) = stack.pop() {
+            if name == to {
+                return true;
+            }
+            if seen.insert(name) {
+                stack.extend(
+                    edges
+                        .get(name)
+                        .into_iter()
+                        .flatten()
+                        .map(|(_, target)| *target),
+                );
+            }
+        }
+        false
+    };
+    let mut boxed = BTreeSet::new();
+    for ty in &model.types {
+        if matches!(ty.kind, TypeKind::Alias(_)) {
+            continue;
+        }
+        for (member, target) in edges.get(ty.name.as_str()).into_iter().flatten() {
+            if reaches(target, &ty.name) {
+                boxed.insert((ty.name.clone(), member.to_string()));
+            }
+        }
+    }
+    boxed
+}
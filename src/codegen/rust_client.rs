@@ -0,0 +1,429 @@
+use super::rust::{doc_comment, field_name, parameter_names, parameter_type};
+use super::{free_name, holds_status, status_variant, unique, Encoding};
+use crate::{BodyDef, Model, OperationDef, ParameterDef, ParameterIn, RustGenerator, StatusCode};
+
+/// The names of the methods of the generated client, besides the ones of the operations.
+const METHODS: &[&str] = &["new", "with_http_client", "for_server", "base_url"];
+
+impl RustGenerator {
+    /// An async client with a method per operation, named after its `operationId`, which sends
+    /// requests with `reqwest`. Parameters and the properties of form bodies are serialized
+    /// according to their `style` and `explode`, and responses are decoded into an enum with a
+    /// variant per status code.
+    ///
+    /// The client refers to the types generated by [RustGenerator::types], including the response
+    /// types of the operations, which must be in the same module, and needs the `oas`, `reqwest`
+    /// and `serde_json` crates. JSON, form and multipart request bodies are typed, multipart
+    /// bodies being sent with [MultipartBody::new](crate::MultipartBody::new), and other bodies
+    /// are text or bytes. Multipart responses are [EncodedBody](crate::EncodedBody) values.
+    pub fn client(&self, model: &Model) -> String {
+        let client = free_name(model, "Client");
+        let error = free_name(model, "ClientError");
+        let mut out = String::from("// Generated from an OpenAPI document, do not edit.\n");
+
+        out.push_str(&format!(
+            "
+/// The errors of [{client}].
+#[derive(Debug)]
+pub enum {error} {{
+    Request(reqwest::Error),
+    Json(serde_json::Error),
+    /// The status code of the response is not described by the document. Holds the status code
+    /// and the body of the response.
+    UnexpectedStatus(u16, Vec<u8>),
+}}
+
+impl std::fmt::Display for {error} {{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{
+        match self {{
+            {error}::Request(error) => write!(f, \"request failed: {{error}}\"),
+            {error}::Json(error) => write!(f, \"invalid JSON: {{error}}\"),
+            {error}::UnexpectedStatus(status, _) => write!(f, \"unexpected status code {{status}}\"),
+        }}
+    }}
+}}
+
+impl std::error::Error for {error} {{}}
+
+impl From<reqwest::Error> for {error} {{
+    fn from(error: reqwest::Error) -> Self {{
+        {error}::Request(error)
+    }}
+}}
+
+impl From<serde_json::Error> for {error} {{
+    fn from(error: serde_json::Error) -> Self {{
+        {error}::Json(error)
+    }}
+}}
+
+/// A client of the API.
+#[derive(Debug, Clone)]
+pub struct {client} {{
+    base_url: String,
+    http: reqwest::Client,
+}}
+
+impl {client} {{
+"
+        ));
+        let servers = model
+            .servers
+            .iter()
+            .map(|server| format!("{:?}", server.url))
+            .collect::<Vec<_>>();
+        out.push_str(&format!(
+            "    /// The URLs of the servers of the document, with the default values of their variables.
+    pub const SERVERS: &'static [&'static str] = &[{}];
+
+    /// A client sending requests to the given base URL, e.g. one of [{client}::SERVERS].
+    pub fn new(base_url: impl Into<String>) -> Self {{
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }}
+
+    /// A client sending requests to the given base URL with a configured `reqwest` client.
+    pub fn with_http_client(base_url: impl Into<String>, http: reqwest::Client) -> Self {{
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        {client} {{ base_url, http }}
+    }}
+
+    /// A client sending requests to a server of the document, by index in [{client}::SERVERS].
+    pub fn for_server(index: usize) -> Option<Self> {{
+        Self::SERVERS.get(index).map(|url| Self::new(*url))
+    }}
+
+    pub fn base_url(&self) -> &str {{
+        &self.base_url
+    }}
+",
+            servers.join(", ")
+        ));
+        let mut methods = METHODS.iter().map(|name| name.to_string()).collect();
+        for operation in &model.operations {
+            let name = unique(field_name(&operation.name), &mut methods);
+            out.push('\n');
+            method(&mut out, operation, &name, &error);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn method(out: &mut String, operation: &OperationDef, name: &str, error: &str) {
+    let indent = "        ";
+    let summary = [&operation.summary, &operation.description]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    doc_comment(out, "    ", Some(&summary));
+    if operation.deprecated {
+        out.push_str("    #[deprecated]\n");
+    }
+    let names = parameter_names(operation);
+    let mut arguments = vec!["&self".to_string()];
+    arguments.extend(
+        operation
+            .parameters
+            .iter()
+            .zip(&names)
+            .map(|(parameter, name)| format!("{name}: {}", parameter_type(parameter))),
+    );
+    if let Some(body) = &operation.body {
+        let encoding = Encoding::of(&body.content_type);
+        let ty = match encoding {
+            Encoding::Json | Encoding::Form | Encoding::Multipart => {
+                format!("&{}", encoding.request_type(&body.ty))
+            }
+            encoding => encoding.request_type(&body.ty),
+        };
+        arguments.push(match body.required {
+            true => format!("body: {ty}"),
+            false => format!("body: Option<{ty}>"),
+        });
+    }
+    out.push_str(&format!(
+        "    pub async fn {name}({}) -> Result<{}, {error}> {{\n",
+        arguments.join(", "),
+        operation.response_type
+    ));
+
+    // the path, with its parameters substituted
+    let mut template = String::from("{}");
+    let mut values = vec!["self.base_url".to_string()];
+    let mut rest = operation.path.as_str();
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        template.push_str(&escape_braces(&rest[..start]));
+        let variable = &rest[start + 1..end];
+        match operation
+            .parameters
+            .iter()
+            .zip(&names)
+            .find(|(parameter, _)| {
+                parameter.location == ParameterIn::Path && parameter.name == variable
+            }) {
+            Some((parameter, name)) => {
+                template.push_str("{}");
+                values.push(format!(
+                    "oas::Style::{:?}.serialize_path({:?}, &{}, {})",
+                    parameter.style,
+                    parameter.name,
+                    to_value(parameter, name),
+                    parameter.explode
+                ));
+            }
+            None => template.push_str(&escape_braces(&rest[start..=end])),
+        }
+        rest = &rest[end + 1..];
+    }
+    template.push_str(&escape_braces(rest));
+    let in_location = |location| {
+        operation
+            .parameters
+            .iter()
+            .zip(&names)
+            .filter(move |(parameter, _)| parameter.location == location)
+    };
+    // only declare mutable variables which are modified, to keep the code free of warnings
+    let has_query = in_location(ParameterIn::Query).next().is_some();
+    let mutable = |modified: bool| if modified { "mut " } else { "" };
+    out.push_str(&format!(
+        "{indent}let {}url = format!({template:?}, {});\n",
+        mutable(has_query),
+        values.join(", ")
+    ));
+    if has_query {
+        out.push_str(&format!("{indent}let mut query: Vec<String> = vec![];\n"));
+        for (parameter, name) in in_location(ParameterIn::Query) {
+            let push = format!(
+                "query.push(oas::Style::{:?}.serialize_query({:?}, &{}, {}, {}));",
+                parameter.style,
+                parameter.name,
+                to_value(parameter, name),
+                parameter.explode,
+                parameter.allow_reserved
+            );
+            optional(out, parameter, name, &push);
+        }
+        out.push_str(&format!(
+            "{indent}query.retain(|pair| !pair.is_empty());\n\
+             {indent}if !query.is_empty() {{\n\
+             {indent}    url.push('?');\n\
+             {indent}    url.push_str(&query.join(\"&\"));\n\
+             {indent}}}\n"
+        ));
+    }
+    let modifies_request = operation.body.is_some()
+        || operation.parameters.iter().any(|parameter| {
+            matches!(
+                parameter.location,
+                ParameterIn::Header | ParameterIn::Cookie
+            )
+        });
+    out.push_str(&format!(
+        "{indent}let {}request = self.http.request(reqwest::Method::{}, url);\n",
+        mutable(modifies_request),
+        operation.method.as_str().to_uppercase()
+    ));
+    for (parameter, name) in in_location(ParameterIn::Header) {
+        let push = format!(
+            "request = request.header({:?}, oas::Style::Simple.serialize_header({:?}, &{}, {}));",
+            parameter.name,
+            parameter.name,
+            to_value(parameter, name),
+            parameter.explode
+        );
+        optional(out, parameter, name, &push);
+    }
+    if in_location(ParameterIn::Cookie).next().is_some() {
+        out.push_str(&format!("{indent}let mut cookies: Vec<String> = vec![];\n"));
+        for (parameter, name) in in_location(ParameterIn::Cookie) {
+            let push = format!(
+                "cookies.push(oas::Style::Form.serialize_query({:?}, &{}, {}, false));",
+                parameter.name,
+                to_value(parameter, name),
+                parameter.explode
+            );
+            optional(out, parameter, name, &push);
+        }
+        out.push_str(&format!(
+            "{indent}if !cookies.is_empty() {{\n\
+             {indent}    request = request.header(\"Cookie\", cookies.join(\"; \"));\n\
+             {indent}}}\n"
+        ));
+    }
+    if let Some(body) = &operation.body {
+        request_body(out, body);
+    }
+
+    let needs_body = operation
+        .responses
+        .iter()
+        .any(|response| response.ty.is_some())
+        || operation
+            .responses
+            .iter()
+            .all(|response| response.status.is_some());
+    out.push_str(&format!(
+        "{indent}let response = request.send().await?;\n\
+         {indent}let status = response.status().as_u16();\n"
+    ));
+    let multipart = operation.responses.iter().any(|response| {
+        response.ty.is_some()
+            && response.content_type.as_deref().map(Encoding::of_response)
+                == Some(Encoding::Multipart)
+    });
+    if multipart {
+        out.push_str(&format!(
+            "{indent}let content_type = response\n\
+             {indent}    .headers()\n\
+             {indent}    .get(\"Content-Type\")\n\
+             {indent}    .and_then(|value| value.to_str().ok())\n\
+             {indent}    .unwrap_or_default()\n\
+             {indent}    .to_string();\n"
+        ));
+    }
+    if needs_body {
+        out.push_str(&format!("{indent}let bytes = response.bytes().await?;\n"));
+    }
+    out.push_str(&format!("{indent}match status {{\n"));
+    for response in &operation.responses {
+        let pattern = match &response.status {
+            Some(StatusCode::Code(code)) => code.to_string(),
+            Some(StatusCode::Range(class)) => format!("{0}00..={0}99", class),
+            None => "_".to_string(),
+        };
+        let mut fields = vec![];
+        if holds_status(response) {
+            fields.push("status".to_string());
+        }
+        if let (Some(content_type), Some(_)) = (&response.content_type, &response.ty) {
+            fields.push(
+                match Encoding::of_response(content_type) {
+                    Encoding::Json => "serde_json::from_slice(&bytes)?",
+                    Encoding::Text => "String::from_utf8_lossy(&bytes).into_owned()",
+                    Encoding::Multipart => {
+                        "oas::EncodedBody { content_type, body: bytes.to_vec() }"
+                    }
+                    _ => "bytes.to_vec()",
+                }
+                .to_string(),
+            );
+        }
+        let variant = format!(
+            "{}::{}",
+            operation.response_type,
+            status_variant(response.status.as_ref())
+        );
+        let value = match fields.is_empty() {
+            true => variant,
+            false => format!("{variant}({})", fields.join(", ")),
+        };
+        out.push_str(&format!("{indent}    {pattern} => Ok({value}),\n"));
+    }
+    if operation
+        .responses
+        .iter()
+        .all(|response| response.status.is_some())
+    {
+        out.push_str(&format!(
+            "{indent}    _ => Err({error}::UnexpectedStatus(status, bytes.to_vec())),\n"
+        ));
+    }
+    out.push_str(&format!("{indent}}}\n    }}\n"));
+}
+
+fn request_body(out: &mut String, body: &BodyDef) {
+    let indent = "        ";
+    let content_type = match body.content_type.contains('*') {
+        true => "application/octet-stream",
+        false => body.content_type.as_str(),
+    };
+    // the properties without an `encoding` use the `form` style, exploded
+    let mut serialize = "oas::Style::Form.serialize_query(name, value, true, false)".to_string();
+    if !body.encodings.is_empty() {
+        let arms = body
+            .encodings
+            .iter()
+            .map(|(name, encoding)| {
+                format!(
+                    "{indent}            {name:?} => oas::Style::{:?}.serialize_query(name, value, {}, {}),\n",
+                    encoding.style, encoding.explode, encoding.allow_reserved
+                )
+            })
+            .collect::<String>();
+        serialize = format!(
+            "match name.as_str() {{\n{arms}{indent}            _ => {serialize},\n{indent}        }}"
+        );
+    }
+    let send = match Encoding::of(content_type) {
+        Encoding::Json => format!(
+            "request = request.header(\"Content-Type\", {content_type:?}).body(serde_json::to_vec(body)?);"
+        ),
+        Encoding::Form => format!(
+            "let form = match serde_json::to_value(body)? {{\n\
+             {indent}    serde_json::Value::Object(object) => object\n\
+             {indent}        .iter()\n\
+             {indent}        .map(|(name, value)| {serialize})\n\
+             {indent}        .collect::<Vec<_>>()\n\
+             {indent}        .join(\"&\"),\n\
+             {indent}    _ => String::new(),\n\
+             {indent}}};\n\
+             {indent}request = request.header(\"Content-Type\", {content_type:?}).body(form);"
+        ),
+        Encoding::Multipart => format!(
+            "let fields = match serde_json::to_value(body)? {{\n\
+             {indent}    serde_json::Value::Object(object) => object\n\
+             {indent}        .into_iter()\n\
+             {indent}        .map(|(name, value)| (name, oas::FormValue::Value(value)))\n\
+             {indent}        .collect(),\n\
+             {indent}    _ => oas::Map::new(),\n\
+             {indent}}};\n\
+             {indent}let body = oas::MultipartBody::new(&fields);\n\
+             {indent}request = request.header(\"Content-Type\", body.content_type()).body(body.body);"
+        ),
+        Encoding::Text | Encoding::Bytes => {
+            format!("request = request.header(\"Content-Type\", {content_type:?}).body(body);")
+        }
+    };
+    match body.required {
+        true => out.push_str(&format!("{indent}{send}\n")),
+        false => out.push_str(&format!(
+            "{indent}if let Some(body) = body {{\n{indent}    {}\n{indent}}}\n",
+            send.replace('\n', "\n    ")
+        )),
+    }
+}
+
+/// Runs a statement for a parameter, only when it is set if it is optional, in which case the
+/// statement uses `value`.
+fn optional(out: &mut String, parameter: &ParameterDef, name: &str, statement: &str) {
+    let indent = "        ";
+    match parameter.required {
+        true => out.push_str(&format!("{indent}{statement}\n")),
+        false => out.push_str(&format!(
+            "{indent}if let Some(value) = &{name} {{\n{indent}    {statement}\n{indent}}}\n"
+        )),
+    }
+}
+
+/// The expression converting a parameter to a JSON value, serialized as JSON when it is described
+/// with `content`. Uses `value` for parameters which are not required.
+fn to_value(parameter: &ParameterDef, name: &str) -> String {
+    let name = match parameter.required || parameter.location == ParameterIn::Path {
+        true => name,
+        false => "value",
+    };
+    match &parameter.content_type {
+        Some(_) => format!("serde_json::Value::String(serde_json::to_string(&{name})?)"),
+        None => format!("serde_json::to_value(&{name})?"),
+    }
+}
+
+fn escape_braces(text: &str) -> String {
+    text.replace('{', "{{").replace('}', "}}")
+}
@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+
+use super::rust::{doc_comment, field_name, parameter_names, parameter_type};
+use super::{free_name, holds_status, pascal_case, status_variant, unique, Encoding};
+use crate::{Model, OperationDef, RustGenerator, StatusCode};
+
+/// The names of the methods of the generated server, besides the ones handling the operations.
+const METHODS: &[&str] = &["new", "service", "spec", "handle"];
+
+impl RustGenerator {
+    /// A trait with an async method per operation, to be implemented by the service, and a
+    /// server routing `http` requests to it.
+    ///
+    /// The methods of the trait take the parameters and the body of the operation, and return
+    /// the response type generated by [RustGenerator::types]. The server matches requests with a
+    /// [Router](crate::Router) built from the document, and parses and validates them with
+    /// [RouteMatch::extract](crate::RouteMatch::extract) before calling the service, so it
+    /// answers invalid requests with `400`, `404`, `405` or `415` without calling it.
+    ///
+    /// The server is built with the document the code was generated from, and needs the `oas`,
+    /// `http`, `serde` and `serde_json` crates. Adapting it to a framework such as `axum` or
+    /// `hyper` only takes collecting the body of the request.
+    pub fn server(&self, model: &Model) -> String {
+        let server = free_name(model, "Server");
+        let mut traits: BTreeMap<String, Vec<&OperationDef>> = BTreeMap::new();
+        for operation in &model.operations {
+            let name = match (self.trait_per_tag, operation.tags.first()) {
+                (false, _) => "Api".to_string(),
+                (true, Some(tag)) => format!("{}Api", pascal_case(tag)),
+                (true, None) => "DefaultApi".to_string(),
+            };
+            traits.entry(name).or_default().push(operation);
+        }
+        let traits = traits
+            .into_iter()
+            .map(|(name, operations)| (free_name(model, &name), operations))
+            .collect::<Vec<_>>();
+
+        // the methods of the traits, which are also the names of the handlers of the server
+        let mut names = BTreeMap::new();
+        let mut methods = METHODS.iter().map(|name| name.to_string()).collect();
+        for operation in &model.operations {
+            let name = unique(field_name(&operation.name), &mut methods);
+            names.insert(operation.name.as_str(), name);
+        }
+
+        let mut out = String::from("// Generated from an OpenAPI document, do not edit.\n");
+        for (name, operations) in &traits {
+            out.push('\n');
+            match (self.trait_per_tag, operations[0].tags.first()) {
+                (true, Some(tag)) => out.push_str(&format!("/// The operations tagged `{tag}`.\n")),
+                (true, None) => out.push_str("/// The operations without tags.\n"),
+                (false, _) => out.push_str("/// The operations of the API.\n"),
+            }
+            out.push_str(&format!("pub trait {name} {{\n"));
+            for (index, operation) in operations.iter().enumerate() {
+                if index > 0 {
+                    out.push('\n');
+                }
+                trait_method(&mut out, operation, &names[operation.name.as_str()]);
+            }
+            out.push_str("}\n");
+        }
+
+        let bounds = traits
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .chain(["Send", "Sync"])
+            .collect::<Vec<_>>()
+            .join(" + ");
+        out.push_str(&format!(
+            "
+/// Routes requests to the operations of the document and answers them with a service.
+#[derive(Debug, Clone)]
+pub struct {server}<S> {{
+    service: S,
+    spec: oas::OpenAPIV3,
+    router: oas::Router,
+}}
+
+impl<S: {bounds}> {server}<S> {{
+    /// A server for the document the code was generated from.
+    pub fn new(service: S, spec: oas::OpenAPIV3) -> Self {{
+        let router = oas::Router::new(&spec);
+        {server} {{ service, spec, router }}
+    }}
+
+    pub fn service(&self) -> &S {{
+        &self.service
+    }}
+
+    pub fn spec(&self) -> &oas::OpenAPIV3 {{
+        &self.spec
+    }}
+
+    /// Answers a request, calling the service if it is valid.
+    pub async fn handle(&self, request: http::Request<Vec<u8>>) -> http::Response<Vec<u8>> {{
+        let Ok(method) = request.method().as_str().parse::<oas::Method>() else {{
+            return error_response(405, serde_json::json!({{ \"error\": \"method not allowed\" }}));
+        }};
+        let target = request.uri().path_and_query().map_or(\"/\", |target| target.as_str());
+        let route = match self.router.route(method, target) {{
+            Ok(route) => route,
+            Err(oas::RouteError::MethodNotAllowed(allowed)) => {{
+                let allowed = allowed.iter().map(oas::Method::to_string).collect::<Vec<_>>();
+                let mut response =
+                    error_response(405, serde_json::json!({{ \"error\": \"method not allowed\" }}));
+                if let Ok(allowed) = http::HeaderValue::from_str(&allowed.join(\", \")) {{
+                    response.headers_mut().insert(http::header::ALLOW, allowed);
+                }}
+                return response;
+            }}
+            Err(error) => return error_response(404, serde_json::json!({{ \"error\": error.to_string() }})),
+        }};
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| {{
+                let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                (name.as_str().to_string(), value)
+            }})
+            .collect::<Vec<_>>();
+        let parts = match route.extract(&self.spec, target, &headers, request.body()) {{
+            Ok(parts) => parts,
+            Err(error) => return error_response(error.status(), error.to_json()),
+        }};
+        let bytes = request.body();
+        let response = match (route.path.as_str(), method) {{
+"
+        ));
+        for operation in &model.operations {
+            out.push_str(&format!(
+                "            ({:?}, oas::Method::{:?}) => self.{}(&parts, bytes).await,\n",
+                operation.path,
+                operation.method,
+                names[operation.name.as_str()]
+            ));
+        }
+        out.push_str(
+            "            _ => return error_response(404, serde_json::json!({ \"error\": \"no path matches the request\" })),
+        };
+        response.unwrap_or_else(|error| {
+            let error = oas::ExtractError::Invalid(vec![error]);
+            error_response(error.status(), error.to_json())
+        })
+    }
+",
+        );
+        let mut uses_json = false;
+        for (name, operations) in &traits {
+            for operation in operations {
+                out.push('\n');
+                handler(
+                    &mut out,
+                    operation,
+                    name,
+                    &names[operation.name.as_str()],
+                    &mut uses_json,
+                );
+            }
+        }
+        out.push_str(
+            "}
+
+fn response(status: u16, content_type: Option<&str>, body: Vec<u8>) -> http::Response<Vec<u8>> {
+    let mut response = http::Response::new(body);
+    *response.status_mut() =
+        http::StatusCode::from_u16(status).unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+    if let Some(content_type) = content_type.and_then(|value| http::HeaderValue::from_str(value).ok()) {
+        response.headers_mut().insert(http::header::CONTENT_TYPE, content_type);
+    }
+    response
+}
+
+fn error_response(status: u16, error: serde_json::Value) -> http::Response<Vec<u8>> {
+    response(status, Some(\"application/json\"), error.to_string().into_bytes())
+}
+",
+        );
+        if uses_json {
+            out.push_str(
+                "
+fn json_response<T: serde::Serialize>(status: u16, content_type: &str, body: &T) -> http::Response<Vec<u8>> {
+    match serde_json::to_vec(body) {
+        Ok(body) => response(status, Some(content_type), body),
+        Err(error) => error_response(500, serde_json::json!({ \"error\": error.to_string() })),
+    }
+}
+",
+            );
+        }
+        out
+    }
+}
+
+fn trait_method(out: &mut String, operation: &OperationDef, name: &str) {
+    let summary = [&operation.summary, &operation.description]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    doc_comment(out, "    ", Some(&summary));
+    if operation.deprecated {
+        if !summary.is_empty() {
+            out.push_str("    ///\n");
+        }
+        out.push_str("    /// Deprecated.\n");
+    }
+    let mut arguments = vec!["&self".to_string()];
+    arguments.extend(
+        operation
+            .parameters
+            .iter()
+            .zip(parameter_names(operation))
+            .map(|(parameter, name)| format!("{name}: {}", parameter_type(parameter))),
+    );
+    if let Some(body) = &operation.body {
+        let ty = Encoding::of(&body.content_type).request_type(&body.ty);
+        arguments.push(match body.required {
+            true => format!("body: {ty}"),
+            false => format!("body: Option<{ty}>"),
+        });
+    }
+    out.push_str(&format!(
+        "    fn {name}({}) -> impl std::future::Future<Output = {}> + Send;\n",
+        arguments.join(", "),
+        operation.response_type
+    ));
+}
+
+fn handler(
+    out: &mut String,
+    operation: &OperationDef,
+    api: &str,
+    name: &str,
+    uses_json: &mut bool,
+) {
+    let indent = "        ";
+    let names = parameter_names(operation);
+    let uses_bytes = operation.body.as_ref().is_some_and(|body| {
+        matches!(
+            Encoding::of(&body.content_type),
+            Encoding::Text | Encoding::Bytes
+        )
+    });
+    let parts = match operation.parameters.is_empty() && operation.body.is_none() {
+        true => "_parts",
+        false => "parts",
+    };
+    let bytes = match uses_bytes {
+        true => "bytes",
+        false => "_bytes",
+    };
+    out.push_str(&format!(
+        "    async fn {name}(&self, {parts}: &oas::RequestParts, {bytes}: &[u8]) -> Result<http::Response<Vec<u8>>, oas::RequestError> {{\n"
+    ));
+    let mut arguments = vec!["&self.service".to_string()];
+    for (parameter, local) in operation.parameters.iter().zip(&names) {
+        let extract = match parameter.required {
+            true => "required",
+            false => "parameter",
+        };
+        out.push_str(&format!(
+            "{indent}let {local}: {} = parts.{extract}(oas::ParameterIn::{:?}, {:?})?;\n",
+            parameter_type(parameter),
+            parameter.location,
+            parameter.name
+        ));
+        arguments.push(local.clone());
+    }
+    if let Some(body) = &operation.body {
+        let ty = Encoding::of(&body.content_type).request_type(&body.ty);
+        let value = match (Encoding::of(&body.content_type), body.required) {
+            (Encoding::Text, required) => {
+                let text =
+                    "String::from_utf8(bytes.to_vec()).map_err(|error| oas::RequestError {\n\
+                     {indent}    parameter: None,\n\
+                     {indent}    pointer: String::new(),\n\
+                     {indent}    message: error.to_string(),\n\
+                     {indent}})?"
+                        .replace("{indent}", indent);
+                match required {
+                    true => text,
+                    false => format!(
+                        "match bytes.is_empty() {{\n\
+                         {indent}    true => None,\n\
+                         {indent}    false => Some({}),\n\
+                         {indent}}}",
+                        text.replace('\n', "\n    ")
+                    ),
+                }
+            }
+            (Encoding::Bytes, true) => "bytes.to_vec()".to_string(),
+            (Encoding::Bytes, false) => "(!bytes.is_empty()).then(|| bytes.to_vec())".to_string(),
+            (_, true) => "parts.required_body()?".to_string(),
+            (_, false) => "parts.body()?".to_string(),
+        };
+        let ty = match body.required {
+            true => ty,
+            false => format!("Option<{ty}>"),
+        };
+        out.push_str(&format!("{indent}let body: {ty} = {value};\n"));
+        arguments.push("body".to_string());
+    }
+    out.push_str(&format!(
+        "{indent}Ok(match {api}::{name}({}).await {{\n",
+        arguments.join(", ")
+    ));
+    for response in &operation.responses {
+        let variant = format!(
+            "{}::{}",
+            operation.response_type,
+            status_variant(response.status.as_ref())
+        );
+        let mut fields = vec![];
+        let status = match &response.status {
+            Some(StatusCode::Code(code)) => code.to_string(),
+            _ => "status".to_string(),
+        };
+        if holds_status(response) {
+            fields.push("status");
+        }
+        let value = match (&response.content_type, &response.ty) {
+            (Some(content_type), Some(_)) => {
+                fields.push("body");
+                let content_type = match content_type.contains('*') {
+                    true => "application/octet-stream",
+                    false => content_type.as_str(),
+                };
+                match Encoding::of_response(content_type) {
+                    Encoding::Json => {
+                        *uses_json = true;
+                        format!("json_response({status}, {content_type:?}, &body)")
+                    }
+                    Encoding::Multipart => {
+                        format!("response({status}, Some(&body.content_type), body.body)")
+                    }
+                    Encoding::Text | Encoding::Form => {
+                        format!("response({status}, Some({content_type:?}), body.into_bytes())")
+                    }
+                    Encoding::Bytes => format!("response({status}, Some({content_type:?}), body)"),
+                }
+            }
+            _ => format!("response({status}, None, Vec::new())"),
+        };
+        let pattern = match fields.is_empty() {
+            true => variant,
+            false => format!("{variant}({})", fields.join(", ")),
+        };
+        out.push_str(&format!("{indent}    {pattern} => {value},\n"));
+    }
+    out.push_str(&format!("{indent}}})\n    }}\n"));
+}
@@ -0,0 +1,704 @@
+use std::collections::BTreeSet;
+
+use super::{camel_case, free_name, unique, Encoding};
+use crate::{
+    FieldDef, Model, OperationDef, ParameterDef, ParameterIn, ResponseDef, StatusCode, Style,
+    TypeDef, TypeKind, TypeRef,
+};
+
+/// The global types and values of TypeScript used by the generated code, which types of the model
+/// must not shadow. Such types get a `_` suffix.
+const GLOBALS: &[&str] = &[
+    "Array",
+    "BigInt",
+    "BodyInit",
+    "Blob",
+    "Boolean",
+    "Date",
+    "FormData",
+    "Function",
+    "Map",
+    "Number",
+    "Object",
+    "Partial",
+    "Promise",
+    "Readonly",
+    "Record",
+    "RequestInit",
+    "Response",
+    "Set",
+    "String",
+    "Symbol",
+    "TextEncoder",
+];
+
+/// The functions serializing parameters according to their style, which follow
+/// [Style::serialize_query](crate::Style::serialize_query) and
+/// [Style::serialize_path](crate::Style::serialize_path). They are exported so that unused ones
+/// are not reported by `noUnusedLocals`.
+const RUNTIME: &str = r#"
+type Style = "simple" | "label" | "matrix" | "form" | "spaceDelimited" | "pipeDelimited" | "deepObject";
+
+const RESERVED = ":/?#[]@!$&'()*+,;=";
+
+function encode(value: string, allowReserved = false): string {
+  return Array.from(new TextEncoder().encode(value), (byte) => {
+    const char = String.fromCharCode(byte);
+    return /^[A-Za-z0-9\-._~]$/.test(char) || (allowReserved && RESERVED.includes(char))
+      ? char
+      : "%" + byte.toString(16).toUpperCase().padStart(2, "0");
+  }).join("");
+}
+
+function text(value: unknown): string {
+  if (value === null || value === undefined) {
+    return "";
+  }
+  return typeof value === "string" ? value : JSON.stringify(value);
+}
+
+function isObject(value: unknown): value is { [key: string]: unknown } {
+  return typeof value === "object" && value !== null && !Array.isArray(value);
+}
+
+function deepObjectPairs(prefix: string, object: { [key: string]: unknown }, pairs: string[], allowReserved: boolean): void {
+  for (const [key, value] of Object.entries(object)) {
+    const name = `${prefix}[${encode(key)}]`;
+    if (isObject(value)) {
+      deepObjectPairs(name, value, pairs, allowReserved);
+    } else {
+      pairs.push(`${name}=${encode(text(value), allowReserved)}`);
+    }
+  }
+}
+
+export function serializeQuery(style: Style, name: string, value: unknown, explode: boolean, allowReserved: boolean): string {
+  const pair = (key: string, value: unknown) => `${encode(key, allowReserved)}=${encode(text(value), allowReserved)}`;
+  const delimiter = style === "spaceDelimited" ? "%20" : style === "pipeDelimited" ? "|" : ",";
+  if (isObject(value) && style === "deepObject") {
+    const pairs: string[] = [];
+    deepObjectPairs(encode(name, allowReserved), value, pairs, allowReserved);
+    return pairs.join("&");
+  }
+  if (Array.isArray(value)) {
+    return explode
+      ? value.map((item) => pair(name, item)).join("&")
+      : `${encode(name, allowReserved)}=${value.map((item) => encode(text(item), allowReserved)).join(delimiter)}`;
+  }
+  if (isObject(value)) {
+    const entries = Object.entries(value);
+    return explode
+      ? entries.map(([key, item]) => pair(key, item)).join("&")
+      : `${encode(name, allowReserved)}=${entries.flatMap(([key, item]) => [encode(key, allowReserved), encode(text(item), allowReserved)]).join(delimiter)}`;
+  }
+  return pair(name, value);
+}
+
+function serializeDelimited(style: Style, name: string, value: unknown, explode: boolean, encoded: (value: string) => string): string {
+  const item = (value: unknown) => encoded(text(value));
+  const named = `;${encoded(name)}=`;
+  const prefix = style === "label" ? "." : style === "matrix" ? named : "";
+  const separator = !explode ? "," : style === "label" ? "." : style === "matrix" ? named : ",";
+  if (Array.isArray(value)) {
+    return prefix + value.map(item).join(separator);
+  }
+  if (isObject(value)) {
+    const entries = Object.entries(value);
+    if (explode) {
+      const [prefix, separator] = style === "label" ? [".", "."] as const : style === "matrix" ? [";", ";"] as const : ["", ","] as const;
+      return prefix + entries.map(([key, value]) => `${encoded(key)}=${item(value)}`).join(separator);
+    }
+    return prefix + entries.flatMap(([key, value]) => [encoded(key), item(value)]).join(",");
+  }
+  if (value === "" && style === "matrix") {
+    return `;${encoded(name)}`;
+  }
+  return prefix + item(value);
+}
+
+export function serializePath(style: Style, name: string, value: unknown, explode: boolean): string {
+  return serializeDelimited(style, name, value, explode, (value) => encode(value));
+}
+
+export function serializeHeader(value: unknown, explode: boolean): string {
+  return serializeDelimited("simple", "", value, explode, (value) => value);
+}
+"#;
+
+/// Generates TypeScript code from a [Model], for web frontends.
+///
+/// Objects become interfaces with optional properties for the ones which are not required, and
+/// nullable types become unions with `null`. String enumerations become unions of string
+/// literals, unions with a discriminator become unions of the variants intersected with the value
+/// of their tag, and formats are strings, since they are not revived by `JSON.parse`.
+#[derive(Debug, Clone, Default)]
+pub struct TypeScriptGenerator {
+    /// The module the client imports the types from, e.g. `./types`. By default, the client is
+    /// meant to be in the same module as the types.
+    pub types_module: Option<String>,
+}
+
+impl TypeScriptGenerator {
+    /// The definitions of the types of the model, followed by a union per operation of its
+    /// responses, told apart by their `status`.
+    pub fn types(&self, model: &Model) -> String {
+        let mut out = String::from("// Generated from an OpenAPI document, do not edit.\n");
+        for ty in &model.types {
+            out.push('\n');
+            type_def(&mut out, ty);
+        }
+        for operation in &model.operations {
+            out.push('\n');
+            response_type(&mut out, operation);
+        }
+        out
+    }
+
+    /// A client with an async method per operation, named after its `operationId` in camelCase,
+    /// which sends requests with `fetch`.
+    ///
+    /// The parameters are passed in an object keyed by their names, and serialized according to
+    /// their `style` and `explode`, like the properties of form bodies with their `encoding`.
+    /// JSON and form bodies are typed, multipart bodies are `FormData`, text bodies are strings
+    /// and other bodies are `Blob`s. Responses with a status code which is not described by the
+    /// document are thrown as a `ClientError`.
+    pub fn client(&self, model: &Model) -> String {
+        let client = free_name(model, "Client");
+        let options = free_name(model, "ClientOptions");
+        let error = free_name(model, "ClientError");
+        let mut out = String::from("// Generated from an OpenAPI document, do not edit.\n");
+        if let Some(module) = &self.types_module {
+            let mut imported = model
+                .operations
+                .iter()
+                .map(|operation| operation.response_type.clone())
+                .collect::<BTreeSet<_>>();
+            for operation in &model.operations {
+                let types = operation
+                    .parameters
+                    .iter()
+                    .map(|parameter| &parameter.ty)
+                    .chain(operation.body.iter().map(|body| &body.ty));
+                for ty in types {
+                    named_types(ty, &mut imported);
+                }
+            }
+            let imported = imported
+                .iter()
+                .map(|name| type_name(name))
+                .collect::<Vec<_>>();
+            out.push_str(&format!(
+                "\nimport type {{ {} }} from {module:?};\n",
+                imported.join(", ")
+            ));
+        }
+        out.push_str(RUNTIME);
+        let servers = model
+            .servers
+            .iter()
+            .map(|server| format!("{:?}", server.url))
+            .collect::<Vec<_>>();
+        out.push_str(&format!(
+            "
+/** A response with a status code which is not described by the document. */
+export class {error} extends globalThis.Error {{
+  constructor(readonly status: number, readonly body: string) {{
+    super(`unexpected status code ${{status}}`);
+  }}
+}}
+
+export interface {options} {{
+  /** The function sending the requests, which defaults to the global `fetch`. */
+  fetch?: typeof globalThis.fetch;
+  /** The headers sent with every request, e.g. for authentication. */
+  headers?: {{ [name: string]: string }};
+}}
+
+/** A client of the API. */
+export class {client} {{
+  /** The URLs of the servers of the document, with the default values of their variables. */
+  static readonly servers: readonly string[] = [{}];
+
+  readonly baseUrl: string;
+  private readonly options: {options};
+
+  /** A client sending requests to the given base URL, e.g. one of `{client}.servers`. */
+  constructor(baseUrl: string, options: {options} = {{}}) {{
+    this.baseUrl = baseUrl.replace(/\\/+$/, \"\");
+    this.options = options;
+  }}
+",
+            servers.join(", ")
+        ));
+        let mut methods = ["baseUrl", "options", "send"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        for operation in &model.operations {
+            let name = unique(camel_case(&operation.name), &mut methods);
+            out.push('\n');
+            method(&mut out, operation, &name, &error);
+        }
+        out.push_str(
+            "
+  private async send(method: string, path: string, query: string[], headers: { [name: string]: string }, body?: BodyInit): Promise<globalThis.Response> {
+    const search = query.filter((pair) => pair !== \"\").join(\"&\");
+    const url = this.baseUrl + path + (search === \"\" ? \"\" : \"?\" + search);
+    const send = this.options.fetch ?? globalThis.fetch;
+    return send(url, { method, headers: { ...this.options.headers, ...headers }, body });
+  }
+}
+",
+        );
+        out
+    }
+}
+
+/// The name of a type of the model in TypeScript, which must not shadow the global types.
+fn type_name(name: &str) -> String {
+    match GLOBALS.contains(&name) {
+        true => format!("{name}_"),
+        false => name.to_string(),
+    }
+}
+
+/// The TypeScript type of a use of a type.
+fn ts_type(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::Any => "unknown".to_string(),
+        TypeRef::Boolean => "boolean".to_string(),
+        TypeRef::Int32 | TypeRef::Int64 | TypeRef::Float | TypeRef::Double => "number".to_string(),
+        TypeRef::String
+        | TypeRef::DateTime
+        | TypeRef::Date
+        | TypeRef::Time
+        | TypeRef::Uuid
+        | TypeRef::Uri => "string".to_string(),
+        TypeRef::Named(name) => type_name(name),
+        TypeRef::Array(items) => match items.as_ref() {
+            TypeRef::Nullable(_) => format!("({})[]", ts_type(items)),
+            items => format!("{}[]", ts_type(items)),
+        },
+        TypeRef::Map(values) => format!("{{ [key: string]: {} }}", ts_type(values)),
+        TypeRef::Nullable(ty) => format!("{} | null", ts_type(ty)),
+    }
+}
+
+/// The names of the types of the model used by a type.
+fn named_types(ty: &TypeRef, names: &mut BTreeSet<String>) {
+    match ty {
+        TypeRef::Named(name) => {
+            names.insert(name.clone());
+        }
+        TypeRef::Array(inner) | TypeRef::Map(inner) | TypeRef::Nullable(inner) => {
+            named_types(inner, names)
+        }
+        _ => {}
+    }
+}
+
+/// A property name, quoted unless it is an identifier.
+fn property_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    match identifier {
+        true => name.to_string(),
+        false => format!("{name:?}"),
+    }
+}
+
+/// Writes a description as a JSDoc comment, with a `@deprecated` tag if needed.
+fn js_doc(out: &mut String, indent: &str, description: Option<&str>, deprecated: bool) {
+    let mut lines = description
+        .into_iter()
+        .flat_map(|description| description.trim().lines())
+        .map(|line| line.trim_end().replace("*/", "*\\/"))
+        .collect::<Vec<_>>();
+    if deprecated {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push("@deprecated".to_string());
+    }
+    match lines.as_slice() {
+        [] => {}
+        [line] => out.push_str(&format!("{indent}/** {line} */\n")),
+        lines => {
+            out.push_str(&format!("{indent}/**\n"));
+            for line in lines {
+                match line.is_empty() {
+                    true => out.push_str(&format!("{indent} *\n")),
+                    false => out.push_str(&format!("{indent} * {line}\n")),
+                }
+            }
+            out.push_str(&format!("{indent} */\n"));
+        }
+    }
+}
+
+fn type_def(out: &mut String, ty: &TypeDef) {
+    js_doc(out, "", ty.description.as_deref(), ty.deprecated);
+    let name = type_name(&ty.name);
+    match &ty.kind {
+        TypeKind::Struct {
+            fields,
+            additional_properties,
+        } => {
+            out.push_str(&format!("export interface {name} {{\n"));
+            for field in fields {
+                property(out, field);
+            }
+            if let Some(values) = additional_properties {
+                // the properties must be assignable to the index signature
+                let values = match (fields.is_empty(), values) {
+                    (true, values) | (false, values @ TypeRef::Any) => ts_type(values),
+                    (false, _) => "unknown".to_string(),
+                };
+                out.push_str(&format!("  [key: string]: {values};\n"));
+            }
+            out.push_str("}\n");
+        }
+        TypeKind::Enum { values } => {
+            let values = values
+                .iter()
+                .map(|value| format!("{value:?}"))
+                .collect::<Vec<_>>();
+            out.push_str(&format!("export type {name} = {};\n", values.join(" | ")));
+        }
+        TypeKind::Union { tag, variants } => {
+            let variants = variants
+                .iter()
+                .map(|variant| match (tag, &variant.tag) {
+                    (Some(tag), Some(value)) => format!(
+                        "({{ {}: {value:?} }} & {})",
+                        property_name(tag),
+                        ts_type(&variant.ty)
+                    ),
+                    _ => match &variant.ty {
+                        TypeRef::Nullable(_) => format!("({})", ts_type(&variant.ty)),
+                        ty => ts_type(ty),
+                    },
+                })
+                .collect::<Vec<_>>();
+            match variants.is_empty() {
+                true => out.push_str(&format!("export type {name} = never;\n")),
+                false => out.push_str(&format!(
+                    "export type {name} =\n  | {};\n",
+                    variants.join("\n  | ")
+                )),
+            }
+        }
+        TypeKind::Alias(target) => {
+            out.push_str(&format!("export type {name} = {};\n", ts_type(target)));
+        }
+    }
+}
+
+fn property(out: &mut String, field: &FieldDef) {
+    js_doc(out, "  ", field.description.as_deref(), field.deprecated);
+    let readonly = match field.read_only {
+        true => "readonly ",
+        false => "",
+    };
+    let optional = match field.required {
+        true => "",
+        false => "?",
+    };
+    out.push_str(&format!(
+        "  {readonly}{}{optional}: {};\n",
+        property_name(&field.name),
+        ts_type(&field.ty)
+    ));
+}
+
+/// The TypeScript type of a body with an encoding.
+fn body_type(encoding: Encoding, ty: &TypeRef) -> String {
+    match encoding {
+        Encoding::Json | Encoding::Form => ts_type(ty),
+        Encoding::Multipart => "FormData".to_string(),
+        Encoding::Text => "string".to_string(),
+        Encoding::Bytes => "Blob".to_string(),
+    }
+}
+
+/// The type of the `status` of a response, which is a literal for a single status code.
+fn status_type(response: &ResponseDef) -> String {
+    match &response.status {
+        Some(StatusCode::Code(code)) => code.to_string(),
+        _ => "number".to_string(),
+    }
+}
+
+fn response_type(out: &mut String, operation: &OperationDef) {
+    out.push_str(&format!("/** The responses of `{}`. */\n", operation.name));
+    let variants = operation
+        .responses
+        .iter()
+        .map(|response| {
+            let status = status_type(response);
+            match (&response.content_type, &response.ty) {
+                (Some(content_type), Some(ty)) => {
+                    let body = body_type(Encoding::of_response(content_type), ty);
+                    format!("{{ status: {status}; body: {body} }}")
+                }
+                _ => format!("{{ status: {status} }}"),
+            }
+        })
+        .collect::<Vec<_>>();
+    let name = type_name(&operation.response_type);
+    match variants.is_empty() {
+        true => out.push_str(&format!("export type {name} = never;\n")),
+        false => out.push_str(&format!(
+            "export type {name} =\n  | {};\n",
+            variants.join("\n  | ")
+        )),
+    }
+}
+
+/// The expression of the value of a parameter, serialized as JSON when it is described with
+/// `content`.
+fn parameter_value(parameter: &ParameterDef) -> String {
+    let value = match property_name(&parameter.name) {
+        name if name.starts_with('"') => format!("params[{name}]"),
+        name => format!("params.{name}"),
+    };
+    match &parameter.content_type {
+        Some(_) => format!("JSON.stringify({value})"),
+        None => value,
+    }
+}
+
+fn style_name(style: &Style) -> String {
+    let style = format!("{style:?}");
+    let mut chars = style.chars();
+    let first = chars.next().into_iter().flat_map(char::to_lowercase);
+    first.chain(chars).collect()
+}
+
+fn method(out: &mut String, operation: &OperationDef, name: &str, error: &str) {
+    let indent = "    ";
+    let summary = [&operation.summary, &operation.description]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let summary = Some(summary.as_str()).filter(|summary| !summary.is_empty());
+    js_doc(out, "  ", summary, operation.deprecated);
+
+    let mut arguments = vec![];
+    if !operation.parameters.is_empty() {
+        let properties = operation
+            .parameters
+            .iter()
+            .map(|parameter| {
+                let optional = match parameter.required {
+                    true => "",
+                    false => "?",
+                };
+                format!(
+                    "{}{optional}: {}",
+                    property_name(&parameter.name),
+                    ts_type(parameter.ty.non_null())
+                )
+            })
+            .collect::<Vec<_>>();
+        // a default is only allowed when the following arguments are optional too
+        let optional = operation
+            .parameters
+            .iter()
+            .all(|parameter| !parameter.required)
+            && operation.body.as_ref().is_none_or(|body| !body.required);
+        arguments.push(format!(
+            "params: {{ {} }}{}",
+            properties.join("; "),
+            if optional { " = {}" } else { "" }
+        ));
+    }
+    if let Some(body) = &operation.body {
+        let ty = body_type(Encoding::of(&body.content_type), &body.ty);
+        match body.required {
+            true => arguments.push(format!("body: {ty}")),
+            false => arguments.push(format!("body?: {ty}")),
+        }
+    }
+    out.push_str(&format!(
+        "  async {name}({}): Promise<{}> {{\n",
+        arguments.join(", "),
+        type_name(&operation.response_type)
+    ));
+
+    // the path, with its parameters substituted
+    let mut template = String::new();
+    let mut rest = operation.path.as_str();
+    let escape = |text: &str| {
+        text.replace('\\', "\\\\")
+            .replace('`', "\\`")
+            .replace('$', "\\$")
+    };
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        template.push_str(&escape(&rest[..start]));
+        let variable = &rest[start + 1..end];
+        match operation
+            .parameters
+            .iter()
+            .find(|parameter| parameter.location == ParameterIn::Path && parameter.name == variable)
+        {
+            Some(parameter) => template.push_str(&format!(
+                "${{serializePath({:?}, {:?}, {}, {})}}",
+                style_name(&parameter.style),
+                parameter.name,
+                parameter_value(parameter),
+                parameter.explode
+            )),
+            None => template.push_str(&escape(&rest[start..=end])),
+        }
+        rest = &rest[end + 1..];
+    }
+    template.push_str(&escape(rest));
+    out.push_str(&format!("{indent}const path = `{template}`;\n"));
+    out.push_str(&format!("{indent}const query: string[] = [];\n"));
+    out.push_str(&format!(
+        "{indent}const headers: {{ [name: string]: string }} = {{}};\n"
+    ));
+    let cookies = operation
+        .parameters
+        .iter()
+        .any(|parameter| parameter.location == ParameterIn::Cookie);
+    if cookies {
+        out.push_str(&format!("{indent}const cookies: string[] = [];\n"));
+    }
+    for parameter in &operation.parameters {
+        let value = parameter_value(parameter);
+        let statement = match parameter.location {
+            ParameterIn::Path => continue,
+            ParameterIn::Query => format!(
+                "query.push(serializeQuery({:?}, {:?}, {value}, {}, {}));",
+                style_name(&parameter.style),
+                parameter.name,
+                parameter.explode,
+                parameter.allow_reserved
+            ),
+            ParameterIn::Header => format!(
+                "headers[{:?}] = serializeHeader({value}, {});",
+                parameter.name, parameter.explode
+            ),
+            ParameterIn::Cookie => format!(
+                "cookies.push(serializeQuery(\"form\", {:?}, {value}, {}, false));",
+                parameter.name, parameter.explode
+            ),
+        };
+        match parameter.required {
+            true => out.push_str(&format!("{indent}{statement}\n")),
+            false => {
+                let value = parameter_value(&ParameterDef {
+                    content_type: None,
+                    ..parameter.clone()
+                });
+                out.push_str(&format!(
+                    "{indent}if ({value} !== undefined) {{\n{indent}  {statement}\n{indent}}}\n"
+                ));
+            }
+        }
+    }
+    if cookies {
+        out.push_str(&format!(
+            "{indent}if (cookies.length > 0) {{\n{indent}  headers[\"Cookie\"] = cookies.join(\"; \");\n{indent}}}\n"
+        ));
+    }
+
+    let mut body_argument = "";
+    if let Some(body) = &operation.body {
+        let content_type = match body.content_type.contains('*') {
+            true => "application/octet-stream",
+            false => body.content_type.as_str(),
+        };
+        // the properties without an `encoding` use the `form` style, exploded
+        let serialize = body.encodings.iter().rev().fold(
+            "serializeQuery(\"form\", name, value, true, false)".to_string(),
+            |serialize, (name, encoding)| {
+                format!(
+                    "name === {name:?} ? serializeQuery({:?}, name, value, {}, {}) : {serialize}",
+                    style_name(&encoding.style),
+                    encoding.explode,
+                    encoding.allow_reserved
+                )
+            },
+        );
+        let encoded = match Encoding::of(content_type) {
+            Encoding::Json => "JSON.stringify(body)".to_string(),
+            Encoding::Form => {
+                format!("Object.entries(body).map(([name, value]) => {serialize}).join(\"&\")")
+            }
+            Encoding::Multipart | Encoding::Text | Encoding::Bytes => "body".to_string(),
+        };
+        // the content type of multipart bodies is set by `fetch`, with the boundary
+        let header = match Encoding::of(content_type) {
+            Encoding::Multipart => String::new(),
+            _ => format!("{indent}  headers[\"Content-Type\"] = {content_type:?};\n"),
+        };
+        match body.required {
+            true => out.push_str(&format!(
+                "{}{indent}const encoded: BodyInit = {encoded};\n",
+                header.replacen("  ", "", 1)
+            )),
+            false => out.push_str(&format!(
+                "{indent}let encoded: BodyInit | undefined;\n\
+                 {indent}if (body !== undefined) {{\n{header}{indent}  encoded = {encoded};\n{indent}}}\n"
+            )),
+        }
+        body_argument = ", encoded";
+    }
+    out.push_str(&format!(
+        "{indent}const response = await this.send({:?}, path, query, headers{body_argument});\n",
+        operation.method.as_str().to_uppercase()
+    ));
+    out.push_str(&format!("{indent}const status = response.status;\n"));
+    let mut exhaustive = false;
+    for response in &operation.responses {
+        let condition = match &response.status {
+            Some(StatusCode::Code(code)) => Some(format!("status === {code}")),
+            Some(StatusCode::Range(class)) => {
+                Some(format!("status >= {class}00 && status <= {class}99"))
+            }
+            None => None,
+        };
+        let status = match &response.status {
+            Some(StatusCode::Code(code)) => code.to_string(),
+            _ => "status".to_string(),
+        };
+        let value = match (&response.content_type, &response.ty) {
+            (Some(content_type), Some(_)) => {
+                let body = match Encoding::of_response(content_type) {
+                    Encoding::Json => "await response.json()",
+                    Encoding::Text | Encoding::Form => "await response.text()",
+                    Encoding::Multipart => "await response.formData()",
+                    Encoding::Bytes => "await response.blob()",
+                };
+                format!("{{ status: {status}, body: {body} }}")
+            }
+            _ => format!("{{ status: {status} }}"),
+        };
+        match condition {
+            Some(condition) => out.push_str(&format!(
+                "{indent}if ({condition}) {{\n{indent}  return {value};\n{indent}}}\n"
+            )),
+            None => {
+                out.push_str(&format!("{indent}return {value};\n"));
+                exhaustive = true;
+            }
+        }
+    }
+    if !exhaustive {
+        out.push_str(&format!(
+            "{indent}throw new {error}(status, await response.text());\n"
+        ));
+    }
+    out.push_str("  }\n");
+}
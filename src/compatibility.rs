@@ -0,0 +1,399 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Change, ChangeKind, Direction, Element, OpenAPIV3};
+
+/// How much a finding matters. Findings of [Severity::Off] are not reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Off => "off",
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// The kinds of changes which can break existing clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeRule {
+    RemovedPath,
+    RemovedOperation,
+    /// A parameter was added as required, or became required.
+    NewRequiredParameter,
+    /// A parameter moved, e.g. from the query to a header.
+    ChangedParameterLocation,
+    /// The request body was added as required, or became required.
+    RequiredRequestBody,
+    /// A media type of a request body or a response was removed.
+    RemovedMediaType,
+    /// Values of a request enum were removed, or an enum was added to a request schema.
+    NarrowedEnum,
+    /// Values were added to a response enum, which clients may not handle.
+    WidenedResponseEnum,
+    RemovedResponseProperty,
+    /// A property of a request schema became required.
+    NewRequiredRequestProperty,
+    TypeChanged,
+    /// A request schema became stricter, e.g. with a lower `maxLength` or a new `pattern`.
+    TightenedRequestConstraint,
+    /// A response schema became nullable.
+    NullableResponse,
+    /// A security requirement was removed while the remaining ones all ask for more, or an
+    /// operation without any now has one.
+    StricterSecurity,
+}
+
+impl ChangeRule {
+    /// The name of the rule in configurations, e.g. `removed-operation`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeRule::RemovedPath => "removed-path",
+            ChangeRule::RemovedOperation => "removed-operation",
+            ChangeRule::NewRequiredParameter => "new-required-parameter",
+            ChangeRule::ChangedParameterLocation => "changed-parameter-location",
+            ChangeRule::RequiredRequestBody => "required-request-body",
+            ChangeRule::RemovedMediaType => "removed-media-type",
+            ChangeRule::NarrowedEnum => "narrowed-enum",
+            ChangeRule::WidenedResponseEnum => "widened-response-enum",
+            ChangeRule::RemovedResponseProperty => "removed-response-property",
+            ChangeRule::NewRequiredRequestProperty => "new-required-request-property",
+            ChangeRule::TypeChanged => "type-changed",
+            ChangeRule::TightenedRequestConstraint => "tightened-request-constraint",
+            ChangeRule::NullableResponse => "nullable-response",
+            ChangeRule::StricterSecurity => "stricter-security",
+        }
+    }
+
+    /// The severity of the rule unless configured otherwise: [Severity::Warning] for
+    /// [ChangeRule::WidenedResponseEnum], which only breaks strict clients, and
+    /// [Severity::Error] for the others.
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            ChangeRule::WidenedResponseEnum => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for ChangeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The severities of the rules, e.g. `{"severities": {"widened-response-enum": "off"}}`. Rules
+/// which are not listed keep their [default severity](ChangeRule::default_severity).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompatibilityConfig {
+    #[serde(default)]
+    pub severities: BTreeMap<ChangeRule, Severity>,
+}
+
+impl CompatibilityConfig {
+    pub fn severity(&self, rule: ChangeRule) -> Severity {
+        self.severities
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+/// A change between two documents, with the rule it breaks if any.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassifiedChange {
+    #[serde(flatten)]
+    pub change: Change,
+    /// The rule broken by the change, `None` for changes which do not break clients.
+    pub rule: Option<ChangeRule>,
+    /// The configured severity of the rule, [Severity::Info] for changes which do not break
+    /// clients.
+    pub severity: Severity,
+}
+
+impl ClassifiedChange {
+    pub fn is_breaking(&self) -> bool {
+        self.rule.is_some() && self.severity > Severity::Off
+    }
+}
+
+impl fmt::Display for ClassifiedChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rule {
+            Some(rule) => write!(f, "{}[{rule}]: {}", self.severity, self.change),
+            None => write!(f, "{}: {}", self.severity, self.change),
+        }
+    }
+}
+
+/// The changes between two documents, found by [OpenAPIV3::check_compatibility].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompatibilityReport {
+    pub changes: Vec<ClassifiedChange>,
+}
+
+impl CompatibilityReport {
+    /// The changes which break clients, unless their rule is turned off.
+    pub fn breaking(&self) -> impl Iterator<Item = &ClassifiedChange> {
+        self.changes.iter().filter(|change| change.is_breaking())
+    }
+
+    /// The highest severity of the breaking changes.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.breaking().map(|change| change.severity).max()
+    }
+
+    /// Whether a breaking change has at least the given severity, to fail a CI job.
+    pub fn fails(&self, threshold: Severity) -> bool {
+        self.max_severity()
+            .is_some_and(|severity| severity >= threshold)
+    }
+}
+
+/// Lists the breaking changes, then the other ones.
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (breaking, other): (Vec<_>, Vec<_>) =
+            self.changes.iter().partition(|change| change.is_breaking());
+        for change in breaking.into_iter().chain(other) {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl OpenAPIV3 {
+    /// Compares this document with a newer version of it with [OpenAPIV3::diff], and classifies
+    /// each change as breaking clients of this version or not.
+    pub fn check_compatibility(
+        &self,
+        new: &OpenAPIV3,
+        config: &CompatibilityConfig,
+    ) -> CompatibilityReport {
+        let changes = self.diff(new);
+        let (old, new) = (self.to_value(), new.to_value());
+        let classifier = Classifier {
+            moved: moved_parameters(&changes, &old, &new),
+            old,
+            new,
+        };
+        let changes = changes
+            .into_iter()
+            .map(|change| {
+                let rule = classifier.classify(&change);
+                let severity = rule.map_or(Severity::Info, |rule| config.severity(rule));
+                ClassifiedChange {
+                    change,
+                    rule,
+                    severity,
+                }
+            })
+            .collect();
+        CompatibilityReport { changes }
+    }
+}
+
+/// The pointers of the parameters which were removed and added with the same name in another
+/// location of the same operation.
+fn moved_parameters(changes: &[Change], old: &Value, new: &Value) -> BTreeSet<String> {
+    let parameter = |document: &Value, pointer: &str| {
+        let parameter = document.pointer(pointer)?;
+        Some((
+            parameter.get("name")?.as_str()?.to_string(),
+            parameter.get("in")?.as_str()?.to_string(),
+        ))
+    };
+    let mut moved = BTreeSet::new();
+    for removed in changes
+        .iter()
+        .filter(|change| change.kind == ChangeKind::Removed && change.element == Element::Parameter)
+    {
+        let Some((name, location)) = parameter(old, &removed.old) else {
+            continue;
+        };
+        for added in changes.iter().filter(|change| {
+            change.kind == ChangeKind::Added
+                && change.element == Element::Parameter
+                && change.old == removed.new
+        }) {
+            if let Some((added_name, added_location)) = parameter(new, &added.new) {
+                if added_name == name && added_location != location {
+                    moved.insert(removed.old.clone());
+                    moved.insert(added.new.clone());
+                }
+            }
+        }
+    }
+    moved
+}
+
+struct Classifier {
+    old: Value,
+    new: Value,
+    moved: BTreeSet<String>,
+}
+
+impl Classifier {
+    fn classify(&self, change: &Change) -> Option<ChangeRule> {
+        use ChangeKind::*;
+        let field = change.field.as_deref();
+        let (old, new) = (change.old_value.as_ref(), change.new_value.as_ref());
+        let request = change.direction == Some(Direction::Request);
+        let response = change.direction == Some(Direction::Response);
+        fn required(value: Option<&Value>) -> Option<&Value> {
+            value?.get("required")
+        }
+        match (change.kind, change.element) {
+            (Removed, Element::Path) => Some(ChangeRule::RemovedPath),
+            (Removed, Element::Operation) => Some(ChangeRule::RemovedOperation),
+            (Removed, Element::Parameter) if self.moved.contains(&change.old) => {
+                Some(ChangeRule::ChangedParameterLocation)
+            }
+            (Added, Element::Parameter) if self.moved.contains(&change.new) => {
+                Some(ChangeRule::ChangedParameterLocation)
+            }
+            (Added, Element::Parameter)
+                if required(self.new.pointer(&change.new)) == Some(&Value::Bool(true)) =>
+            {
+                Some(ChangeRule::NewRequiredParameter)
+            }
+            (Changed, Element::Parameter) if field == Some("required") => {
+                (new == Some(&Value::Bool(true))).then_some(ChangeRule::NewRequiredParameter)
+            }
+            (Added, Element::RequestBody)
+                if required(self.new.pointer(&change.new)) == Some(&Value::Bool(true)) =>
+            {
+                Some(ChangeRule::RequiredRequestBody)
+            }
+            (Changed, Element::RequestBody) if field == Some("required") => {
+                (new == Some(&Value::Bool(true))).then_some(ChangeRule::RequiredRequestBody)
+            }
+            (Removed, Element::MediaType) if change.direction.is_some() => {
+                Some(ChangeRule::RemovedMediaType)
+            }
+            (Removed, Element::Property) if response => Some(ChangeRule::RemovedResponseProperty),
+            (Removed, Element::SecurityRequirement) => {
+                // the clients meeting the removed requirement keep their access when the operation
+                // becomes public, or when a remaining requirement asks them for no more
+                let removed = self.old.pointer(&change.old)?.as_object()?;
+                let requirements = self
+                    .new
+                    .pointer(&change.new)
+                    .and_then(|operation| operation.get("security"))
+                    .or_else(|| self.new.get("security"))
+                    .and_then(Value::as_array)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let covered = |requirement: &Value| {
+                    requirement.as_object().is_some_and(|requirement| {
+                        requirement.iter().all(|(name, scopes)| {
+                            let granted = removed.get(name).and_then(Value::as_array);
+                            granted.is_some_and(|granted| {
+                                scopes
+                                    .as_array()
+                                    .into_iter()
+                                    .flatten()
+                                    .all(|scope| granted.contains(scope))
+                            })
+                        })
+                    })
+                };
+                (!requirements.is_empty() && !requirements.iter().any(covered))
+                    .then_some(ChangeRule::StricterSecurity)
+            }
+            (Added, Element::SecurityRequirement) => {
+                // an operation without requirements is public, otherwise this is an alternative
+                let requirements = self
+                    .old
+                    .pointer(&change.old)
+                    .and_then(|operation| operation.get("security"))
+                    .or_else(|| self.old.get("security"));
+                let public = requirements
+                    .and_then(Value::as_array)
+                    .is_none_or(|requirements| requirements.is_empty());
+                public.then_some(ChangeRule::StricterSecurity)
+            }
+            (Changed, Element::Schema) => match field? {
+                "type" => Some(ChangeRule::TypeChanged),
+                "enum" if request => narrowed(old, new).then_some(ChangeRule::NarrowedEnum),
+                "enum" if response => narrowed(new, old).then_some(ChangeRule::WidenedResponseEnum),
+                "required" if request => {
+                    let names = |value: Option<&Value>| {
+                        value
+                            .and_then(Value::as_array)
+                            .into_iter()
+                            .flatten()
+                            .cloned()
+                            .collect::<Vec<_>>()
+                    };
+                    let old = names(old);
+                    names(new)
+                        .iter()
+                        .any(|name| !old.contains(name))
+                        .then_some(ChangeRule::NewRequiredRequestProperty)
+                }
+                "nullable" if response => {
+                    (new == Some(&Value::Bool(true))).then_some(ChangeRule::NullableResponse)
+                }
+                field if request => {
+                    tightened(field, old, new).then_some(ChangeRule::TightenedRequestConstraint)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Whether an enum accepts fewer values, which includes adding an enum.
+fn narrowed(old: Option<&Value>, new: Option<&Value>) -> bool {
+    match (old.and_then(Value::as_array), new.and_then(Value::as_array)) {
+        (_, None) => false,
+        (None, Some(_)) => true,
+        (Some(old), Some(new)) => old.iter().any(|value| !new.contains(value)),
+    }
+}
+
+/// Whether a change of a keyword makes a schema accept fewer values.
+fn tightened(field: &str, old: Option<&Value>, new: Option<&Value>) -> bool {
+    let number = |value: Option<&Value>| value.and_then(Value::as_f64);
+    let flag = |value: Option<&Value>| value == Some(&Value::Bool(true));
+    match field {
+        "maximum" | "maxLength" | "maxItems" | "maxProperties" => {
+            match (number(old), number(new)) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some(old), Some(new)) => new < old,
+            }
+        }
+        "minimum" => match (number(old), number(new)) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(old), Some(new)) => new > old,
+        },
+        // lengths and counts cannot be negative, so a missing minimum is 0
+        "minLength" | "minItems" | "minProperties" => {
+            number(new).unwrap_or(0.0) > number(old).unwrap_or(0.0)
+        }
+        "exclusiveMaximum" | "exclusiveMinimum" | "uniqueItems" => !flag(old) && flag(new),
+        "nullable" => flag(old) && !flag(new),
+        "pattern" | "multipleOf" | "format" => new.is_some(),
+        "additionalProperties" => new == Some(&Value::Bool(false)),
+        "readOnly" => flag(new),
+        _ => false,
+    }
+}
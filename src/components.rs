@@ -1,34 +1,129 @@
 use crate::{
-    Any, Callback, Example, Header, Link, Parameter, Referenceable, RequestBody, Response, Schema,
-    SecurityScheme,
+    Any, Callback, Example, Header, Link, Map, Parameter, Reference, Referenceable, RequestBody,
+    Response, Schema, SecurityScheme,
 };
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
-use std::collections::BTreeMap;
 
 /// Holds a set of reusable objects for different aspects of the OAS. All objects defined within the components object will have no effect on the API unless they are explicitly referenced from properties outside the components object.
 #[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Components {
     /// An object to hold reusable Schema Objects.
-    pub schemas: Option<BTreeMap<String, Referenceable<Schema>>>,
+    pub schemas: Option<Map<String, Referenceable<Schema>>>,
     /// An object to hold reusable Response Objects.
-    pub responses: Option<BTreeMap<String, Referenceable<Response>>>,
+    pub responses: Option<Map<String, Referenceable<Response>>>,
     /// An object to hold reusable Parameter Objects.
-    pub parameters: Option<BTreeMap<String, Referenceable<Parameter>>>,
+    pub parameters: Option<Map<String, Referenceable<Parameter>>>,
     /// An object to hold reusable Example Objects.
-    pub examples: Option<BTreeMap<String, Referenceable<Example>>>,
+    pub examples: Option<Map<String, Referenceable<Example>>>,
     /// An object to hold reusable Request Body Objects.
-    pub request_bodies: Option<BTreeMap<String, Referenceable<RequestBody>>>,
+    pub request_bodies: Option<Map<String, Referenceable<RequestBody>>>,
     /// An object to hold reusable Header Objects.
-    pub headers: Option<BTreeMap<String, Referenceable<Header>>>,
+    pub headers: Option<Map<String, Referenceable<Header>>>,
     /// An object to hold reusable Security Scheme Objects.
-    pub security_schemes: Option<BTreeMap<String, Referenceable<SecurityScheme>>>,
+    pub security_schemes: Option<Map<String, Referenceable<SecurityScheme>>>,
     /// An object to hold reusable Link Objects.
-    pub links: Option<BTreeMap<String, Referenceable<Link>>>,
+    pub links: Option<Map<String, Referenceable<Link>>>,
     /// An object to hold reusable Callback Objects.
-    pub callbacks: Option<BTreeMap<String, Referenceable<Callback>>>,
+    pub callbacks: Option<Map<String, Referenceable<Callback>>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
+}
+
+/// An object which can be defined in [Components] and referenced from elsewhere in the
+/// document with a [Reference](crate::Reference).
+pub trait Component: Sized {
+    /// The field of [Components] holding this kind of object, e.g. `schemas`.
+    const KIND: &'static str;
+
+    /// The objects of this kind defined in the given components.
+    fn components(components: &Components) -> Option<&Map<String, Referenceable<Self>>>;
+}
+
+macro_rules! impl_component {
+    ($($t:ty => $field:ident, $kind:literal,)+) => {
+        $(
+        impl Component for $t {
+            const KIND: &'static str = $kind;
+
+            fn components(components: &Components) -> Option<&Map<String, Referenceable<Self>>> {
+                components.$field.as_ref()
+            }
+        }
+        )+
+    };
+}
+impl_component! {
+    Schema => schemas, "schemas",
+    Response => responses, "responses",
+    Parameter => parameters, "parameters",
+    Example => examples, "examples",
+    RequestBody => request_bodies, "requestBodies",
+    Header => headers, "headers",
+    SecurityScheme => security_schemes, "securitySchemes",
+    Link => links, "links",
+    Callback => callbacks, "callbacks",
+}
+
+/// The number of references followed before giving up on a chain, which guards against cycles.
+const MAX_REFERENCE_DEPTH: usize = 32;
+
+impl Components {
+    /// Looks up the component a local reference such as `#/components/schemas/Pet` points to,
+    /// without following it any further.
+    pub fn get<T: Component>(&self, reference: &Reference) -> Option<&Referenceable<T>> {
+        let name = reference.component_name(T::KIND)?;
+        T::components(self)?.get(&name)
+    }
+
+    /// Returns the object itself, or follows references until an object is found. Returns `None`
+    /// for dangling, external and cyclic references.
+    pub fn resolve<'a, T: Component>(&'a self, mut value: &'a Referenceable<T>) -> Option<&'a T> {
+        for _ in 0..MAX_REFERENCE_DEPTH {
+            match value {
+                Referenceable::Data(data) => return Some(data),
+                Referenceable::Reference(reference) => value = self.get(reference)?,
+            }
+        }
+        None
+    }
+}
+
+impl<T: Component> Referenceable<T> {
+    /// Returns the object itself, or resolves the reference against the given components.
+    pub fn resolve<'a>(&'a self, components: Option<&'a Components>) -> Option<&'a T> {
+        match self {
+            Referenceable::Data(data) => Some(data),
+            reference => components?.resolve(reference),
+        }
+    }
+}
+
+impl Reference {
+    /// Creates a local reference to the component of the given kind, e.g. `#/components/schemas/Pet`.
+    pub fn component<T: Component>(name: &str) -> Reference {
+        Reference {
+            _ref: format!(
+                "#/components/{}/{}",
+                T::KIND,
+                name.replace('~', "~0").replace('/', "~1")
+            ),
+        }
+    }
+
+    /// The name of the component this reference points to if it is a local reference to a
+    /// component of the given kind, e.g. `Pet` for `#/components/schemas/Pet`.
+    pub fn component_name(&self, kind: &str) -> Option<String> {
+        let name = self
+            ._ref
+            .strip_prefix("#/components/")?
+            .strip_prefix(kind)?
+            .strip_prefix('/')?;
+        if name.contains('/') {
+            return None;
+        }
+        Some(name.replace("~1", "/").replace("~0", "~"))
+    }
 }
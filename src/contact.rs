@@ -1,11 +1,9 @@
-use std::collections::BTreeMap;
-
 use email_address::EmailAddress;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use url::Url;
 
-use crate::{Any, Parseable};
+use crate::{Any, Map, Parseable};
 
 /// Contact information for the exposed API.
 #[skip_serializing_none]
@@ -18,5 +16,5 @@ pub struct Contact {
     /// The email address of the contact person/organization. MUST be in the format of an email address.
     pub email: Option<Parseable<EmailAddress>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
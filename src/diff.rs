@@ -0,0 +1,874 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+use crate::pointer;
+use crate::{
+    AdditionalProperties, Component, Components, Header, Map, MediaRange, MediaType, Method,
+    OpenAPIV3, Operation, Parameter, ParameterIn, PathItem, Referenceable, RequestBody, Response,
+    Schema, SecurityRequirement,
+};
+
+/// Whether an element was added, removed or changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// The kind of object a [Change] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Element {
+    Path,
+    Operation,
+    Parameter,
+    RequestBody,
+    Response,
+    /// A header of a response.
+    Header,
+    MediaType,
+    Schema,
+    /// A property of an object schema.
+    Property,
+    SecurityRequirement,
+    SecurityScheme,
+}
+
+/// Whether a schema describes what clients send or what they receive, which tells whether
+/// a change of the schema can break them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Request,
+    Response,
+}
+
+/// A difference between two documents, found by [OpenAPIV3::diff].
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub element: Element,
+    /// The field of the element which changed, e.g. `required` or `maxLength`. `None` for
+    /// added and removed elements, and for elements which are compared as a whole.
+    pub field: Option<String>,
+    /// Whether the element is part of requests or responses, for parameters, request bodies,
+    /// responses and the schemas they use. Schemas only used by components have none.
+    pub direction: Option<Direction>,
+    /// The JSON Pointer to the element in the old document. For added elements, this points to
+    /// the object which would hold it.
+    pub old: String,
+    /// The JSON Pointer to the element in the new document. For removed elements, this points
+    /// to the object which would hold it.
+    pub new: String,
+    /// The old value of the field, for changes. `None` when the field was absent.
+    pub old_value: Option<Value>,
+    /// The new value of the field, for changes. `None` when the field is absent.
+    pub new_value: Option<Value>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "nothing".to_string(),
+        };
+        match self.kind {
+            ChangeKind::Added => write!(f, "added {:?} {}", self.element, self.new),
+            ChangeKind::Removed => write!(f, "removed {:?} {}", self.element, self.old),
+            ChangeKind::Changed => {
+                write!(f, "changed {:?} ", self.element)?;
+                if let Some(field) = &self.field {
+                    write!(f, "`{field}` ")?;
+                }
+                write!(
+                    f,
+                    "{} -> {}: {} -> {}",
+                    self.old,
+                    self.new,
+                    value(&self.old_value),
+                    value(&self.new_value)
+                )
+            }
+        }
+    }
+}
+
+impl OpenAPIV3 {
+    /// Compares this document with a newer version of it, and lists the paths, operations,
+    /// parameters, request bodies, responses, media types, schemas and security requirements
+    /// which were added, removed or changed.
+    ///
+    /// References are followed, so moving a schema to `components` is not a change, and the
+    /// changes of a shared schema are reported once per direction, with pointers to where the
+    /// schema is defined. Paths are matched regardless of the names of their templated
+    /// parameters, and parameters by name and location. Documentation fields such as
+    /// `description` and `example` are ignored.
+    pub fn diff(&self, new: &OpenAPIV3) -> Vec<Change> {
+        let mut differ = Differ {
+            old: self.components.as_ref(),
+            new: new.components.as_ref(),
+            direction: None,
+            visited: HashSet::new(),
+            changes: vec![],
+        };
+        differ.paths(self, new);
+        differ.security_schemes();
+        differ.component_schemas();
+        differ.changes
+    }
+}
+
+/// `/pets/{}` for `/pets/{id}`, to match paths whose parameters were renamed.
+pub(crate) fn normalize(path: &str) -> String {
+    let mut normalized = String::new();
+    let mut in_parameter = false;
+    for c in path.chars() {
+        match c {
+            '{' => {
+                in_parameter = true;
+                normalized.push_str("{}");
+            }
+            '}' => in_parameter = false,
+            c if !in_parameter => normalized.push(c),
+            _ => {}
+        }
+    }
+    normalized
+}
+
+fn to_value<T: Serialize>(value: &Option<T>) -> Option<Value> {
+    value
+        .as_ref()
+        .map(|value| serde_json::to_value(value).expect("the value can be serialized"))
+}
+
+/// The fields of a schema which are compared as values, rather than recursively.
+fn keywords(schema: &Schema) -> [(&'static str, Option<Value>); 23] {
+    [
+        ("type", to_value(&schema._type)),
+        ("format", to_value(&schema.format)),
+        ("nullable", to_value(&schema.nullable)),
+        ("multipleOf", to_value(&schema.multiple_of)),
+        ("maximum", to_value(&schema.maximum)),
+        ("exclusiveMaximum", to_value(&schema.exclusive_maximum)),
+        ("minimum", to_value(&schema.minimum)),
+        ("exclusiveMinimum", to_value(&schema.exclusive_minimum)),
+        ("maxLength", to_value(&schema.max_length)),
+        ("minLength", to_value(&schema.min_length)),
+        ("pattern", to_value(&schema.pattern)),
+        ("maxItems", to_value(&schema.max_items)),
+        ("minItems", to_value(&schema.min_items)),
+        ("uniqueItems", to_value(&schema.unique_items)),
+        ("maxProperties", to_value(&schema.max_properties)),
+        ("minProperties", to_value(&schema.min_properties)),
+        ("required", to_value(&schema.required)),
+        ("enum", to_value(&schema._enum)),
+        ("default", to_value(&schema.default)),
+        ("readOnly", to_value(&schema.read_only)),
+        ("writeOnly", to_value(&schema.write_only)),
+        ("deprecated", to_value(&schema.deprecated)),
+        (
+            "discriminator",
+            to_value(&schema.discriminator.as_ref().map(|d| &d.property_name)),
+        ),
+    ]
+}
+
+/// Whether two arrays hold the same items, in any order.
+fn same_items(old: &Option<Value>, new: &Option<Value>) -> bool {
+    match (old, new) {
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            old.iter().all(|item| new.contains(item)) && new.iter().all(|item| old.contains(item))
+        }
+        _ => old == new,
+    }
+}
+
+/// The security requirements as sets, since the order of the scopes does not matter.
+fn requirement(requirement: &SecurityRequirement) -> BTreeMap<&str, BTreeSet<&str>> {
+    requirement
+        .data
+        .iter()
+        .map(|(name, scopes)| (name.as_str(), scopes.iter().map(String::as_str).collect()))
+        .collect()
+}
+
+struct Differ<'a> {
+    old: Option<&'a Components>,
+    new: Option<&'a Components>,
+    /// The direction of the elements being compared.
+    direction: Option<Direction>,
+    /// The pairs of schemas already compared, by pointer and direction, which avoids reporting
+    /// the changes of shared schemas several times and guards against recursive schemas.
+    visited: HashSet<(String, String, Option<Direction>)>,
+    changes: Vec<Change>,
+}
+
+impl<'a> Differ<'a> {
+    fn push(&mut self, kind: ChangeKind, element: Element, old: &str, new: &str) {
+        self.changes.push(Change {
+            kind,
+            element,
+            field: None,
+            direction: self.direction,
+            old: old.to_string(),
+            new: new.to_string(),
+            old_value: None,
+            new_value: None,
+        });
+    }
+
+    fn field(
+        &mut self,
+        element: Element,
+        field: &str,
+        (old, new): (&str, &str),
+        old_value: Option<Value>,
+        new_value: Option<Value>,
+    ) {
+        if old_value != new_value {
+            self.changes.push(Change {
+                kind: ChangeKind::Changed,
+                element,
+                field: Some(field.to_string()),
+                direction: self.direction,
+                old: old.to_string(),
+                new: new.to_string(),
+                old_value,
+                new_value,
+            });
+        }
+    }
+
+    /// Compares two maps, calling `common` for the entries of both.
+    fn map<K: Ord, V>(
+        &mut self,
+        element: Element,
+        (old, new): (&str, &str),
+        old_entries: BTreeMap<K, (V, String)>,
+        mut new_entries: BTreeMap<K, (V, String)>,
+        mut common: impl FnMut(&mut Self, V, V, (&str, &str)),
+    ) {
+        for (key, (old_value, old_pointer)) in old_entries {
+            match new_entries.remove(&key) {
+                Some((new_value, new_pointer)) => {
+                    common(self, old_value, new_value, (&old_pointer, &new_pointer))
+                }
+                None => self.push(ChangeKind::Removed, element, &old_pointer, new),
+            }
+        }
+        for (_, new_pointer) in new_entries.into_values() {
+            self.push(ChangeKind::Added, element, old, &new_pointer);
+        }
+    }
+
+    fn paths(&mut self, old: &'a OpenAPIV3, new: &'a OpenAPIV3) {
+        let entries = |spec: &'a OpenAPIV3| {
+            spec.paths
+                .iter()
+                .map(|(path, item)| {
+                    let pointer = pointer::join("/paths", path);
+                    (normalize(path), ((path.as_str(), item), pointer))
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        self.map(
+            Element::Path,
+            ("/paths", "/paths"),
+            entries(old),
+            entries(new),
+            |differ, (old_path, old_item), (new_path, new_item), pointers| {
+                differ.path_item(
+                    (old, old_path, old_item),
+                    (new, new_path, new_item),
+                    pointers,
+                )
+            },
+        );
+    }
+
+    fn path_item(
+        &mut self,
+        (old_spec, old_path, old): (&'a OpenAPIV3, &str, &'a PathItem),
+        (new_spec, new_path, new): (&'a OpenAPIV3, &str, &'a PathItem),
+        (old_pointer, new_pointer): (&str, &str),
+    ) {
+        let entries = |item: &'a PathItem, pointer: &str| {
+            item.operations()
+                .map(|(method, operation)| {
+                    (method, (operation, pointer::join(pointer, method.as_str())))
+                })
+                .collect::<BTreeMap<Method, _>>()
+        };
+        self.map(
+            Element::Operation,
+            (old_pointer, new_pointer),
+            entries(old, old_pointer),
+            entries(new, new_pointer),
+            |differ, old_operation, new_operation, pointers| {
+                let parameters = |path, item, operation, components, pointers: (&str, &str)| {
+                    Self::parameters(path, item, operation, components, pointers)
+                };
+                differ.direction = Some(Direction::Request);
+                differ.map(
+                    Element::Parameter,
+                    pointers,
+                    parameters(
+                        old_path,
+                        old,
+                        old_operation,
+                        differ.old,
+                        (old_pointer, pointers.0),
+                    ),
+                    parameters(
+                        new_path,
+                        new,
+                        new_operation,
+                        differ.new,
+                        (new_pointer, pointers.1),
+                    ),
+                    |differ, old, new, pointers| differ.parameter(old, new, pointers),
+                );
+                differ.direction = None;
+                differ.operation(
+                    (old_spec, old_operation),
+                    (new_spec, new_operation),
+                    pointers,
+                );
+            },
+        );
+    }
+
+    /// The parameters of an operation, including the ones of its path item, by location and
+    /// name, with their pointers. Path parameters are identified by their position in the path,
+    /// since paths are matched regardless of their names.
+    fn parameters(
+        path: &str,
+        item: &'a PathItem,
+        operation: &'a Operation,
+        components: Option<&'a Components>,
+        (item_pointer, operation_pointer): (&str, &str),
+    ) -> BTreeMap<(ParameterIn, String), (&'a Parameter, String)> {
+        let mut parameters = BTreeMap::new();
+        for (parameters_of, pointer) in [
+            (&item.parameters, item_pointer),
+            (&operation.parameters, operation_pointer),
+        ] {
+            for (index, parameter) in parameters_of.iter().flatten().enumerate() {
+                let pointer =
+                    pointer::join(&pointer::join(pointer, "parameters"), index.to_string());
+                if let Some((parameter, pointer)) = resolve(components, parameter, pointer) {
+                    let position = || {
+                        path.split('{')
+                            .skip(1)
+                            .filter_map(|segment| Some(segment.split_once('}')?.0))
+                            .position(|name| name == parameter.name)
+                            .map_or(parameter.name.clone(), |position| format!("{{{position}}}"))
+                    };
+                    let name = match parameter._in {
+                        ParameterIn::Header => parameter.name.to_ascii_lowercase(),
+                        ParameterIn::Path => position(),
+                        _ => parameter.name.clone(),
+                    };
+                    parameters.insert((parameter._in, name), (parameter, pointer));
+                }
+            }
+        }
+        parameters
+    }
+
+    fn operation(
+        &mut self,
+        (old_spec, old): (&'a OpenAPIV3, &'a Operation),
+        (new_spec, new): (&'a OpenAPIV3, &'a Operation),
+        pointers @ (old_pointer, new_pointer): (&str, &str),
+    ) {
+        for (field, old_value, new_value) in [
+            (
+                "operationId",
+                to_value(&old.operation_id),
+                to_value(&new.operation_id),
+            ),
+            (
+                "deprecated",
+                to_value(&old.deprecated),
+                to_value(&new.deprecated),
+            ),
+        ] {
+            self.field(Element::Operation, field, pointers, old_value, new_value);
+        }
+
+        self.direction = Some(Direction::Request);
+        let request_body = |operation: &'a Operation, components, pointer: &str| {
+            operation
+                .request_body
+                .as_ref()
+                .and_then(|body| resolve(components, body, pointer::join(pointer, "requestBody")))
+                .map(|entry| ((), entry))
+                .into_iter()
+                .collect::<BTreeMap<_, _>>()
+        };
+        self.map(
+            Element::RequestBody,
+            pointers,
+            request_body(old, self.old, old_pointer),
+            request_body(new, self.new, new_pointer),
+            |differ, old, new, pointers| differ.request_body(old, new, pointers),
+        );
+
+        self.direction = Some(Direction::Response);
+        let responses = |operation: &'a Operation, components, pointer: &str| {
+            let pointer = pointer::join(pointer, "responses");
+            let default = operation
+                .responses
+                .default
+                .as_ref()
+                .map(|response| ("default".to_string(), response));
+            let codes = operation
+                .responses
+                .data
+                .iter()
+                .map(|(code, response)| (code.to_string(), response));
+            default
+                .into_iter()
+                .chain(codes)
+                .filter_map(|(code, response)| {
+                    let entry = resolve(components, response, pointer::join(&pointer, &code))?;
+                    Some((code, entry))
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        self.map(
+            Element::Response,
+            (
+                &pointer::join(old_pointer, "responses"),
+                &pointer::join(new_pointer, "responses"),
+            ),
+            responses(old, self.old, old_pointer),
+            responses(new, self.new, new_pointer),
+            |differ, old, new, pointers| differ.response(old, new, pointers),
+        );
+        self.direction = None;
+
+        // the requirements of the operation, or the ones of the document
+        let security = |spec: &'a OpenAPIV3, operation: &'a Operation, pointer: &str| {
+            let (requirements, pointer) = match &operation.security {
+                Some(requirements) => (Some(requirements), pointer::join(pointer, "security")),
+                None => (spec.security.as_ref(), "/security".to_string()),
+            };
+            requirements
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(index, requirement)| {
+                    (
+                        self::requirement(requirement),
+                        ((), pointer::join(&pointer, index.to_string())),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        self.map(
+            Element::SecurityRequirement,
+            pointers,
+            security(old_spec, old, old_pointer),
+            security(new_spec, new, new_pointer),
+            |_, (), (), _| {},
+        );
+    }
+
+    fn parameter(&mut self, old: &'a Parameter, new: &'a Parameter, pointers: (&str, &str)) {
+        for (field, old_value, new_value) in [
+            ("required", to_value(&old.required), to_value(&new.required)),
+            (
+                "deprecated",
+                to_value(&old.deprecated),
+                to_value(&new.deprecated),
+            ),
+            ("style", to_value(&old.style), to_value(&new.style)),
+            ("explode", to_value(&old.explode), to_value(&new.explode)),
+            (
+                "allowEmptyValue",
+                to_value(&old.allow_empty_value),
+                to_value(&new.allow_empty_value),
+            ),
+            (
+                "allowReserved",
+                to_value(&old.allow_reserved),
+                to_value(&new.allow_reserved),
+            ),
+        ] {
+            self.field(Element::Parameter, field, pointers, old_value, new_value);
+        }
+        self.optional_schema(old.schema.as_ref(), new.schema.as_ref(), pointers);
+        self.content(old.content.as_ref(), new.content.as_ref(), pointers);
+    }
+
+    fn request_body(&mut self, old: &'a RequestBody, new: &'a RequestBody, pointers: (&str, &str)) {
+        self.field(
+            Element::RequestBody,
+            "required",
+            pointers,
+            to_value(&old.required),
+            to_value(&new.required),
+        );
+        self.content(Some(&old.content), Some(&new.content), pointers);
+    }
+
+    fn response(
+        &mut self,
+        old: &'a Response,
+        new: &'a Response,
+        pointers @ (old_pointer, new_pointer): (&str, &str),
+    ) {
+        self.content(old.content.as_ref(), new.content.as_ref(), pointers);
+
+        // the names of headers are case-insensitive
+        let headers = |response: &'a Response, components, pointer: &str| {
+            let pointer = pointer::join(pointer, "headers");
+            response
+                .headers
+                .iter()
+                .flatten()
+                .filter_map(|(name, header)| {
+                    let entry = resolve(components, header, pointer::join(&pointer, name))?;
+                    Some((name.to_ascii_lowercase(), entry))
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        self.map(
+            Element::Header,
+            (
+                &pointer::join(old_pointer, "headers"),
+                &pointer::join(new_pointer, "headers"),
+            ),
+            headers(old, self.old, old_pointer),
+            headers(new, self.new, new_pointer),
+            |differ, old, new, pointers| differ.header(old, new, pointers),
+        );
+    }
+
+    fn header(&mut self, old: &'a Header, new: &'a Header, pointers: (&str, &str)) {
+        for (field, old_value, new_value) in [
+            ("required", to_value(&old.required), to_value(&new.required)),
+            (
+                "deprecated",
+                to_value(&old.deprecated),
+                to_value(&new.deprecated),
+            ),
+            ("style", to_value(&old.style), to_value(&new.style)),
+            ("explode", to_value(&old.explode), to_value(&new.explode)),
+        ] {
+            self.field(Element::Header, field, pointers, old_value, new_value);
+        }
+        self.optional_schema(old.schema.as_ref(), new.schema.as_ref(), pointers);
+        self.content(old.content.as_ref(), new.content.as_ref(), pointers);
+    }
+
+    fn content(
+        &mut self,
+        old: Option<&'a Map<MediaRange, MediaType>>,
+        new: Option<&'a Map<MediaRange, MediaType>>,
+        (old_pointer, new_pointer): (&str, &str),
+    ) {
+        let entries = |content: Option<&'a Map<MediaRange, MediaType>>, pointer: &str| {
+            let pointer = pointer::join(pointer, "content");
+            content
+                .into_iter()
+                .flatten()
+                .map(|(range, media_type)| {
+                    (range, (media_type, pointer::join(&pointer, range.as_str())))
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        self.map(
+            Element::MediaType,
+            (
+                &pointer::join(old_pointer, "content"),
+                &pointer::join(new_pointer, "content"),
+            ),
+            entries(old, old_pointer),
+            entries(new, new_pointer),
+            |differ, old, new, pointers| {
+                differ.optional_schema(old.schema.as_ref(), new.schema.as_ref(), pointers)
+            },
+        );
+    }
+
+    /// Compares the `schema` fields of two objects.
+    fn optional_schema(
+        &mut self,
+        old: Option<&'a Referenceable<Schema>>,
+        new: Option<&'a Referenceable<Schema>>,
+        (old_pointer, new_pointer): (&str, &str),
+    ) {
+        let old_pointer = pointer::join(old_pointer, "schema");
+        let new_pointer = pointer::join(new_pointer, "schema");
+        match (old, new) {
+            (Some(old), Some(new)) => self.schema(old, new, (&old_pointer, &new_pointer)),
+            (Some(_), None) => self.push(
+                ChangeKind::Removed,
+                Element::Schema,
+                &old_pointer,
+                &new_pointer,
+            ),
+            (None, Some(_)) => self.push(
+                ChangeKind::Added,
+                Element::Schema,
+                &old_pointer,
+                &new_pointer,
+            ),
+            (None, None) => {}
+        }
+    }
+
+    fn schema(
+        &mut self,
+        old: &'a Referenceable<Schema>,
+        new: &'a Referenceable<Schema>,
+        (old_pointer, new_pointer): (&str, &str),
+    ) {
+        let (Some((old_schema, old_pointer)), Some((new_schema, new_pointer))) = (
+            resolve(self.old, old, old_pointer.to_string()),
+            resolve(self.new, new, new_pointer.to_string()),
+        ) else {
+            // dangling or external references are compared as they are written
+            let reference = |schema: &Referenceable<Schema>| match schema {
+                Referenceable::Reference(reference) => Some(Value::String(reference._ref.clone())),
+                Referenceable::Data(_) => None,
+            };
+            self.field(
+                Element::Schema,
+                "$ref",
+                (old_pointer, new_pointer),
+                reference(old),
+                reference(new),
+            );
+            return;
+        };
+        let key = (old_pointer.clone(), new_pointer.clone(), self.direction);
+        if !self.visited.insert(key) {
+            return;
+        }
+        let pointers = (old_pointer.as_str(), new_pointer.as_str());
+
+        for ((field, old_value), (_, new_value)) in
+            keywords(old_schema).into_iter().zip(keywords(new_schema))
+        {
+            // the order of the required properties and of the allowed values does not matter
+            if matches!(field, "required" | "enum") && same_items(&old_value, &new_value) {
+                continue;
+            }
+            self.field(Element::Schema, field, pointers, old_value, new_value);
+        }
+
+        let properties = |schema: &'a Schema, pointer: &str| {
+            let pointer = pointer::join(pointer, "properties");
+            schema
+                .properties
+                .iter()
+                .flatten()
+                .map(|(name, property)| (name, (property, pointer::join(&pointer, name))))
+                .collect::<BTreeMap<_, _>>()
+        };
+        self.map(
+            Element::Property,
+            pointers,
+            properties(old_schema, &old_pointer),
+            properties(new_schema, &new_pointer),
+            |differ, old, new, pointers| differ.schema(old, new, pointers),
+        );
+
+        let boxed = |schema: Option<&'a Box<Referenceable<Schema>>>| schema.map(Box::as_ref);
+        for (field, old, new) in [
+            (
+                "items",
+                boxed(old_schema.items.as_ref()),
+                boxed(new_schema.items.as_ref()),
+            ),
+            (
+                "not",
+                boxed(old_schema.not.as_ref()),
+                boxed(new_schema.not.as_ref()),
+            ),
+        ] {
+            self.subschema(field, old, new, pointers);
+        }
+
+        let additional = |schema: &'a Schema| match &schema.additional_properties {
+            Some(AdditionalProperties::Schema(schema)) => (None, Some(schema.as_ref())),
+            other => (to_value(other), None),
+        };
+        let (old_allowed, old_additional) = additional(old_schema);
+        let (new_allowed, new_additional) = additional(new_schema);
+        match (old_additional, new_additional) {
+            (Some(old), Some(new)) => {
+                self.subschema("additionalProperties", Some(old), Some(new), pointers)
+            }
+            _ => self.field(
+                Element::Schema,
+                "additionalProperties",
+                pointers,
+                old_allowed.or_else(|| old_additional.map(|_| Value::Bool(true))),
+                new_allowed.or_else(|| new_additional.map(|_| Value::Bool(true))),
+            ),
+        }
+
+        for (field, old, new) in [
+            ("allOf", &old_schema.all_of, &new_schema.all_of),
+            ("oneOf", &old_schema.one_of, &new_schema.one_of),
+            ("anyOf", &old_schema.any_of, &new_schema.any_of),
+        ] {
+            let entries = |schemas: &'a Option<Vec<Referenceable<Schema>>>, pointer: &str| {
+                let pointer = pointer::join(pointer, field);
+                schemas
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .map(|(index, schema)| {
+                        (index, (schema, pointer::join(&pointer, index.to_string())))
+                    })
+                    .collect::<BTreeMap<_, _>>()
+            };
+            self.map(
+                Element::Schema,
+                pointers,
+                entries(old, &old_pointer),
+                entries(new, &new_pointer),
+                |differ, old, new, pointers| differ.schema(old, new, pointers),
+            );
+        }
+    }
+
+    /// Compares a field holding a single schema, such as `items`.
+    fn subschema(
+        &mut self,
+        field: &str,
+        old: Option<&'a Referenceable<Schema>>,
+        new: Option<&'a Referenceable<Schema>>,
+        (old_pointer, new_pointer): (&str, &str),
+    ) {
+        let old_field = pointer::join(old_pointer, field);
+        let new_field = pointer::join(new_pointer, field);
+        match (old, new) {
+            (Some(old), Some(new)) => self.schema(old, new, (&old_field, &new_field)),
+            (Some(_), None) => self.push(
+                ChangeKind::Removed,
+                Element::Schema,
+                &old_field,
+                new_pointer,
+            ),
+            (None, Some(_)) => {
+                self.push(ChangeKind::Added, Element::Schema, old_pointer, &new_field)
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn security_schemes(&mut self) {
+        let entries = |components: Option<&'a Components>| {
+            components
+                .and_then(|components| components.security_schemes.as_ref())
+                .into_iter()
+                .flatten()
+                .map(|(name, scheme)| {
+                    (
+                        name,
+                        (scheme, pointer::join("/components/securitySchemes", name)),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        self.map(
+            Element::SecurityScheme,
+            ("/components/securitySchemes", "/components/securitySchemes"),
+            entries(self.old),
+            entries(self.new),
+            |differ, old, new, pointers| {
+                let old = Some(serde_json::to_value(old).expect("the scheme can be serialized"));
+                let new = Some(serde_json::to_value(new).expect("the scheme can be serialized"));
+                if old != new {
+                    differ.changes.push(Change {
+                        kind: ChangeKind::Changed,
+                        element: Element::SecurityScheme,
+                        field: None,
+                        direction: None,
+                        old: pointers.0.to_string(),
+                        new: pointers.1.to_string(),
+                        old_value: old,
+                        new_value: new,
+                    });
+                }
+            },
+        );
+    }
+
+    /// Compares the schemas of `components`, except the ones already compared where they are
+    /// used.
+    fn component_schemas(&mut self) {
+        let entries = |components: Option<&'a Components>| {
+            components
+                .and_then(|components| components.schemas.as_ref())
+                .into_iter()
+                .flatten()
+                .map(|(name, schema)| (name, (schema, pointer::join("/components/schemas", name))))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let compared = self
+            .visited
+            .iter()
+            .map(|(old, new, _)| (old.clone(), new.clone()))
+            .collect::<HashSet<_>>();
+        let (mut old, mut new) = (entries(self.old), entries(self.new));
+        // schemas moved to or from `components` were compared where they are used
+        let (old_names, new_names) = (
+            old.keys().copied().collect::<HashSet<_>>(),
+            new.keys().copied().collect::<HashSet<_>>(),
+        );
+        old.retain(|name, (_, pointer)| {
+            new_names.contains(name) || !compared.iter().any(|(old, _)| old == pointer)
+        });
+        new.retain(|name, (_, pointer)| {
+            old_names.contains(name) || !compared.iter().any(|(_, new)| new == pointer)
+        });
+        self.map(
+            Element::Schema,
+            ("/components/schemas", "/components/schemas"),
+            old,
+            new,
+            |differ, old, new, pointers| {
+                if !compared.contains(&(pointers.0.to_string(), pointers.1.to_string())) {
+                    differ.schema(old, new, pointers);
+                }
+            },
+        );
+    }
+}
+
+/// Follows references, returning the object with the pointer to where it is defined.
+fn resolve<'a, T: Component>(
+    components: Option<&'a Components>,
+    mut value: &'a Referenceable<T>,
+    mut pointer: String,
+) -> Option<(&'a T, String)> {
+    // as many references as `Components::resolve` follows
+    for _ in 0..32 {
+        match value {
+            Referenceable::Data(data) => return Some((data, pointer)),
+            Referenceable::Reference(reference) => {
+                let name = reference.component_name(T::KIND)?;
+                value = components?.get(reference)?;
+                pointer = pointer::join(&format!("/components/{}", T::KIND), name);
+            }
+        }
+    }
+    None
+}
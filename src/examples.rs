@@ -0,0 +1,276 @@
+use std::fmt;
+
+use crate::pointer;
+use crate::{
+    Any, Callback, Components, Direction, Example, Header, Map, MediaRange, MediaType, OpenAPIV3,
+    Operation, Parameter, PathItem, Reference, Referenceable, RequestBody, Response, Schema,
+    ValidationError,
+};
+
+/// An example which does not conform to the schema it illustrates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExampleError {
+    /// The JSON Pointer to the example in the document, e.g.
+    /// `/paths/~1pets/get/responses/200/content/application~1json/example`. For examples defined
+    /// in `components/examples`, this points to the `value` of the component.
+    pub pointer: String,
+    /// The JSON Pointer to the reference of the example, when it is defined in
+    /// `components/examples`. Such examples are checked wherever they are referenced.
+    pub reference: Option<String>,
+    pub error: ValidationError,
+}
+
+impl fmt::Display for ExampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pointer)?;
+        if let Some(reference) = &self.reference {
+            write!(f, " (referenced from {reference})")?;
+        }
+        write!(f, ": {}", self.error)
+    }
+}
+
+impl std::error::Error for ExampleError {}
+
+impl OpenAPIV3 {
+    /// Validates the `example` and `examples` of parameters, media types and headers against
+    /// their sibling `schema`, or the schema of their single `content` entry.
+    ///
+    /// Examples of parameters and request bodies may leave out required `readOnly` properties,
+    /// and examples of responses may leave out required `writeOnly` properties, see
+    /// [Schema::validate_for]. Objects are checked where they are defined, in `paths` or in
+    /// `components`. Examples of `components/examples` have no schema of their own, so they are
+    /// checked against the schema of every object referencing them. External values are not
+    /// fetched.
+    pub fn validate_examples(&self) -> Vec<ExampleError> {
+        let mut checker = Checker {
+            components: self.components.as_ref(),
+            direction: None,
+            errors: vec![],
+        };
+        for (path, item) in &self.paths {
+            checker.path_item(item, &pointer::join("/paths", path));
+        }
+        if let Some(components) = &self.components {
+            checker.components(components);
+        }
+        checker.errors
+    }
+}
+
+struct Checker<'a> {
+    components: Option<&'a Components>,
+    /// Who sends the examples being checked, unknown for the headers of `components`.
+    direction: Option<Direction>,
+    errors: Vec<ExampleError>,
+}
+
+impl<'a> Checker<'a> {
+    fn components(&mut self, components: &'a Components) {
+        for (name, response) in components.responses.iter().flatten() {
+            self.inline(response, "/components/responses", name, Self::response);
+        }
+        for (name, parameter) in components.parameters.iter().flatten() {
+            self.inline(parameter, "/components/parameters", name, Self::parameter);
+        }
+        for (name, body) in components.request_bodies.iter().flatten() {
+            self.inline(body, "/components/requestBodies", name, Self::request_body);
+        }
+        for (name, header) in components.headers.iter().flatten() {
+            self.direction = None;
+            self.inline(header, "/components/headers", name, Self::header);
+        }
+        for (name, callback) in components.callbacks.iter().flatten() {
+            self.inline(callback, "/components/callbacks", name, Self::callback);
+        }
+    }
+
+    /// Checks an object unless it is a reference, which is checked where it is defined.
+    fn inline<T>(
+        &mut self,
+        value: &'a Referenceable<T>,
+        parent: &str,
+        token: impl AsRef<str>,
+        check: fn(&mut Self, &'a T, &str),
+    ) {
+        if let Referenceable::Data(data) = value {
+            check(self, data, &pointer::join(parent, token));
+        }
+    }
+
+    fn path_item(&mut self, item: &'a PathItem, at: &str) {
+        for (index, parameter) in item.parameters.iter().flatten().enumerate() {
+            let parameters = pointer::join(at, "parameters");
+            self.inline(parameter, &parameters, index.to_string(), Self::parameter);
+        }
+        for (method, operation) in item.operations() {
+            self.operation(operation, &pointer::join(at, method.as_str()));
+        }
+    }
+
+    fn operation(&mut self, operation: &'a Operation, at: &str) {
+        for (index, parameter) in operation.parameters.iter().flatten().enumerate() {
+            let parameters = pointer::join(at, "parameters");
+            self.inline(parameter, &parameters, index.to_string(), Self::parameter);
+        }
+        if let Some(Referenceable::Data(body)) = &operation.request_body {
+            self.request_body(body, &pointer::join(at, "requestBody"));
+        }
+        let responses = pointer::join(at, "responses");
+        if let Some(response) = &operation.responses.default {
+            self.inline(response, &responses, "default", Self::response);
+        }
+        for (code, response) in &operation.responses.data {
+            self.inline(response, &responses, code.to_string(), Self::response);
+        }
+        for (name, callback) in operation.callbacks.iter().flatten() {
+            let callbacks = pointer::join(at, "callbacks");
+            self.inline(callback, &callbacks, name, Self::callback);
+        }
+    }
+
+    fn callback(&mut self, callback: &'a Callback, at: &str) {
+        for (expression, item) in &callback.data {
+            self.path_item(item, &pointer::join(at, expression));
+        }
+    }
+
+    fn request_body(&mut self, body: &'a RequestBody, at: &str) {
+        self.direction = Some(Direction::Request);
+        self.content(&body.content, &pointer::join(at, "content"));
+    }
+
+    fn response(&mut self, response: &'a Response, at: &str) {
+        self.direction = Some(Direction::Response);
+        for (name, header) in response.headers.iter().flatten() {
+            self.inline(header, &pointer::join(at, "headers"), name, Self::header);
+        }
+        if let Some(content) = &response.content {
+            self.content(content, &pointer::join(at, "content"));
+        }
+    }
+
+    fn parameter(&mut self, parameter: &'a Parameter, at: &str) {
+        self.direction = Some(Direction::Request);
+        self.examples(
+            schema_of(&parameter.schema, &parameter.content),
+            &parameter.example,
+            &parameter.examples,
+            at,
+        );
+        if let Some(content) = &parameter.content {
+            self.content(content, &pointer::join(at, "content"));
+        }
+    }
+
+    fn header(&mut self, header: &'a Header, at: &str) {
+        self.examples(
+            schema_of(&header.schema, &header.content),
+            &header.example,
+            &header.examples,
+            at,
+        );
+        if let Some(content) = &header.content {
+            self.content(content, &pointer::join(at, "content"));
+        }
+    }
+
+    fn content(&mut self, content: &'a Map<MediaRange, MediaType>, at: &str) {
+        for (key, media_type) in content {
+            let at = pointer::join(at, key.as_str());
+            self.examples(
+                media_type.schema.as_ref(),
+                &media_type.example,
+                &media_type.examples,
+                &at,
+            );
+            for (name, encoding) in media_type.encoding.iter().flatten() {
+                let headers = pointer::join(&pointer::join(&at, "encoding"), name);
+                let headers = pointer::join(&headers, "headers");
+                for (header_name, header) in encoding.headers.iter().flatten() {
+                    self.inline(header, &headers, header_name, Self::header);
+                }
+            }
+        }
+    }
+
+    fn examples(
+        &mut self,
+        schema: Option<&'a Referenceable<Schema>>,
+        example: &'a Option<Any>,
+        examples: &'a Option<Map<String, Referenceable<Example>>>,
+        at: &str,
+    ) {
+        let Some(schema) = schema else {
+            return;
+        };
+        if let Some(example) = example {
+            self.check(schema, example, pointer::join(at, "example"), None);
+        }
+        let examples_at = pointer::join(at, "examples");
+        for (name, example) in examples.iter().flatten() {
+            let at = pointer::join(&examples_at, name);
+            match example {
+                Referenceable::Data(Example {
+                    value: Some(value), ..
+                }) => self.check(schema, value, pointer::join(&at, "value"), None),
+                Referenceable::Data(_) => {}
+                Referenceable::Reference(reference) => {
+                    if let Some((target, value)) = self.referenced(reference) {
+                        self.check(schema, value, pointer::join(&target, "value"), Some(at));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The value of a referenced example and the pointer to the example defining it.
+    fn referenced(&self, mut reference: &'a Reference) -> Option<(String, &'a Any)> {
+        let components = self.components?;
+        for _ in 0..32 {
+            match components.get::<Example>(reference)? {
+                Referenceable::Reference(next) => reference = next,
+                Referenceable::Data(example) => {
+                    let target = reference._ref.strip_prefix('#')?.to_string();
+                    return Some((target, example.value.as_ref()?));
+                }
+            }
+        }
+        None
+    }
+
+    fn check(
+        &mut self,
+        schema: &Referenceable<Schema>,
+        value: &Any,
+        pointer: String,
+        reference: Option<String>,
+    ) {
+        let result = match self.direction {
+            Some(direction) => schema.validate_for(value, direction, self.components),
+            None => schema.validate(value, self.components),
+        };
+        for error in result.err().into_iter().flatten() {
+            self.errors.push(ExampleError {
+                pointer: pointer.clone(),
+                reference: reference.clone(),
+                error,
+            });
+        }
+    }
+}
+
+/// The schema of a parameter or header, which is either its `schema` or the one of its single
+/// `content` entry.
+fn schema_of<'a>(
+    schema: &'a Option<Referenceable<Schema>>,
+    content: &'a Option<Map<MediaRange, MediaType>>,
+) -> Option<&'a Referenceable<Schema>> {
+    schema.as_ref().or_else(|| {
+        content
+            .iter()
+            .flatten()
+            .next()
+            .and_then(|(_, media_type)| media_type.schema.as_ref())
+    })
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::{BodyError, ContentNegotiation, Direction, OpenAPIV3, ParameterIn, RouteMatch};
+
+/// The parameters and the body of a request, parsed and validated against the operation it was
+/// routed to with [RouteMatch::extract].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RequestParts {
+    parameters: Vec<(ParameterIn, String, Value)>,
+    /// The decoded body, for JSON and form bodies. Other bodies are left to the caller.
+    pub body: Option<Value>,
+}
+
+/// A part of a request which does not conform to its operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestError {
+    /// The location and the name of the parameter, or `None` for the body.
+    pub parameter: Option<(ParameterIn, String)>,
+    /// The JSON Pointer to the invalid value within the parameter or the body.
+    pub pointer: String,
+    pub message: String,
+}
+
+/// The reasons why the parts of a request cannot be extracted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    /// The `Content-Type` of the body is not one of the operation.
+    UnsupportedMediaType(String),
+    /// Parameters or the body are missing or invalid.
+    Invalid(Vec<RequestError>),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.parameter {
+            Some((location, name)) => {
+                let location = format!("{location:?}").to_lowercase();
+                write!(f, "{location} parameter `{name}`")?
+            }
+            None => write!(f, "body")?,
+        }
+        if !self.pointer.is_empty() {
+            write!(f, " at {}", self.pointer)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::UnsupportedMediaType(content_type) => {
+                write!(f, "unsupported media type `{content_type}`")
+            }
+            ExtractError::Invalid(errors) => {
+                let errors = errors
+                    .iter()
+                    .map(RequestError::to_string)
+                    .collect::<Vec<_>>();
+                write!(f, "invalid request: {}", errors.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+impl ExtractError {
+    /// The status code of a response rejecting the request, `415` or `400`.
+    pub fn status(&self) -> u16 {
+        match self {
+            ExtractError::UnsupportedMediaType(_) => 415,
+            ExtractError::Invalid(_) => 400,
+        }
+    }
+
+    /// The body of a response rejecting the request, e.g.
+    /// `{"error": "invalid request", "errors": [{"location": "query", "name": "limit",
+    /// "pointer": "", "message": "..."}]}`. The location of errors in the body is `body`.
+    pub fn to_json(&self) -> Value {
+        let ExtractError::Invalid(errors) = self else {
+            return json!({ "error": self.to_string() });
+        };
+        let errors = errors
+            .iter()
+            .map(|error| match &error.parameter {
+                Some((location, name)) => json!({
+                    "location": location,
+                    "name": name,
+                    "pointer": error.pointer,
+                    "message": error.message,
+                }),
+                None => json!({
+                    "location": "body",
+                    "pointer": error.pointer,
+                    "message": error.message,
+                }),
+            })
+            .collect::<Vec<_>>();
+        json!({ "error": "invalid request", "errors": errors })
+    }
+}
+
+impl RequestError {
+    fn new(parameter: Option<(ParameterIn, &str)>, pointer: &str, message: String) -> Self {
+        RequestError {
+            parameter: parameter.map(|(location, name)| (location, name.to_string())),
+            pointer: pointer.to_string(),
+            message,
+        }
+    }
+}
+
+impl RequestParts {
+    /// The value of a parameter, converted to a Rust type. Returns `None` when the parameter is
+    /// absent.
+    pub fn parameter<T: DeserializeOwned>(
+        &self,
+        location: ParameterIn,
+        name: &str,
+    ) -> Result<Option<T>, RequestError> {
+        let Some((_, _, value)) = self
+            .parameters
+            .iter()
+            .find(|(other, other_name, _)| *other == location && other_name == name)
+        else {
+            return Ok(None);
+        };
+        serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|error| RequestError::new(Some((location, name)), "", error.to_string()))
+    }
+
+    /// The value of a parameter which must be present, converted to a Rust type.
+    pub fn required<T: DeserializeOwned>(
+        &self,
+        location: ParameterIn,
+        name: &str,
+    ) -> Result<T, RequestError> {
+        self.parameter(location, name)?.ok_or_else(|| {
+            let message = format!("missing required parameter `{name}`");
+            RequestError::new(Some((location, name)), "", message)
+        })
+    }
+
+    /// The decoded body, converted to a Rust type. Returns `None` when there is no body.
+    pub fn body<T: DeserializeOwned>(&self) -> Result<Option<T>, RequestError> {
+        self.body
+            .clone()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|error| RequestError::new(None, "", error.to_string()))
+    }
+
+    /// The decoded body of a request which must have one, converted to a Rust type.
+    pub fn required_body<T: DeserializeOwned>(&self) -> Result<T, RequestError> {
+        self.body()?
+            .ok_or_else(|| RequestError::new(None, "", "missing required request body".to_string()))
+    }
+}
+
+impl RouteMatch {
+    /// Parses the parameters and decodes the body of a request routed to an operation of the
+    /// document, and validates them against their schemas. `target` is the requested path with
+    /// its query string, and the body is decoded according to the `Content-Type` header,
+    /// defaulting to JSON.
+    ///
+    /// All the errors are reported at once. Bodies of media types which are neither JSON nor
+    /// forms are accepted as is when the operation has such a media type.
+    pub fn extract(
+        &self,
+        spec: &OpenAPIV3,
+        target: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<RequestParts, ExtractError> {
+        let Some((item, operation)) = self.operation(spec) else {
+            return Ok(RequestParts::default());
+        };
+        let components = spec.components.as_ref();
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        };
+        let query = target.split_once('?').map_or("", |(_, query)| query);
+        let query = query.split('#').next().unwrap_or_default();
+        let mut parts = RequestParts::default();
+        let mut errors = vec![];
+        for parameter in item.parameters_for(operation, components) {
+            let raw = match parameter._in {
+                ParameterIn::Path => self.parameters.get(&parameter.name).cloned(),
+                ParameterIn::Query => Some(query.to_string()),
+                ParameterIn::Header => header(&parameter.name).map(str::to_string),
+                ParameterIn::Cookie => cookie(headers, &parameter.name),
+            };
+            let at = Some((parameter._in, parameter.name.as_str()));
+            let Some(value) = raw.and_then(|raw| parameter.parse(&raw, components)) else {
+                if parameter.required.unwrap_or(false) || parameter._in == ParameterIn::Path {
+                    let message = format!("missing required parameter `{}`", parameter.name);
+                    errors.push(RequestError::new(at, "", message));
+                }
+                continue;
+            };
+            if let Some(schema) = &parameter.schema {
+                for error in schema
+                    .validate_for(&value, Direction::Request, components)
+                    .err()
+                    .into_iter()
+                    .flatten()
+                {
+                    errors.push(RequestError::new(at, &error.pointer, error.message));
+                }
+            }
+            parts
+                .parameters
+                .push((parameter._in, parameter.name.clone(), value));
+        }
+
+        let request_body = operation
+            .request_body
+            .as_ref()
+            .and_then(|body| body.resolve(components));
+        if let Some(request_body) = request_body {
+            let content_type = header("content-type").unwrap_or("application/json");
+            if body.is_empty() {
+                if request_body.required.unwrap_or(false) {
+                    let message = "missing required request body".to_string();
+                    errors.push(RequestError::new(None, "", message));
+                }
+            } else {
+                match request_body.decode(content_type, body, components) {
+                    Ok(value) => parts.body = Some(value),
+                    Err(BodyError::UnsupportedMediaType(_))
+                        if request_body
+                            .content
+                            .for_content_type(content_type)
+                            .is_some() => {}
+                    Err(BodyError::UnsupportedMediaType(_)) => {
+                        return Err(ExtractError::UnsupportedMediaType(content_type.to_string()));
+                    }
+                    Err(BodyError::Invalid(invalid)) => errors.extend(
+                        invalid
+                            .into_iter()
+                            .map(|error| RequestError::new(None, &error.pointer, error.message)),
+                    ),
+                    Err(error) => errors.push(RequestError::new(None, "", error.to_string())),
+                }
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(parts),
+            false => Err(ExtractError::Invalid(errors)),
+        }
+    }
+}
+
+fn cookie(headers: &[(String, String)], name: &str) -> Option<String> {
+    headers
+        .iter()
+        .filter(|(header, _)| header.eq_ignore_ascii_case("cookie"))
+        .flat_map(|(_, value)| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(cookie, _)| *cookie == name)
+        .map(|(_, value)| value.to_string())
+}
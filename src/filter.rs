@@ -0,0 +1,119 @@
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::unused::{components, retain_components, used_components};
+use crate::{Any, Map, Method, OpenAPIV3, Operation};
+
+/// The operations kept by [OpenAPIV3::filter_operations], e.g. to publish the public operations
+/// of a document only. An operation is kept when it matches all the criteria.
+#[derive(Debug, Clone, Default)]
+pub struct OperationFilter {
+    /// Keeps the operations with at least one of these tags.
+    pub tags: Option<Vec<String>>,
+    /// Drops the operations with one of these tags.
+    pub exclude_tags: Vec<String>,
+    /// Keeps the operations of the paths starting with one of these prefixes, e.g. `/public`.
+    pub path_prefixes: Option<Vec<String>>,
+    /// Keeps the operations with one of these methods.
+    pub methods: Option<Vec<Method>>,
+    /// Drops the operations, or the operations of path items, with one of these extensions set
+    /// to `true`, e.g. `x-internal`.
+    pub exclude_extensions: Vec<String>,
+    /// Drops the deprecated operations.
+    pub exclude_deprecated: bool,
+}
+
+impl OperationFilter {
+    /// Whether an operation is kept.
+    pub fn matches(&self, path: &str, method: Method, operation: &Operation) -> bool {
+        let tags = operation.tags.as_deref().unwrap_or_default();
+        let flagged = |extras: &Option<Map<String, Any>>| {
+            self.exclude_extensions.iter().any(|extension| {
+                extras
+                    .as_ref()
+                    .and_then(|extras| extras.get(extension))
+                    .is_some_and(|value| *value == Value::Bool(true))
+            })
+        };
+        self.tags
+            .as_ref()
+            .is_none_or(|kept| tags.iter().any(|tag| kept.contains(tag)))
+            && !tags.iter().any(|tag| self.exclude_tags.contains(tag))
+            && self.path_prefixes.as_ref().is_none_or(|prefixes| {
+                prefixes
+                    .iter()
+                    .any(|prefix| path.starts_with(prefix.as_str()))
+            })
+            && self
+                .methods
+                .as_ref()
+                .is_none_or(|methods| methods.contains(&method))
+            && !flagged(&operation.extras)
+            && !(self.exclude_deprecated && operation.deprecated == Some(true))
+    }
+}
+
+impl OpenAPIV3 {
+    /// A copy of the document with the operations matching a filter only.
+    ///
+    /// See [OpenAPIV3::filter].
+    pub fn filter_operations(&self, filter: &OperationFilter) -> OpenAPIV3 {
+        let flagged = |path: &str| {
+            self.paths[path].extras.as_ref().is_some_and(|extras| {
+                filter
+                    .exclude_extensions
+                    .iter()
+                    .any(|extension| extras.get(extension) == Some(&Value::Bool(true)))
+            })
+        };
+        self.filter(|path, method, operation| {
+            !flagged(path) && filter.matches(path, method, operation)
+        })
+    }
+
+    /// A copy of the document with the operations for which `keep` returns `true` only, given
+    /// their path and method.
+    ///
+    /// The paths left without operations are removed, along with the components and tags which
+    /// were used by the removed operations only. Components and tags which were not used in the
+    /// first place are kept.
+    pub fn filter(&self, mut keep: impl FnMut(&str, Method, &Operation) -> bool) -> OpenAPIV3 {
+        let mut filtered = self.clone();
+        filtered.paths.retain(|path, item| {
+            if item.operations().next().is_none() {
+                return true;
+            }
+            for method in Method::ALL {
+                let operation = item.operation_mut(method);
+                if operation
+                    .as_ref()
+                    .is_some_and(|operation| !keep(path, method, operation))
+                {
+                    *operation = None;
+                }
+            }
+            item.operations().next().is_some()
+        });
+
+        let tags = |spec: &OpenAPIV3| {
+            spec.operations()
+                .flat_map(|(_, _, operation)| operation.tags.iter().flatten().cloned())
+                .collect::<BTreeSet<_>>()
+        };
+        let (used, kept) = (tags(self), tags(&filtered));
+        if let Some(declared) = &mut filtered.tags {
+            declared.retain(|tag| kept.contains(&tag.name) || !used.contains(&tag.name));
+        }
+
+        let before = self.to_value();
+        let mut after = filtered.to_value();
+        let unused = components(&before)
+            .difference(&used_components(&before, vec![]))
+            .cloned()
+            .collect();
+        let used = used_components(&after, unused);
+        retain_components(&mut after, &used);
+        serde_json::from_value(after).expect("the filtered document is valid")
+    }
+}
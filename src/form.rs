@@ -0,0 +1,655 @@
+use mediatype::MediaTypeBuf;
+use serde_json::{Map, Number, Value};
+
+use crate::body::boundary;
+use crate::style::primitive_to_string;
+use crate::{
+    BodyError, Components, Encoding, Format, Header, MediaType, Parseable, Schema, Style, Type,
+};
+
+/// A part of a `multipart/form-data` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    /// The name of the form field, from the `Content-Disposition` header.
+    pub name: String,
+    /// The name of the uploaded file, from the `Content-Disposition` header.
+    pub filename: Option<String>,
+    /// The headers of the part, in the order they appear in the body.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Part {
+    /// The value of a header, looked up case insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The value of the `Content-Type` header, which defaults to `text/plain`.
+    pub fn content_type(&self) -> &str {
+        self.header("content-type").unwrap_or("text/plain")
+    }
+}
+
+/// Splits a `multipart/form-data` body into its parts.
+pub fn parse_multipart(body: &[u8], boundary: &str) -> Result<Vec<Part>, BodyError> {
+    let delimiter = format!("--{boundary}");
+    let malformed = |message: &str| BodyError::Malformed(message.to_string());
+    let start = find(body, delimiter.as_bytes()).ok_or_else(|| malformed("missing boundary"))?;
+    let mut rest = &body[start + delimiter.len()..];
+    let closing = format!("\r\n{delimiter}");
+    let mut parts = vec![];
+    loop {
+        if rest.starts_with(b"--") {
+            return Ok(parts);
+        }
+        while let [b' ' | b'\t', tail @ ..] = rest {
+            rest = tail;
+        }
+        rest = rest
+            .strip_prefix(b"\r\n")
+            .ok_or_else(|| malformed("expected a line break after the boundary"))?;
+        let end = find(rest, closing.as_bytes()).ok_or_else(|| malformed("unterminated part"))?;
+        parts.push(parse_part(&rest[..end])?);
+        rest = &rest[end + closing.len()..];
+    }
+}
+
+fn parse_part(part: &[u8]) -> Result<Part, BodyError> {
+    let (head, body) = match part.strip_prefix(b"\r\n") {
+        Some(body) => (&[][..], body),
+        None => {
+            let end = find(part, b"\r\n\r\n")
+                .ok_or_else(|| BodyError::Malformed("unterminated part headers".to_string()))?;
+            (&part[..end], &part[end + 4..])
+        }
+    };
+    let headers = String::from_utf8_lossy(head)
+        .split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect::<Vec<_>>();
+    let disposition = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-disposition"))
+        .map(|(_, value)| disposition_params(value))
+        .unwrap_or_default();
+    let param = |key: &str| {
+        disposition
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.clone())
+    };
+    Ok(Part {
+        name: param("name").ok_or_else(|| {
+            BodyError::Malformed("part without a name in its Content-Disposition".to_string())
+        })?,
+        filename: param("filename"),
+        headers,
+        body: body.to_vec(),
+    })
+}
+
+/// The parameters of a `Content-Disposition` header such as `form-data; name="file"`.
+fn disposition_params(value: &str) -> Vec<(String, String)> {
+    let mut params = vec![];
+    let mut rest = value.split_once(';').map_or("", |(_, rest)| rest);
+    while let Some((name, tail)) = rest.split_once('=') {
+        let tail = tail.trim_start();
+        let (value, tail) = match tail.strip_prefix('"') {
+            Some(quoted) => {
+                let mut value = String::new();
+                let mut chars = quoted.char_indices();
+                let mut end = quoted.len();
+                while let Some((index, c)) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                        '"' => {
+                            end = index + 1;
+                            break;
+                        }
+                        c => value.push(c),
+                    }
+                }
+                (value, &quoted[end..])
+            }
+            None => {
+                let end = tail.find(';').unwrap_or(tail.len());
+                (tail[..end].trim().to_string(), &tail[end..])
+            }
+        };
+        params.push((name.trim().to_string(), value));
+        rest = tail.split_once(';').map_or("", |(_, rest)| rest);
+    }
+    params
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// A `multipart/form-data` body along with the boundary separating its parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartBody {
+    pub boundary: String,
+    pub body: Vec<u8>,
+}
+
+impl MultipartBody {
+    /// Encodes fields without a schema, with a random boundary: objects and arrays are sent as
+    /// JSON, files as they are and other values as text. This is how the clients generated by
+    /// [RustGenerator::client](crate::RustGenerator::client) send multipart bodies.
+    pub fn new(fields: &crate::Map<String, FormValue>) -> MultipartBody {
+        let media_type = MediaType {
+            schema: None,
+            example: None,
+            examples: None,
+            encoding: None,
+            extras: None,
+        };
+        // a boundary must not appear within the parts, which is unlikely but possible
+        let contents = fields
+            .values()
+            .flat_map(Item::all)
+            .map(|item| match item {
+                Item::Value(value) => value.to_string().into_bytes(),
+                Item::File(_, content) => content.to_vec(),
+            })
+            .collect::<Vec<_>>();
+        let boundary = std::iter::repeat_with(boundary)
+            .find(|boundary| {
+                contents
+                    .iter()
+                    .all(|content| find(content, boundary.as_bytes()).is_none())
+            })
+            .expect("the iterator is infinite");
+        media_type.encode_multipart(fields, &boundary, None)
+    }
+
+    /// The value of the `Content-Type` header to send along with the body.
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+}
+
+/// A field of a `multipart/form-data` body. Unlike a JSON [Value], it keeps the raw content of
+/// files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormValue {
+    /// A value sent as JSON or as text.
+    Value(Value),
+    /// The content of a file, sent as is.
+    File {
+        filename: Option<String>,
+        content: Vec<u8>,
+    },
+    /// Values sent as one part each, e.g. the parts of an array property or of a repeated field.
+    Parts(Vec<FormValue>),
+}
+
+impl FormValue {
+    /// The field as a JSON value, e.g. to validate it. The content of files is decoded lossily as
+    /// UTF-8.
+    pub fn to_value(&self) -> Value {
+        match self {
+            FormValue::Value(value) => value.clone(),
+            FormValue::File { content, .. } => {
+                Value::String(String::from_utf8_lossy(content).into_owned())
+            }
+            FormValue::Parts(values) => {
+                Value::Array(values.iter().map(FormValue::to_value).collect())
+            }
+        }
+    }
+}
+
+impl From<Value> for FormValue {
+    fn from(value: Value) -> FormValue {
+        FormValue::Value(value)
+    }
+}
+
+impl MediaType {
+    /// The schema of the media type, with references resolved.
+    pub fn resolved_schema<'a>(&'a self, components: Option<&'a Components>) -> Option<&'a Schema> {
+        self.schema.as_ref()?.resolve(components)
+    }
+
+    /// The encoding information of a property, if any.
+    pub fn encoding_for(&self, property: &str) -> Option<&Encoding> {
+        self.encoding.as_ref()?.get(property)
+    }
+
+    /// Encodes the properties of an object as an `application/x-www-form-urlencoded` body. Each
+    /// property is serialized according to the `style`, `explode` and `allowReserved` of its
+    /// [Encoding], defaulting to the `form` style. `null` properties are omitted.
+    pub fn encode_urlencoded(&self, value: &Map<String, Value>) -> String {
+        value
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(name, value)| {
+                let encoding = self.encoding_for(name);
+                let style = encoding
+                    .and_then(|encoding| encoding.style.clone())
+                    .unwrap_or(Style::Form);
+                let explode = encoding
+                    .and_then(|encoding| encoding.explode)
+                    .unwrap_or_else(|| style.default_explode());
+                let allow_reserved = encoding
+                    .and_then(|encoding| encoding.allow_reserved)
+                    .unwrap_or(false);
+                style.serialize_query(name, value, explode, allow_reserved)
+            })
+            .filter(|pair| !pair.is_empty())
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// Encodes the fields of a form as a `multipart/form-data` body with one part per field, or
+    /// one part per item for arrays. `null` values are omitted.
+    ///
+    /// The `Content-Type` of a part is the first non wildcard media type of the `contentType` of
+    /// its [Encoding], and otherwise `application/octet-stream` for files and binary strings,
+    /// `application/json` for objects and `text/plain` for other primitives. Files and binary
+    /// strings are sent with a `filename`, which defaults to the name of the field. The `headers`
+    /// of the [Encoding] are added to the part using the `example` of each [Header]; the
+    /// components are used to resolve referenced headers and examples.
+    pub fn encode_multipart(
+        &self,
+        value: &crate::Map<String, FormValue>,
+        boundary: &str,
+        components: Option<&Components>,
+    ) -> MultipartBody {
+        let schema = self.resolved_schema(components);
+        let mut body = Vec::new();
+        for (name, value) in value {
+            let encoding = self.encoding_for(name);
+            let mut property = schema
+                .and_then(|schema| schema.property(name, components))
+                .and_then(|property| property.resolve(components));
+            let items = match value {
+                FormValue::Value(Value::Array(items)) => items.iter().map(Item::Value).collect(),
+                FormValue::Parts(values) => values.iter().flat_map(Item::all).collect(),
+                value => Item::all(value),
+            };
+            if matches!(
+                value,
+                FormValue::Value(Value::Array(_)) | FormValue::Parts(_)
+            ) {
+                property = property
+                    .and_then(|property| property.items.as_deref())
+                    .and_then(|items| items.resolve(components));
+            }
+            for item in items {
+                let (filename, content) = match item {
+                    Item::Value(Value::Null) => continue,
+                    Item::Value(value) => (
+                        is_binary(property).then_some(name.as_str()),
+                        primitive_to_string(value).into_bytes(),
+                    ),
+                    Item::File(filename, content) => {
+                        (Some(filename.unwrap_or(name)), content.to_vec())
+                    }
+                };
+                body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+                let mut disposition = format!("form-data; name=\"{}\"", escape_quoted(name));
+                if let Some(filename) = filename {
+                    disposition.push_str(&format!("; filename=\"{}\"", escape_quoted(filename)));
+                }
+                body.extend_from_slice(
+                    format!("Content-Disposition: {disposition}\r\n").as_bytes(),
+                );
+                body.extend_from_slice(
+                    format!(
+                        "Content-Type: {}\r\n",
+                        part_content_type(encoding, property, &item)
+                    )
+                    .as_bytes(),
+                );
+                for (header, value) in part_headers(encoding, components) {
+                    body.extend_from_slice(format!("{header}: {value}\r\n").as_bytes());
+                }
+                body.extend_from_slice(b"\r\n");
+                body.extend_from_slice(&content);
+                body.extend_from_slice(b"\r\n");
+            }
+        }
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+        MultipartBody {
+            boundary: boundary.to_string(),
+            body,
+        }
+    }
+}
+
+/// A part of a multipart body to encode.
+enum Item<'a> {
+    Value(&'a Value),
+    File(Option<&'a str>, &'a [u8]),
+}
+
+impl Item<'_> {
+    /// The parts of a field, flattening nested [FormValue::Parts].
+    fn all(value: &FormValue) -> Vec<Item<'_>> {
+        match value {
+            FormValue::Value(value) => vec![Item::Value(value)],
+            FormValue::File { filename, content } => vec![Item::File(filename.as_deref(), content)],
+            FormValue::Parts(values) => values.iter().flat_map(Item::all).collect(),
+        }
+    }
+}
+
+impl MediaType {
+    /// Decodes an `application/x-www-form-urlencoded` body into an object, reversing
+    /// [MediaType::encode_urlencoded]. The properties of the schema are read according to their
+    /// [Encoding] and converted to the type of their schema; other fields are kept as strings, or
+    /// as arrays of strings when repeated. The result is not validated.
+    pub fn decode_urlencoded(&self, body: &str, components: Option<&Components>) -> Value {
+        let mut pairs = url::form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .map(Some)
+            .collect::<Vec<_>>();
+        let mut object = Map::new();
+        let schema = self.resolved_schema(components);
+        let properties = schema
+            .map(|schema| schema.all_properties(components))
+            .unwrap_or_default();
+        let claimed = properties.keys().copied().collect::<Vec<_>>();
+        for (name, property) in properties {
+            let encoding = self.encoding_for(name);
+            let style = encoding
+                .and_then(|encoding| encoding.style.clone())
+                .unwrap_or(Style::Form);
+            let explode = encoding
+                .and_then(|encoding| encoding.explode)
+                .unwrap_or_else(|| style.default_explode());
+            let property = property.resolve(components);
+            if let Some(value) = take_property(
+                &mut pairs, name, property, &style, explode, &claimed, components,
+            ) {
+                object.insert(name.to_string(), value);
+            }
+        }
+        for (name, value) in pairs.into_iter().flatten() {
+            insert_repeated(&mut object, name, Value::String(value));
+        }
+        Value::Object(object)
+    }
+
+    /// Decodes a `multipart/form-data` body into its fields, reversing
+    /// [MediaType::encode_multipart]. Files, binary strings and parts which are not UTF-8 are kept
+    /// as raw [FormValue::File]s. JSON parts are parsed, other parts are converted to the type of
+    /// the schema of their property, and parts of array properties or repeated fields are
+    /// collected into [FormValue::Parts]. The result is not validated.
+    pub fn decode_multipart(
+        &self,
+        body: &[u8],
+        boundary: &str,
+        components: Option<&Components>,
+    ) -> Result<crate::Map<String, FormValue>, BodyError> {
+        let schema = self.resolved_schema(components);
+        let mut fields = crate::Map::new();
+        for part in parse_multipart(body, boundary)? {
+            let property = schema
+                .and_then(|schema| schema.property(&part.name, components))
+                .and_then(|property| property.resolve(components));
+            let items = property
+                .filter(|property| property._type == Some(Type::Array))
+                .map(|property| {
+                    property
+                        .items
+                        .as_deref()
+                        .and_then(|items| items.resolve(components))
+                });
+            let value = part_value(&part, items.unwrap_or(property))?;
+            insert_part(&mut fields, part.name, value, items.is_some());
+        }
+        Ok(fields)
+    }
+}
+
+/// Removes the pairs holding a property from the pairs of a form and builds its value. An exploded
+/// object holds the pairs named after its properties, except the `claimed` names which belong to
+/// the other properties of the form.
+pub(crate) fn take_property(
+    pairs: &mut [Option<(String, String)>],
+    name: &str,
+    schema: Option<&Schema>,
+    style: &Style,
+    explode: bool,
+    claimed: &[&str],
+    components: Option<&Components>,
+) -> Option<Value> {
+    let mut take = |matches: &dyn Fn(&str) -> bool, all: bool| {
+        let mut taken = vec![];
+        for pair in pairs.iter_mut() {
+            if pair.as_ref().is_some_and(|(key, _)| matches(key)) {
+                taken.extend(pair.take());
+                if !all {
+                    break;
+                }
+            }
+        }
+        taken
+    };
+    let delimiter = match style {
+        Style::SpaceDelimited => ' ',
+        Style::PipeDelimited => '|',
+        _ => ',',
+    };
+    match schema.and_then(|schema| schema._type.as_ref()) {
+        Some(Type::Array) => {
+            let items = schema
+                .and_then(|schema| schema.items.as_deref())
+                .and_then(|items| items.resolve(components));
+            let values = match explode {
+                true => take(&|key| key == name, true)
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .collect::<Vec<_>>(),
+                false => {
+                    let (_, value) = take(&|key| key == name, false).pop()?;
+                    value.split(delimiter).map(str::to_string).collect()
+                }
+            };
+            if values.is_empty() {
+                return None;
+            }
+            let values = values.iter().map(|value| coerce(value, items)).collect();
+            Some(Value::Array(values))
+        }
+        Some(Type::Object) => {
+            let schema = schema?;
+            let prefix = format!("{name}[");
+            let entries = match (style, explode) {
+                (Style::DeepObject, _) => {
+                    take(&|key| key.starts_with(&prefix) && key.ends_with(']'), true)
+                        .into_iter()
+                        .map(|(key, value)| (key[prefix.len()..key.len() - 1].to_string(), value))
+                        .collect::<Vec<_>>()
+                }
+                (_, true) => {
+                    let properties = schema.all_properties(components);
+                    take(
+                        &|key| properties.contains_key(key) && !claimed.contains(&key),
+                        true,
+                    )
+                }
+                (_, false) => {
+                    let (_, value) = take(&|key| key == name, false).pop()?;
+                    let items = value.split(delimiter).collect::<Vec<_>>();
+                    items
+                        .chunks(2)
+                        .map(|chunk| {
+                            (
+                                chunk[0].to_string(),
+                                chunk.get(1).unwrap_or(&"").to_string(),
+                            )
+                        })
+                        .collect()
+                }
+            };
+            if entries.is_empty() {
+                return None;
+            }
+            let object = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let property = schema
+                        .property(&key, components)
+                        .and_then(|property| property.resolve(components));
+                    let value = coerce(&value, property);
+                    (key, value)
+                })
+                .collect();
+            Some(Value::Object(object))
+        }
+        _ => {
+            let (_, value) = take(&|key| key == name, false).pop()?;
+            Some(coerce(&value, schema))
+        }
+    }
+}
+
+/// Converts the text of a field to the type of its schema, keeping it as a string when it cannot
+/// be converted so that validation reports it.
+pub(crate) fn coerce(text: &str, schema: Option<&Schema>) -> Value {
+    let Some(schema) = schema else {
+        return Value::String(text.to_string());
+    };
+    if text.is_empty() && schema.is_nullable() && schema._type != Some(Type::String) {
+        return Value::Null;
+    }
+    let coerced = match &schema._type {
+        Some(Type::Integer) | Some(Type::Number) => text.parse::<Number>().ok().map(Value::Number),
+        Some(Type::Boolean) => text.parse::<bool>().ok().map(Value::Bool),
+        Some(Type::Object) | Some(Type::Array) => serde_json::from_str(text).ok(),
+        _ => None,
+    };
+    coerced.unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+fn part_value(part: &Part, schema: Option<&Schema>) -> Result<FormValue, BodyError> {
+    let content_type = part.content_type().parse::<MediaTypeBuf>().ok();
+    let json = content_type.is_some_and(|content_type| {
+        content_type.subty() == "json"
+            || content_type.suffix().is_some_and(|suffix| suffix == "json")
+    });
+    let file = || FormValue::File {
+        filename: part.filename.clone(),
+        content: part.body.clone(),
+    };
+    if part.filename.is_some() || is_binary(schema) {
+        return Ok(file());
+    }
+    match json {
+        true => serde_json::from_slice(&part.body)
+            .map(FormValue::Value)
+            .map_err(|error| {
+                BodyError::Malformed(format!("invalid JSON in part `{}`: {error}", part.name))
+            }),
+        false => Ok(match std::str::from_utf8(&part.body) {
+            Ok(text) => FormValue::Value(coerce(text, schema)),
+            Err(_) => file(),
+        }),
+    }
+}
+
+/// Inserts a field, turning the value into an array when the field is repeated.
+fn insert_repeated(object: &mut Map<String, Value>, name: String, value: Value) {
+    match object.get_mut(&name) {
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+        None => {
+            object.insert(name, value);
+        }
+    }
+}
+
+/// Inserts a part, collecting the parts of array properties and repeated fields.
+fn insert_part(
+    fields: &mut crate::Map<String, FormValue>,
+    name: String,
+    value: FormValue,
+    array: bool,
+) {
+    match fields.get_mut(&name) {
+        Some(FormValue::Parts(values)) => values.push(value),
+        Some(existing) => {
+            let first = std::mem::replace(existing, FormValue::Parts(vec![]));
+            *existing = FormValue::Parts(vec![first, value]);
+        }
+        None => {
+            let value = match array {
+                true => FormValue::Parts(vec![value]),
+                false => value,
+            };
+            fields.insert(name, value);
+        }
+    }
+}
+
+fn part_content_type(encoding: Option<&Encoding>, schema: Option<&Schema>, item: &Item) -> String {
+    let declared = encoding
+        .and_then(|encoding| match &encoding.content_type {
+            Some(Parseable::Valid(content_type)) => content_type.preferred(),
+            _ => None,
+        })
+        .map(|content_type| content_type.to_string());
+    declared.unwrap_or_else(|| match item {
+        Item::File(..) => "application/octet-stream".to_string(),
+        _ if is_binary(schema) => "application/octet-stream".to_string(),
+        Item::Value(Value::Object(_) | Value::Array(_)) => "application/json".to_string(),
+        Item::Value(_) => "text/plain".to_string(),
+    })
+}
+
+/// Whether a schema describes the content of a file, which is sent as is. The `byte` format is
+/// base64 encoded text, which is sent as a text part.
+fn is_binary(schema: Option<&Schema>) -> bool {
+    schema.is_some_and(
+        |schema| matches!(&schema.format, Some(Format::Other(format)) if format == "binary"),
+    )
+}
+
+/// The headers declared by an [Encoding] which have a value to send. `Content-Type` is described
+/// by `contentType` and is ignored here, as required by the specification.
+fn part_headers(
+    encoding: Option<&Encoding>,
+    components: Option<&Components>,
+) -> Vec<(String, String)> {
+    let Some(headers) = encoding.and_then(|encoding| encoding.headers.as_ref()) else {
+        return vec![];
+    };
+    headers
+        .iter()
+        .filter(|(name, _)| !name.eq_ignore_ascii_case("content-type"))
+        .filter_map(|(name, header)| {
+            let header = header.resolve(components)?;
+            Some((
+                name.clone(),
+                primitive_to_string(&header_value(header, components)?),
+            ))
+        })
+        .collect()
+}
+
+fn header_value(header: &Header, components: Option<&Components>) -> Option<Value> {
+    if let Some(example) = &header.example {
+        return Some(example.clone());
+    }
+    header
+        .examples
+        .as_ref()?
+        .values()
+        .find_map(|example| example.resolve(components)?.value.clone())
+}
+
+fn escape_quoted(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
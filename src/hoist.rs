@@ -0,0 +1,215 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use crate::codegen::{is_named, pascal_case};
+use crate::{
+    pointer, Map, MediaRange, MediaType, Method, OpenAPIV3, Parameter, Reference, Referenceable,
+    Response, Schema,
+};
+
+/// A schema of the components replacing inline schemas, see [OpenAPIV3::hoist_schemas].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoistedSchema {
+    /// The name of the schema in the components, which was already there when it was identical
+    /// to the inline schemas.
+    pub name: String,
+    /// The JSON Pointers of the inline schemas replaced by a reference to it.
+    pub pointers: Vec<String>,
+}
+
+impl OpenAPIV3 {
+    /// Moves the inline schemas of parameters, request bodies and responses which need a named
+    /// type, like objects and enumerations, to the schemas of the components and replaces them
+    /// with references, so that code generators name them.
+    ///
+    /// Identical schemas are moved once, or replaced with an identical schema of the components.
+    /// New schemas are named after the operation and the location of the schema, e.g.
+    /// `ListPets200Response` or `CreatePetBody`, and the items of arrays get an `Item` suffix.
+    /// The schemas nested in hoisted schemas are left as is.
+    pub fn hoist_schemas(&mut self) -> Vec<HoistedSchema> {
+        let existing = self
+            .components
+            .iter()
+            .flat_map(|components| components.schemas.iter().flatten());
+        let mut hoister = Hoister {
+            names: existing.clone().map(|(name, _)| name.clone()).collect(),
+            schemas: existing
+                .filter_map(|(name, schema)| match schema {
+                    Referenceable::Data(schema) => Some((name.clone(), schema.to_value())),
+                    Referenceable::Reference(_) => None,
+                })
+                .collect(),
+            new: BTreeMap::new(),
+            hoisted: vec![],
+        };
+
+        for (path, item) in self.paths.iter_mut() {
+            let item_pointer = pointer::join("/paths", path);
+            // the parameters of a path item are named after its path, as they are shared
+            let path_name = format!("{path}_parameters");
+            for (index, parameter) in item.parameters.iter_mut().flatten().enumerate() {
+                let pointer = pointer::join(
+                    &pointer::join(&item_pointer, "parameters"),
+                    index.to_string(),
+                );
+                hoister.parameter(parameter, &path_name, &pointer);
+            }
+            for method in Method::ALL {
+                let Some(operation) = item.operation_mut(method) else {
+                    continue;
+                };
+                let name = match &operation.operation_id {
+                    Some(id) => id.clone(),
+                    None => format!("{method}_{path}").to_lowercase(),
+                };
+                let pointer = pointer::join(&item_pointer, method.as_str());
+                for (index, parameter) in operation.parameters.iter_mut().flatten().enumerate() {
+                    let pointer =
+                        pointer::join(&pointer::join(&pointer, "parameters"), index.to_string());
+                    hoister.parameter(parameter, &name, &pointer);
+                }
+                if let Some(Referenceable::Data(body)) = &mut operation.request_body {
+                    let pointer = pointer::join(&pointer, "requestBody");
+                    hoister.content(&mut body.content, &format!("{name}_body"), &pointer);
+                }
+                let pointer = pointer::join(&pointer, "responses");
+                let responses = &mut operation.responses;
+                for (status, response) in responses.data.iter_mut() {
+                    let hint = format!("{name}_{status}_response");
+                    hoister.response(
+                        response,
+                        &hint,
+                        &pointer::join(&pointer, status.to_string()),
+                    );
+                }
+                if let Some(response) = &mut responses.default {
+                    let hint = format!("{name}_default_response");
+                    hoister.response(response, &hint, &pointer::join(&pointer, "default"));
+                }
+            }
+        }
+
+        if let Some(components) = &mut self.components {
+            for (name, body) in components.request_bodies.iter_mut().flatten() {
+                if let Referenceable::Data(body) = body {
+                    let pointer = pointer::join("/components/requestBodies", name);
+                    hoister.content(&mut body.content, &format!("{name}_body"), &pointer);
+                }
+            }
+            for (name, response) in components.responses.iter_mut().flatten() {
+                let pointer = pointer::join("/components/responses", name);
+                hoister.response(response, &format!("{name}_response"), &pointer);
+            }
+            for (name, parameter) in components.parameters.iter_mut().flatten() {
+                let pointer = pointer::join("/components/parameters", name);
+                hoister.parameter(parameter, "", &pointer);
+            }
+        }
+        if !hoister.new.is_empty() {
+            self.components
+                .get_or_insert_with(Default::default)
+                .schemas
+                .get_or_insert_with(Map::new)
+                .extend(hoister.new);
+        }
+        hoister.hoisted
+    }
+}
+
+struct Hoister {
+    /// The names of the schemas of the components, including the hoisted ones.
+    names: BTreeSet<String>,
+    /// The serialized schemas of the components, to find identical schemas.
+    schemas: Vec<(String, Value)>,
+    new: BTreeMap<String, Referenceable<Schema>>,
+    hoisted: Vec<HoistedSchema>,
+}
+
+impl Hoister {
+    /// Hoists the schema of a parameter, named after the hint and the name of the parameter.
+    fn parameter(&mut self, parameter: &mut Referenceable<Parameter>, hint: &str, pointer: &str) {
+        let Referenceable::Data(parameter) = parameter else {
+            return;
+        };
+        let hint = format!("{hint}_{}", parameter.name);
+        if let Some(schema) = &mut parameter.schema {
+            self.schema(schema, &hint, &pointer::join(pointer, "schema"));
+        }
+        if let Some(content) = &mut parameter.content {
+            self.content(content, &hint, pointer);
+        }
+    }
+
+    fn response(&mut self, response: &mut Referenceable<Response>, hint: &str, pointer: &str) {
+        if let Referenceable::Data(Response {
+            content: Some(content),
+            ..
+        }) = response
+        {
+            self.content(content, hint, pointer);
+        }
+    }
+
+    fn content(&mut self, content: &mut Map<MediaRange, MediaType>, hint: &str, pointer: &str) {
+        let pointer = pointer::join(pointer, "content");
+        for (media_range, media_type) in content.iter_mut() {
+            if let Some(schema) = &mut media_type.schema {
+                let pointer =
+                    pointer::join(&pointer::join(&pointer, media_range.to_string()), "schema");
+                self.schema(schema, hint, &pointer);
+            }
+        }
+    }
+
+    fn schema(&mut self, schema: &mut Referenceable<Schema>, hint: &str, pointer: &str) {
+        let Referenceable::Data(data) = schema else {
+            return;
+        };
+        if !is_named(data) {
+            if let Some(items) = &mut data.items {
+                self.schema(
+                    items,
+                    &format!("{hint}Item"),
+                    &pointer::join(pointer, "items"),
+                );
+            }
+            return;
+        }
+        let value = data.to_value();
+        let name = match self.schemas.iter().find(|(_, schema)| *schema == value) {
+            Some((name, _)) => name.clone(),
+            None => {
+                let name = self.reserve(hint);
+                self.schemas.push((name.clone(), value));
+                self.new.insert(name.clone(), schema.clone());
+                name
+            }
+        };
+        match self.hoisted.iter_mut().find(|hoisted| hoisted.name == name) {
+            Some(hoisted) => hoisted.pointers.push(pointer.to_string()),
+            None => self.hoisted.push(HoistedSchema {
+                name: name.clone(),
+                pointers: vec![pointer.to_string()],
+            }),
+        }
+        *schema = Referenceable::Reference(Reference::component::<Schema>(&name));
+    }
+
+    /// A free name for a schema, based on the hint.
+    fn reserve(&mut self, hint: &str) -> String {
+        let base = match pascal_case(hint) {
+            name if name.is_empty() => "Schema".to_string(),
+            name => name,
+        };
+        let name = (1..)
+            .map(|index| match index {
+                1 => base.clone(),
+                index => format!("{base}{index}"),
+            })
+            .find(|name| !self.names.contains(name))
+            .expect("the iterator is infinite");
+        self.names.insert(name.clone());
+        name
+    }
+}
@@ -1,9 +1,7 @@
-use std::collections::BTreeMap;
-
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
-use crate::{Any, Contact, License};
+use crate::{Any, Contact, License, Map};
 
 /// The object provides metadata about the API. The metadata MAY be used by the clients if needed, and MAY be presented in editing or documentation generation tools for convenience.
 #[skip_serializing_none]
@@ -23,5 +21,5 @@ pub struct Info {
     /// The version of the OpenAPI document (which is distinct from the OpenAPI Specification version or the API implementation version).
     pub version: String,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
@@ -0,0 +1,876 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{pointer, OpenAPIV3};
+
+/// A step from a value to one of its children.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Location {
+    Key(String),
+    Index(usize),
+}
+
+/// Selects children of a value.
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Index(i64),
+    Wildcard,
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(Box<Expression>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    /// Whether the selectors apply to all the descendants, for `..`.
+    descendants: bool,
+    selectors: Vec<Selector>,
+}
+
+/// A query inside a filter, relative to the current value (`@`) or to the root (`$`).
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FunctionName {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Function {
+    name: FunctionName,
+    arguments: Vec<Operand>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Literal(Value),
+    Query(Query),
+    Function(Function),
+}
+
+/// The condition of a filter selector.
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Or(Box<Expression>, Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Comparison(Operand, Operator, Operand),
+    /// Whether a query selects at least one value.
+    Exists(Query),
+    /// A function returning a logical value, like `match`.
+    Test(Function),
+}
+
+/// A [JSONPath](https://www.rfc-editor.org/rfc/rfc9535) query, e.g. `$.paths['/pets'].*.tags[0]`,
+/// `$..description` or `$.paths.*[?@.deprecated == true]`.
+///
+/// Filters support comparisons, `&&`, `||`, `!`, existence tests and the `length`, `count`,
+/// `match`, `search` and `value` functions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+/// A value selected by a [JsonPath] query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPathNode<'a> {
+    /// The [JSON Pointer](https://www.rfc-editor.org/rfc/rfc6901) of the value, e.g.
+    /// `/paths/~1pets/get`.
+    pub pointer: String,
+    pub value: &'a Value,
+}
+
+/// The reason why a JSONPath query cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPathError {
+    /// The byte offset in the query where parsing failed.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for JsonPathError {}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> JsonPathError {
+        JsonPathError {
+            position: self.position,
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.position..]
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.rest().starts_with(token);
+        if found {
+            self.position += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), JsonPathError> {
+        self.skip_whitespace();
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected `{token}`"))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start_matches([' ', '\t', '\n', '\r']);
+        self.position = self.input.len() - trimmed.len();
+    }
+
+    fn shorthand(&mut self) -> Result<Selector, JsonPathError> {
+        if self.eat("*") {
+            return Ok(Selector::Wildcard);
+        }
+        let length = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || !c.is_ascii()))
+            .unwrap_or(self.rest().len());
+        if length == 0
+            || self
+                .rest()
+                .starts_with(|c: char| c.is_ascii_digit() || c == '-')
+        {
+            return Err(self.error("expected a name"));
+        }
+        let name = self.rest()[..length].to_string();
+        self.position += length;
+        Ok(Selector::Name(name))
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, JsonPathError> {
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, 'b')) => string.push('\u{8}'),
+                    Some((_, 'f')) => string.push('\u{c}'),
+                    Some((_, 'n')) => string.push('\n'),
+                    Some((_, 'r')) => string.push('\r'),
+                    Some((_, 't')) => string.push('\t'),
+                    Some((_, 'u')) => {
+                        let hex = chars.by_ref().take(4).map(|(_, c)| c).collect::<String>();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("invalid unicode escape"))?;
+                        string.push(c);
+                    }
+                    Some((_, c)) if c == quote || c == '\\' || c == '/' => string.push(c),
+                    _ => return Err(self.error("invalid escape")),
+                },
+                c if c == quote => {
+                    self.position += index + 1;
+                    return Ok(string);
+                }
+                c => string.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn integer(&mut self) -> Result<Option<i64>, JsonPathError> {
+        let length = self
+            .rest()
+            .char_indices()
+            .find(|&(index, c)| !(c.is_ascii_digit() || (index == 0 && c == '-')))
+            .map_or(self.rest().len(), |(index, _)| index);
+        if length == 0 {
+            return Ok(None);
+        }
+        let integer = self.rest()[..length]
+            .parse::<i64>()
+            .map_err(|_| self.error("expected an integer"))?;
+        self.position += length;
+        Ok(Some(integer))
+    }
+
+    fn selector(&mut self) -> Result<Selector, JsonPathError> {
+        self.skip_whitespace();
+        if self.eat("*") {
+            return Ok(Selector::Wildcard);
+        }
+        if self.eat("?") {
+            return Ok(Selector::Filter(Box::new(self.or()?)));
+        }
+        for quote in ['\'', '"'] {
+            if self.eat(&quote.to_string()) {
+                return self.string(quote).map(Selector::Name);
+            }
+        }
+        let start = self.integer()?;
+        self.skip_whitespace();
+        if !self.eat(":") {
+            return start
+                .map(Selector::Index)
+                .ok_or_else(|| self.error("expected a selector"));
+        }
+        self.skip_whitespace();
+        let end = self.integer()?;
+        self.skip_whitespace();
+        let step = match self.eat(":") {
+            true => {
+                self.skip_whitespace();
+                self.integer()?
+            }
+            false => None,
+        };
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    fn selectors(&mut self) -> Result<Vec<Selector>, JsonPathError> {
+        let mut selectors = vec![self.selector()?];
+        loop {
+            self.skip_whitespace();
+            if self.eat("]") {
+                return Ok(selectors);
+            }
+            if !self.eat(",") {
+                return Err(self.error("expected `,` or `]`"));
+            }
+            selectors.push(self.selector()?);
+        }
+    }
+
+    fn segment(&mut self) -> Result<Segment, JsonPathError> {
+        if self.eat("..") {
+            let selectors = match self.eat("[") {
+                true => self.selectors()?,
+                false => vec![self.shorthand()?],
+            };
+            return Ok(Segment {
+                descendants: true,
+                selectors,
+            });
+        }
+        let selectors = if self.eat(".") {
+            vec![self.shorthand()?]
+        } else if self.eat("[") {
+            self.selectors()?
+        } else {
+            return Err(self.error("expected `.` or `[`"));
+        };
+        Ok(Segment {
+            descendants: false,
+            selectors,
+        })
+    }
+
+    /// The segments of a query inside a filter, which ends at the first other token.
+    fn query(&mut self, relative: bool) -> Result<Query, JsonPathError> {
+        let mut segments = vec![];
+        loop {
+            let position = self.position;
+            self.skip_whitespace();
+            if !self.rest().starts_with(['.', '[']) {
+                self.position = position;
+                return Ok(Query { relative, segments });
+            }
+            segments.push(self.segment()?);
+        }
+    }
+
+    fn or(&mut self) -> Result<Expression, JsonPathError> {
+        let mut expression = self.and()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat("||") {
+                return Ok(expression);
+            }
+            expression = Expression::Or(Box::new(expression), Box::new(self.and()?));
+        }
+    }
+
+    fn and(&mut self) -> Result<Expression, JsonPathError> {
+        let mut expression = self.basic()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat("&&") {
+                return Ok(expression);
+            }
+            expression = Expression::And(Box::new(expression), Box::new(self.basic()?));
+        }
+    }
+
+    fn basic(&mut self) -> Result<Expression, JsonPathError> {
+        self.skip_whitespace();
+        if self.eat("!") {
+            self.skip_whitespace();
+            if self.eat("(") {
+                let expression = self.or()?;
+                self.expect(")")?;
+                return Ok(Expression::Not(Box::new(expression)));
+            }
+            let operand = self.operand()?;
+            return Ok(Expression::Not(Box::new(self.test(operand)?)));
+        }
+        if self.eat("(") {
+            let expression = self.or()?;
+            self.expect(")")?;
+            return Ok(expression);
+        }
+        let left = self.operand()?;
+        self.skip_whitespace();
+        let operators = [
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessOrEqual),
+            (">=", Operator::GreaterOrEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ];
+        let Some(operator) = operators
+            .into_iter()
+            .find_map(|(token, operator)| self.eat(token).then_some(operator))
+        else {
+            return self.test(left);
+        };
+        self.skip_whitespace();
+        let right = self.operand()?;
+        self.comparable(&left)?;
+        self.comparable(&right)?;
+        Ok(Expression::Comparison(left, operator, right))
+    }
+
+    fn test(&self, operand: Operand) -> Result<Expression, JsonPathError> {
+        match operand {
+            Operand::Query(query) => Ok(Expression::Exists(query)),
+            Operand::Function(function) if function.name.is_logical() => {
+                Ok(Expression::Test(function))
+            }
+            Operand::Function(function) => Err(self.error(format!(
+                "`{}` does not return a logical value",
+                function.name
+            ))),
+            Operand::Literal(_) => Err(self.error("expected a comparison")),
+        }
+    }
+
+    /// Checks that an operand has a single value.
+    fn comparable(&self, operand: &Operand) -> Result<(), JsonPathError> {
+        match operand {
+            Operand::Literal(_) => Ok(()),
+            Operand::Query(query) if query.is_singular() => Ok(()),
+            Operand::Query(_) => Err(self.error("only singular queries can be compared")),
+            Operand::Function(function) if function.name.is_logical() => {
+                Err(self.error(format!("`{}` returns a logical value", function.name)))
+            }
+            Operand::Function(_) => Ok(()),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, JsonPathError> {
+        self.skip_whitespace();
+        if self.eat("@") {
+            return self.query(true).map(Operand::Query);
+        }
+        if self.eat("$") {
+            return self.query(false).map(Operand::Query);
+        }
+        for quote in ['\'', '"'] {
+            if self.eat(&quote.to_string()) {
+                return self
+                    .string(quote)
+                    .map(|s| Operand::Literal(Value::String(s)));
+            }
+        }
+        if self
+            .rest()
+            .starts_with(|c: char| c.is_ascii_digit() || c == '-')
+        {
+            return self.number().map(Operand::Literal);
+        }
+        let length = self
+            .rest()
+            .find(|c: char| !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'))
+            .unwrap_or(self.rest().len());
+        let name = &self.rest()[..length];
+        if !self.rest()[length..].starts_with('(') {
+            let literal = match name {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                _ => return Err(self.error("expected a query, a literal or a function")),
+            };
+            self.position += length;
+            return Ok(Operand::Literal(literal));
+        }
+        let name = match name {
+            "length" => FunctionName::Length,
+            "count" => FunctionName::Count,
+            "match" => FunctionName::Match,
+            "search" => FunctionName::Search,
+            "value" => FunctionName::Value,
+            _ => return Err(self.error(format!("unknown function `{name}`"))),
+        };
+        self.position += length + 1;
+        let mut arguments = vec![];
+        loop {
+            let argument = self.operand()?;
+            self.argument(name, arguments.len(), &argument)?;
+            arguments.push(argument);
+            self.skip_whitespace();
+            if self.eat(")") {
+                break;
+            }
+            if !self.eat(",") {
+                return Err(self.error("expected `,` or `)`"));
+            }
+        }
+        if arguments.len() != name.arity() {
+            return Err(self.error(format!(
+                "`{name}` takes {} argument(s), found {}",
+                name.arity(),
+                arguments.len()
+            )));
+        }
+        Ok(Operand::Function(Function { name, arguments }))
+    }
+
+    /// Checks the type of a function argument.
+    fn argument(
+        &self,
+        name: FunctionName,
+        index: usize,
+        argument: &Operand,
+    ) -> Result<(), JsonPathError> {
+        match name {
+            FunctionName::Count | FunctionName::Value => match argument {
+                Operand::Query(_) => Ok(()),
+                _ => Err(self.error(format!("`{name}` takes a query"))),
+            },
+            _ if index >= name.arity() => Ok(()),
+            _ => self.comparable(argument),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, JsonPathError> {
+        let length = self
+            .rest()
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')))
+            .unwrap_or(self.rest().len());
+        let number = serde_json::from_str::<serde_json::Number>(&self.rest()[..length])
+            .map_err(|_| self.error("invalid number"))?;
+        self.position += length;
+        Ok(Value::Number(number))
+    }
+}
+
+impl FunctionName {
+    fn arity(self) -> usize {
+        match self {
+            FunctionName::Match | FunctionName::Search => 2,
+            _ => 1,
+        }
+    }
+
+    fn is_logical(self) -> bool {
+        matches!(self, FunctionName::Match | FunctionName::Search)
+    }
+}
+
+impl fmt::Display for FunctionName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FunctionName::Length => "length",
+            FunctionName::Count => "count",
+            FunctionName::Match => "match",
+            FunctionName::Search => "search",
+            FunctionName::Value => "value",
+        })
+    }
+}
+
+impl JsonPath {
+    pub fn parse(input: &str) -> Result<JsonPath, JsonPathError> {
+        let mut parser = Parser { input, position: 0 };
+        if !parser.eat("$") {
+            return Err(parser.error("expected `$`"));
+        }
+        let mut segments = vec![];
+        loop {
+            parser.skip_whitespace();
+            if parser.rest().is_empty() {
+                return Ok(JsonPath { segments });
+            }
+            segments.push(parser.segment()?);
+        }
+    }
+
+    /// The values selected in a document, in document order.
+    pub fn query<'a>(&self, root: &'a Value) -> Vec<JsonPathNode<'a>> {
+        evaluate(&self.segments, root, root)
+            .into_iter()
+            .map(|(location, value)| JsonPathNode {
+                pointer: location.iter().fold(String::new(), |ptr, step| match step {
+                    Location::Key(key) => pointer::join(&ptr, key),
+                    Location::Index(index) => pointer::join(&ptr, index.to_string()),
+                }),
+                value,
+            })
+            .collect()
+    }
+
+    /// The locations of the values selected in a document, in document order.
+    pub(crate) fn locate(&self, root: &Value) -> Vec<Vec<Location>> {
+        evaluate(&self.segments, root, root)
+            .into_iter()
+            .map(|(location, _)| location)
+            .collect()
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = JsonPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        JsonPath::parse(s)
+    }
+}
+
+impl OpenAPIV3 {
+    /// Evaluates a JSONPath query against the document, e.g. `$.paths.*[?@.deprecated]` for all
+    /// the deprecated operations, returning the JSON Pointers and the values selected.
+    pub fn query(&self, query: &str) -> Result<Vec<(String, Value)>, JsonPathError> {
+        let query = JsonPath::parse(query)?;
+        let document = self.to_value();
+        Ok(query
+            .query(&document)
+            .into_iter()
+            .map(|node| (node.pointer, node.value.clone()))
+            .collect())
+    }
+}
+
+fn evaluate<'a>(
+    segments: &[Segment],
+    start: &'a Value,
+    root: &'a Value,
+) -> Vec<(Vec<Location>, &'a Value)> {
+    let mut nodes = vec![(vec![], start)];
+    for segment in segments {
+        let mut selected = vec![];
+        for (location, value) in nodes {
+            if segment.descendants {
+                descendants(location, value, &mut |location, value| {
+                    select(&segment.selectors, location, value, root, &mut selected)
+                });
+            } else {
+                select(&segment.selectors, location, value, root, &mut selected);
+            }
+        }
+        nodes = selected;
+    }
+    nodes
+}
+
+/// Calls `visit` with a value and all its descendants.
+fn descendants<'a>(
+    location: Vec<Location>,
+    value: &'a Value,
+    visit: &mut impl FnMut(Vec<Location>, &'a Value),
+) {
+    visit(location.clone(), value);
+    for (child, value) in children(value) {
+        let mut location = location.clone();
+        location.push(child);
+        descendants(location, value, visit);
+    }
+}
+
+fn children(value: &Value) -> Vec<(Location, &Value)> {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| (Location::Key(key.clone()), value))
+            .collect(),
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (Location::Index(index), value))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn select<'a>(
+    selectors: &[Selector],
+    location: Vec<Location>,
+    value: &'a Value,
+    root: &'a Value,
+    selected: &mut Vec<(Vec<Location>, &'a Value)>,
+) {
+    let mut push = |child: Location, value: &'a Value| {
+        let mut location = location.clone();
+        location.push(child);
+        selected.push((location, value));
+    };
+    for selector in selectors {
+        match (selector, value) {
+            (Selector::Name(name), Value::Object(object)) => {
+                if let Some(value) = object.get(name) {
+                    push(Location::Key(name.clone()), value);
+                }
+            }
+            (Selector::Index(index), Value::Array(array)) => {
+                if let Some(index) = normalize(*index, array.len()).filter(|i| *i < array.len()) {
+                    push(Location::Index(index), &array[index]);
+                }
+            }
+            (Selector::Wildcard, value) => {
+                for (child, value) in children(value) {
+                    push(child, value);
+                }
+            }
+            (Selector::Slice { start, end, step }, Value::Array(array)) => {
+                for index in slice(*start, *end, step.unwrap_or(1), array.len()) {
+                    push(Location::Index(index), &array[index]);
+                }
+            }
+            (Selector::Filter(expression), value) => {
+                for (child, value) in children(value) {
+                    if expression.test(value, root) {
+                        push(child, value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The index counted from the start of an array, for negative indices.
+fn normalize(index: i64, length: usize) -> Option<usize> {
+    match index {
+        index if index < 0 => length.checked_sub(index.unsigned_abs() as usize),
+        index => Some(index as usize),
+    }
+}
+
+/// The indices selected by `[start:end:step]`, as in
+/// [RFC 9535](https://www.rfc-editor.org/rfc/rfc9535#name-array-slice-selector).
+fn slice(start: Option<i64>, end: Option<i64>, step: i64, length: usize) -> Vec<usize> {
+    let length = length as i64;
+    let bound = |index: i64, lower: i64, upper: i64| {
+        let index = if index < 0 { index + length } else { index };
+        index.clamp(lower, upper)
+    };
+    let mut indices = vec![];
+    if step > 0 {
+        let mut index = bound(start.unwrap_or(0), 0, length);
+        let upper = bound(end.unwrap_or(length), 0, length);
+        while index < upper {
+            indices.push(index as usize);
+            match index.checked_add(step) {
+                Some(next) => index = next,
+                None => break,
+            }
+        }
+    } else if step < 0 {
+        let mut index = bound(start.unwrap_or(length - 1), -1, length - 1);
+        let lower = bound(end.unwrap_or(-length - 1), -1, length - 1);
+        while index > lower {
+            indices.push(index as usize);
+            match index.checked_add(step) {
+                Some(next) => index = next,
+                None => break,
+            }
+        }
+    }
+    indices
+}
+
+impl Query {
+    /// Whether the query selects at most one value.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            !segment.descendants
+                && matches!(
+                    segment.selectors.as_slice(),
+                    [Selector::Name(_) | Selector::Index(_)]
+                )
+        })
+    }
+
+    fn nodes<'a>(&self, current: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+        let start = if self.relative { current } else { root };
+        evaluate(&self.segments, start, root)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect()
+    }
+}
+
+impl Expression {
+    fn test(&self, current: &Value, root: &Value) -> bool {
+        match self {
+            Expression::Or(left, right) => left.test(current, root) || right.test(current, root),
+            Expression::And(left, right) => left.test(current, root) && right.test(current, root),
+            Expression::Not(expression) => !expression.test(current, root),
+            Expression::Comparison(left, operator, right) => {
+                let left = left.value(current, root);
+                let right = right.value(current, root);
+                compare(left.as_deref(), *operator, right.as_deref())
+            }
+            Expression::Exists(query) => !query.nodes(current, root).is_empty(),
+            Expression::Test(function) => function.test(current, root),
+        }
+    }
+}
+
+impl Operand {
+    /// The single value of the operand, if any.
+    fn value<'a>(&'a self, current: &'a Value, root: &'a Value) -> Option<Cow<'a, Value>> {
+        match self {
+            Operand::Literal(value) => Some(Cow::Borrowed(value)),
+            Operand::Query(query) => match query.nodes(current, root).as_slice() {
+                [value] => Some(Cow::Borrowed(*value)),
+                _ => None,
+            },
+            Operand::Function(function) => function.value(current, root),
+        }
+    }
+}
+
+impl Function {
+    fn value<'a>(&'a self, current: &'a Value, root: &'a Value) -> Option<Cow<'a, Value>> {
+        let argument = &self.arguments[0];
+        match self.name {
+            FunctionName::Length => {
+                let length = match argument.value(current, root)?.as_ref() {
+                    Value::String(string) => string.chars().count(),
+                    Value::Array(array) => array.len(),
+                    Value::Object(object) => object.len(),
+                    _ => return None,
+                };
+                Some(Cow::Owned(Value::from(length)))
+            }
+            FunctionName::Count => match argument {
+                Operand::Query(query) => {
+                    Some(Cow::Owned(Value::from(query.nodes(current, root).len())))
+                }
+                _ => None,
+            },
+            FunctionName::Value => argument.value(current, root),
+            FunctionName::Match | FunctionName::Search => None,
+        }
+    }
+
+    fn test(&self, current: &Value, root: &Value) -> bool {
+        let value = self.arguments[0].value(current, root);
+        let pattern = self.arguments[1].value(current, root);
+        let (Some(Value::String(value)), Some(Value::String(pattern))) =
+            (value.as_deref(), pattern.as_deref())
+        else {
+            return false;
+        };
+        let pattern = match self.name {
+            FunctionName::Match => format!("^(?:{pattern})$"),
+            _ => pattern.clone(),
+        };
+        Regex::new(&pattern).is_ok_and(|regex| regex.is_match(value))
+    }
+}
+
+/// Compares the values of two operands, where `None` is the absence of value.
+fn compare(left: Option<&Value>, operator: Operator, right: Option<&Value>) -> bool {
+    match operator {
+        Operator::Equal => equal(left, right),
+        Operator::NotEqual => !equal(left, right),
+        Operator::Less => less(left, right),
+        Operator::LessOrEqual => less(left, right) || equal(left, right),
+        Operator::Greater => less(right, left),
+        Operator::GreaterOrEqual => less(right, left) || equal(left, right),
+    }
+}
+
+fn equal(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => same(left, right),
+        _ => false,
+    }
+}
+
+/// Deep equality where numbers are compared by value, so that `1 == 1.0`.
+fn same(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(l, r)| same(l, r))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, l)| right.get(key).is_some_and(|r| same(l, r)))
+        }
+        (left, right) => left == right,
+    }
+}
+
+fn less(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (Some(Value::Number(left)), Some(Value::Number(right))) => left.as_f64() < right.as_f64(),
+        (Some(Value::String(left)), Some(Value::String(right))) => left < right,
+        _ => false,
+    }
+}
+
+/// The value at a location, for updates.
+pub(crate) fn get_mut<'a>(
+    mut value: &'a mut Value,
+    location: &[Location],
+) -> Option<&'a mut Value> {
+    for step in location {
+        value = match (step, value) {
+            (Location::Key(key), Value::Object(object)) => object.get_mut(key)?,
+            (Location::Index(index), Value::Array(array)) => array.get_mut(*index)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
@@ -1,28 +1,98 @@
+mod body;
+mod canonical;
+mod codegen;
+mod compatibility;
 mod components;
 mod contact;
+mod diff;
+mod examples;
+mod extract;
+mod filter;
+mod form;
+mod hoist;
 mod info;
+mod jsonpath;
 mod license;
+mod lint;
+mod media_range;
+mod merge;
+mod mock;
 mod openapiv3;
+mod overlay;
 mod parameter;
 mod path;
+mod pointer;
+mod registry;
+mod router;
+mod sample;
 mod schema;
 mod security;
 mod server;
 mod shared;
+mod source_map;
+mod status_code;
+mod style;
+mod to_schema;
+mod unused;
+mod validation;
+pub use body::*;
+pub use canonical::*;
+pub use codegen::*;
+pub use compatibility::*;
 pub use components::*;
 pub use contact::*;
+pub use diff::*;
+pub use examples::*;
+pub use extract::*;
+pub use filter::*;
+pub use form::*;
+pub use hoist::*;
 pub use info::*;
+pub use jsonpath::*;
 pub use license::*;
+pub use lint::*;
+pub use media_range::*;
+pub use merge::*;
+pub use mock::*;
 pub use openapiv3::*;
+pub use overlay::*;
 pub use parameter::*;
 pub use path::*;
+pub use registry::*;
+pub use router::*;
+pub use sample::*;
 pub use schema::*;
 pub use security::*;
 pub use server::*;
 pub use shared::*;
+pub use source_map::*;
+pub use status_code::*;
+pub use to_schema::*;
+pub use unused::*;
+pub use validation::*;
+
+#[cfg(feature = "derive")]
+pub use oas_derive::{operation, ToSchema};
 
 pub type Any = serde_json::Value;
 
+/// The map of the objects of a document: a [BTreeMap](std::collections::BTreeMap) sorting its
+/// keys, or an [IndexMap](indexmap::IndexMap) keeping the order of the parsed document with the
+/// `preserve_order` feature.
+///
+/// The fixed fields of the objects are always serialized in the order of the specification,
+/// followed by their extensions.
+#[cfg(not(feature = "preserve_order"))]
+pub type Map<K, V> = std::collections::BTreeMap<K, V>;
+/// The map of the objects of a document: a [BTreeMap](std::collections::BTreeMap) sorting its
+/// keys, or an [IndexMap](indexmap::IndexMap) keeping the order of the parsed document with the
+/// `preserve_order` feature.
+///
+/// The fixed fields of the objects are always serialized in the order of the specification,
+/// followed by their extensions.
+#[cfg(feature = "preserve_order")]
+pub type Map<K, V> = indexmap::IndexMap<K, V>;
+
 macro_rules! impl_serde_json {
     ($($st:ty,)+) => {
         $(
@@ -42,11 +112,27 @@ impl_serde_json! {
     OpenAPIV3, Info, Contact, License, Server, ServerVariable, Components, PathItem,
     Operation, ExternalDocumentation, ParameterIn, Parameter, RequestBody, MediaType,
     Encoding, Responses, Response, Callback, Example, Link, Header, Tag, Reference,
-    Schema, Discriminator, SecurityType, SecurityScheme, OauthFlows, OauthFlow, SecurityRequirement,
+    Schema, Xml, Discriminator, SecurityType, SecurityScheme, OauthFlows, OauthFlow, SecurityRequirement,
 }
 
 #[cfg(test)]
 mod test {
+    use crate::OpenAPIV3;
+    use serde_json::{json, Value};
+
+    /// Parses a document titled `Pets` with no paths, whose fields are replaced by those given.
+    fn document(fields: Value) -> OpenAPIV3 {
+        let mut document = json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Pets", "version": "1.0.0"},
+            "paths": {},
+        });
+        for (key, value) in fields.as_object().expect("the fields are an object") {
+            document[key] = value.clone();
+        }
+        serde_json::from_value(document).unwrap()
+    }
+
     mod pass {
         use crate::{OpenAPIV3, Parseable, Server};
         use assert_json_diff::assert_json_eq;
@@ -98,4 +184,2692 @@ mod test {
             }
         }
     }
+
+    mod responses {
+        use crate::{Referenceable, Responses, StatusCode};
+
+        const RESPONSES: &str = r#"{
+            "200": {"description": "ok"},
+            "2XX": {"description": "success"},
+            "default": {"description": "error"},
+            "x-internal": true
+        }"#;
+
+        fn description(response: Option<&Referenceable<crate::Response>>) -> &str {
+            match response {
+                Some(Referenceable::Data(response)) => &response.description,
+                _ => panic!("Should be a response"),
+            }
+        }
+
+        #[test]
+        fn extensions_are_not_parsed_as_responses() {
+            let responses: Responses = serde_json::from_str(RESPONSES).unwrap();
+
+            assert_eq!(
+                responses.data.keys().collect::<Vec<_>>(),
+                vec![&StatusCode::Code(200), &StatusCode::Range(2)]
+            );
+            assert!(responses.extras.unwrap().contains_key("x-internal"));
+        }
+
+        #[test]
+        fn for_status_prefers_code_then_range_then_default() {
+            let responses: Responses = serde_json::from_str(RESPONSES).unwrap();
+
+            assert_eq!(description(responses.for_status(200)), "ok");
+            assert_eq!(description(responses.for_status(204)), "success");
+            assert_eq!(description(responses.for_status(404)), "error");
+            assert!(responses.for_status(600).is_none());
+            assert!(responses.for_status(25750).is_none());
+        }
+
+        #[test]
+        fn invalid_status_codes_are_rejected() {
+            for invalid in ["600", "6XX", "2xx", "20", "abc"] {
+                assert!(invalid.parse::<StatusCode>().is_err(), "{invalid}");
+            }
+            let test = r#"{"99": {"description": "too low"}}"#;
+            assert!(serde_json::from_str::<Responses>(test).is_err());
+        }
+    }
+
+    mod media_range {
+        use crate::{ContentNegotiation, Map, MediaRange, MediaType};
+
+        fn content(keys: &[&str]) -> Map<MediaRange, MediaType> {
+            let content = keys
+                .iter()
+                .map(|key| (key.to_string(), serde_json::json!({})))
+                .collect::<serde_json::Map<_, _>>();
+            serde_json::from_value(content.into()).unwrap()
+        }
+
+        #[test]
+        fn invalid_keys_are_rejected() {
+            assert!("application/json; charset=utf-8"
+                .parse::<MediaRange>()
+                .is_ok());
+            assert!("image/*".parse::<MediaRange>().is_ok());
+            assert!("json".parse::<MediaRange>().is_err());
+            assert!("application/json garbage".parse::<MediaRange>().is_err());
+        }
+
+        #[test]
+        fn invalid_keys_are_kept_but_never_match() {
+            let content = content(&["json", "application/json"]);
+            let invalid = content.keys().find(|key| !key.is_valid()).unwrap();
+            assert_eq!(invalid.as_str(), "json");
+            assert!(invalid.media_type().is_none());
+            assert_eq!(
+                serde_json::to_value(&content).unwrap(),
+                serde_json::json!({"application/json": {}, "json": {}})
+            );
+
+            assert_eq!(
+                content.negotiate("*/*").unwrap().0.as_str(),
+                "application/json"
+            );
+            assert!(content.for_content_type("text/plain").is_none());
+        }
+
+        #[test]
+        fn negotiate_uses_q_values_and_specificity() {
+            let content = content(&["application/json", "application/xml", "text/*"]);
+            let negotiate = |accept| content.negotiate(accept).unwrap().0.as_str();
+
+            assert_eq!(
+                negotiate("application/xml, application/json"),
+                "application/xml"
+            );
+            assert_eq!(
+                negotiate("application/xml;q=0.5, application/json"),
+                "application/json"
+            );
+            assert_eq!(negotiate("text/html"), "text/*");
+            assert_eq!(negotiate("*/*;q=0.1, text/*"), "text/*");
+            assert_eq!(negotiate(""), "application/json");
+            assert!(content.negotiate("image/png").is_none());
+            assert!(content.negotiate("text/*;q=0, application/*;q=0").is_none());
+        }
+
+        #[test]
+        fn for_content_type_picks_the_most_specific_key() {
+            let content = content(&["*/*", "image/*", "image/png"]);
+            let select = |content_type| content.for_content_type(content_type).unwrap().0.as_str();
+
+            assert_eq!(select("image/png"), "image/png");
+            assert_eq!(select("image/jpeg"), "image/*");
+            assert_eq!(select("text/plain; charset=utf-8"), "*/*");
+        }
+    }
+
+    mod filter {
+        use super::document;
+        use crate::{Method, OpenAPIV3, OperationFilter};
+        use serde_json::json;
+
+        fn spec() -> OpenAPIV3 {
+            let response = |schema: &str| {
+                json!({"200": {"description": "ok", "content": {"application/json": {
+                    "schema": {"$ref": format!("#/components/schemas/{schema}")},
+                }}}})
+            };
+            document(json!({
+                "paths": {
+                    "/pets": {
+                        "get": {"tags": ["pets"], "responses": response("Pets")},
+                        "delete": {
+                            "tags": ["admin"],
+                            "x-internal": true,
+                            "security": [{"admin": []}],
+                            "responses": response("Audit"),
+                        },
+                    },
+                    "/admin/stats": {
+                        "get": {"tags": ["admin"], "responses": response("Stats")},
+                    },
+                    "/legacy": {
+                        "get": {"tags": ["pets"], "deprecated": true, "responses": response("Pet")},
+                    },
+                },
+                "components": {
+                    "schemas": {
+                        "Audit": {"type": "object", "properties": {
+                            "entries": {"$ref": "#/components/schemas/Stats"},
+                        }},
+                        "Draft": {"$ref": "#/components/schemas/Audit"},
+                        "Pet": {"type": "object"},
+                        "Pets": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+                        "Stats": {"type": "object"},
+                    },
+                    "securitySchemes": {
+                        "admin": {"type": "http", "scheme": "basic"},
+                    },
+                },
+                "tags": [{"name": "pets"}, {"name": "admin"}, {"name": "unused"}],
+            }))
+        }
+
+        #[test]
+        fn filters_operations_and_collects_garbage() {
+            let public = spec().filter_operations(&OperationFilter {
+                exclude_tags: vec!["admin".to_string()],
+                exclude_deprecated: true,
+                ..Default::default()
+            });
+            assert_eq!(public.paths.keys().collect::<Vec<_>>(), ["/pets"]);
+            assert!(public.paths["/pets"].delete.is_none());
+            let components = public.components.unwrap();
+            // `Draft` was not used in the first place, so it keeps `Audit` and `Stats`
+            assert_eq!(
+                components.schemas.unwrap().keys().collect::<Vec<_>>(),
+                ["Audit", "Draft", "Pet", "Pets", "Stats"]
+            );
+            assert!(components.security_schemes.unwrap().is_empty());
+            let tags = public.tags.unwrap();
+            assert_eq!(
+                tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>(),
+                ["pets", "unused"]
+            );
+        }
+
+        #[test]
+        fn matches_paths_methods_and_extensions() {
+            let mut spec = spec();
+            spec.components
+                .as_mut()
+                .unwrap()
+                .schemas
+                .as_mut()
+                .unwrap()
+                .retain(|name, _| name != "Draft");
+            let filter = OperationFilter {
+                path_prefixes: Some(vec!["/pets".to_string()]),
+                exclude_extensions: vec!["x-internal".to_string()],
+                ..Default::default()
+            };
+            let filtered = spec.filter_operations(&filter);
+            assert_eq!(filtered.operations().count(), 1);
+            assert_eq!(
+                filtered
+                    .components
+                    .unwrap()
+                    .schemas
+                    .unwrap()
+                    .keys()
+                    .collect::<Vec<_>>(),
+                ["Pet", "Pets"]
+            );
+            let deletes = spec.filter(|_, method, _| method == Method::Delete);
+            assert_eq!(
+                deletes
+                    .components
+                    .unwrap()
+                    .schemas
+                    .unwrap()
+                    .keys()
+                    .collect::<Vec<_>>(),
+                ["Audit", "Stats"]
+            );
+            assert!(filter.matches(
+                "/pets/{id}",
+                Method::Get,
+                &spec.paths["/pets"].get.clone().unwrap()
+            ));
+        }
+    }
+
+    mod form {
+        use crate::{Encoding, FormValue, Map, MediaType, Parseable};
+        use serde_json::json;
+
+        fn media_type(value: serde_json::Value) -> MediaType {
+            serde_json::from_value(value).unwrap()
+        }
+
+        #[test]
+        fn encoding_content_type_is_a_list_of_media_types() {
+            let encoding: Encoding =
+                serde_json::from_str(r#"{"contentType": "image/png, image/jpeg"}"#).unwrap();
+
+            match encoding.content_type {
+                Some(Parseable::Valid(list)) => {
+                    assert_eq!(list.to_string(), "image/png, image/jpeg");
+                    assert_eq!(list.preferred().unwrap().as_str(), "image/png");
+                }
+                _ => panic!("Should be valid"),
+            }
+        }
+
+        #[test]
+        fn urlencoded_applies_style_and_explode() {
+            let media_type = media_type(json!({
+                "encoding": {
+                    "tags": {"style": "pipeDelimited", "explode": false},
+                    "filter": {"style": "deepObject", "explode": true},
+                    "ids": {"explode": false}
+                }
+            }));
+            let value = json!({
+                "filter": {"color": "red"},
+                "ids": [1, 2],
+                "name": "a b",
+                "tags": ["x", "y"],
+                "unset": null,
+                "values": [3, 4]
+            });
+
+            assert_eq!(
+                media_type.encode_urlencoded(value.as_object().unwrap()),
+                "filter[color]=red&ids=1,2&name=a%20b&tags=x|y&values=3&values=4"
+            );
+        }
+
+        #[test]
+        fn urlencoded_exploded_objects_leave_top_level_fields() {
+            let media_type = media_type(json!({
+                "schema": {
+                    "type": "object",
+                    "properties": {
+                        "owner": {
+                            "type": "object",
+                            "properties": {"tag": {"type": "string"}, "age": {"type": "integer"}}
+                        },
+                        "tag": {"type": "string"}
+                    }
+                }
+            }));
+
+            assert_eq!(
+                media_type.decode_urlencoded("tag=dog&age=42", None),
+                json!({"owner": {"age": 42}, "tag": "dog"})
+            );
+        }
+
+        #[test]
+        fn multipart_applies_content_type_and_headers() {
+            let media_type = media_type(json!({
+                "encoding": {
+                    "icon": {
+                        "contentType": "image/*, image/png",
+                        "headers": {"X-Rate-Limit": {"example": 10}}
+                    }
+                }
+            }));
+            let value = Map::from([
+                ("icon".to_string(), FormValue::Value(json!("PNG"))),
+                ("meta".to_string(), FormValue::Value(json!({"a": 1}))),
+            ]);
+
+            let multipart = media_type.encode_multipart(&value, "boundary", None);
+
+            assert_eq!(
+                multipart.content_type(),
+                "multipart/form-data; boundary=boundary"
+            );
+            assert_eq!(
+                String::from_utf8(multipart.body).unwrap(),
+                "--boundary\r\n\
+                Content-Disposition: form-data; name=\"icon\"\r\n\
+                Content-Type: image/png\r\n\
+                X-Rate-Limit: 10\r\n\
+                \r\n\
+                PNG\r\n\
+                --boundary\r\n\
+                Content-Disposition: form-data; name=\"meta\"\r\n\
+                Content-Type: application/json\r\n\
+                \r\n\
+                {\"a\":1}\r\n\
+                --boundary--\r\n"
+            );
+        }
+
+        #[test]
+        fn multipart_keeps_the_raw_content_of_files() {
+            let media_type = media_type(json!({
+                "schema": {
+                    "type": "object",
+                    "properties": {
+                        "icons": {"type": "array", "items": {"type": "string", "format": "binary"}},
+                        "id": {"type": "integer"}
+                    }
+                }
+            }));
+            let icon = FormValue::File {
+                filename: Some("icon.png".to_string()),
+                content: vec![0x89, b'P', b'N', b'G', 0xff, 0],
+            };
+            let value = Map::from([
+                ("icons".to_string(), FormValue::Parts(vec![icon.clone()])),
+                ("id".to_string(), FormValue::Value(json!(7))),
+            ]);
+
+            let multipart = media_type.encode_multipart(&value, "boundary", None);
+            let start = b"--boundary\r\n\
+                Content-Disposition: form-data; name=\"icons\"; filename=\"icon.png\"\r\n\
+                Content-Type: application/octet-stream\r\n\
+                \r\n\
+                \x89PNG\xff\x00\r\n";
+            assert!(multipart.body.starts_with(start));
+
+            let decoded = media_type
+                .decode_multipart(&multipart.body, "boundary", None)
+                .unwrap();
+            assert_eq!(decoded, value);
+        }
+
+        #[test]
+        fn multipart_sends_base64_strings_as_text() {
+            let media_type = media_type(json!({
+                "schema": {
+                    "type": "object",
+                    "properties": {"thumbnail": {"type": "string", "format": "byte"}}
+                }
+            }));
+            let value = Map::from([("thumbnail".to_string(), FormValue::Value(json!("iVBORw0=")))]);
+
+            let multipart = media_type.encode_multipart(&value, "boundary", None);
+            assert_eq!(
+                String::from_utf8(multipart.body.clone()).unwrap(),
+                "--boundary\r\n\
+                 Content-Disposition: form-data; name=\"thumbnail\"\r\n\
+                 Content-Type: text/plain\r\n\
+                 \r\n\
+                 iVBORw0=\r\n\
+                 --boundary--\r\n"
+            );
+
+            let decoded = media_type
+                .decode_multipart(&multipart.body, "boundary", None)
+                .unwrap();
+            assert_eq!(decoded, value);
+        }
+    }
+
+    mod body {
+        use crate::{BodyError, RequestBody, Schema};
+        use serde_json::json;
+
+        fn upload() -> RequestBody {
+            serde_json::from_value(json!({
+                "content": {
+                    "multipart/form-data": {
+                        "schema": {
+                            "type": "object",
+                            "required": ["id", "file"],
+                            "properties": {
+                                "id": {"type": "integer"},
+                                "tags": {"type": "array", "items": {"type": "string"}},
+                                "address": {"type": "object"},
+                                "file": {"type": "string", "format": "binary"}
+                            }
+                        }
+                    },
+                    "application/x-www-form-urlencoded": {
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "id": {"type": "integer", "minimum": 1},
+                                "tags": {"type": "array", "items": {"type": "string"}},
+                                "filter": {"type": "object", "properties": {"age": {"type": "integer"}}}
+                            }
+                        },
+                        "encoding": {
+                            "tags": {"explode": false},
+                            "filter": {"style": "deepObject", "explode": true}
+                        }
+                    }
+                }
+            }))
+            .unwrap()
+        }
+
+        #[test]
+        fn validate_reports_pointers() {
+            let schema: Schema = serde_json::from_value(json!({
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "email": {"type": "string", "format": "email"},
+                    "tags": {"type": "array", "items": {"type": "string", "maxLength": 3}}
+                },
+                "additionalProperties": false
+            }))
+            .unwrap();
+
+            let errors = schema
+                .validate(
+                    &json!({"email": "nope", "tags": ["ok", "long"], "x": 1}),
+                    None,
+                )
+                .unwrap_err();
+            let pointers = errors
+                .iter()
+                .map(|e| e.pointer.as_str())
+                .collect::<Vec<_>>();
+
+            assert_eq!(pointers, vec!["", "/email", "/tags/1", "/x"]);
+        }
+
+        #[test]
+        fn validate_checks_patterns_and_item_counts() {
+            let schema: Schema = serde_json::from_value(json!({
+                "type": "array",
+                "minItems": 2,
+                "maxItems": 3,
+                "items": {"type": "string", "pattern": "^[a-z]+$"}
+            }))
+            .unwrap();
+
+            assert!(schema.validate(&json!(["a", "b"]), None).is_ok());
+            let errors = |value| {
+                schema
+                    .validate(&value, None)
+                    .unwrap_err()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                errors(json!(["a", "B1", "c", "D"])),
+                [
+                    "more than 3 items",
+                    "/1: does not match the pattern `^[a-z]+$`",
+                    "/3: does not match the pattern `^[a-z]+$`",
+                ]
+            );
+            assert_eq!(errors(json!(["a"])), ["fewer than 2 items"]);
+        }
+
+        #[test]
+        fn multipart_round_trips() {
+            let body = upload();
+            let value =
+                json!({"id": 7, "tags": ["a", "b"], "address": {"city": "Oslo"}, "file": "PNG"});
+
+            let encoded = body.encode("multipart/form-data", &value, None).unwrap();
+            let text = String::from_utf8(encoded.body.clone()).unwrap();
+            assert!(text.contains(
+                "name=\"file\"; filename=\"file\"\r\nContent-Type: application/octet-stream"
+            ));
+
+            let decoded = body
+                .decode(&encoded.content_type, &encoded.body, None)
+                .unwrap();
+            assert_eq!(decoded, value);
+        }
+
+        #[test]
+        fn urlencoded_round_trips() {
+            let body = upload();
+            let value = json!({"filter": {"age": 3}, "id": 7, "tags": ["a", "b"]});
+
+            let encoded = body
+                .encode("application/x-www-form-urlencoded", &value, None)
+                .unwrap();
+            assert_eq!(encoded.body, b"filter[age]=3&id=7&tags=a,b");
+
+            let decoded = body
+                .decode(&encoded.content_type, &encoded.body, None)
+                .unwrap();
+            assert_eq!(decoded, value);
+        }
+
+        #[test]
+        fn decode_validates_against_the_schema() {
+            let body = upload();
+
+            let error = body
+                .decode("application/x-www-form-urlencoded", b"id=0", None)
+                .unwrap_err();
+
+            match error {
+                BodyError::Invalid(errors) => assert_eq!(errors[0].pointer, "/id"),
+                error => panic!("Should be invalid, got {error}"),
+            }
+        }
+    }
+
+    mod merge {
+        use super::document;
+        use crate::{MergeConflict, MergeOptions, Method, OpenAPIV3, ServerRewrite};
+        use serde_json::{json, Value};
+
+        fn spec(title: &str, paths: Value, components: Value) -> OpenAPIV3 {
+            document(json!({
+                "info": {"title": title, "version": "1.0.0"},
+                "servers": [{"url": format!("https://{}.example.com", title.to_lowercase())}],
+                "paths": paths,
+                "components": components,
+                "tags": [{"name": "pets"}],
+            }))
+        }
+
+        fn get(schema: &str) -> Value {
+            json!({"get": {
+                "tags": ["pets"],
+                "responses": {
+                    "200": {"description": "OK", "content": {"application/json": {"schema": {"$ref": format!("#/components/schemas/{schema}")}}}},
+                    "default": {"description": "Error", "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Error"}}}},
+                },
+            }})
+        }
+
+        fn components(pet: Value) -> Value {
+            json!({
+                "schemas": {
+                    "Error": {"type": "object", "properties": {"message": {"type": "string"}}},
+                    "Pet": pet,
+                    "Pets": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+                },
+                "securitySchemes": {"key": {"type": "apiKey", "name": "key", "in": "header"}},
+            })
+        }
+
+        #[test]
+        fn renames_colliding_components() {
+            let mut spec = spec(
+                "Store",
+                json!({"/pets": get("Pets")}),
+                components(json!({"type": "object", "properties": {"name": {"type": "string"}}})),
+            );
+            let mut other = self::spec(
+                "Shelter",
+                json!({"/pets": get("Pets"), "/pets/{id}": get("Pet")}),
+                components(json!({"type": "object", "properties": {"id": {"type": "integer"}}})),
+            );
+            other.security = Some(vec![serde_json::from_value(json!({"key": []})).unwrap()]);
+            let conflicts = spec.merge(other, &MergeOptions::default());
+            assert_eq!(
+                conflicts,
+                [
+                    MergeConflict::RenamedComponent {
+                        kind: "schemas".to_string(),
+                        name: "Pet".to_string(),
+                        renamed: "ShelterPet".to_string(),
+                    },
+                    MergeConflict::RenamedComponent {
+                        kind: "schemas".to_string(),
+                        name: "Pets".to_string(),
+                        renamed: "ShelterPets".to_string(),
+                    },
+                    MergeConflict::PathItem {
+                        path: "/pets".to_string(),
+                    },
+                    MergeConflict::Operation {
+                        path: "/pets".to_string(),
+                        method: Method::Get,
+                    },
+                ]
+            );
+            let value = spec.to_value();
+            let schemas = &value["components"]["schemas"];
+            assert_eq!(
+                schemas.as_object().unwrap().keys().collect::<Vec<_>>(),
+                ["Error", "Pet", "Pets", "ShelterPet", "ShelterPets"]
+            );
+            assert_eq!(
+                schemas["ShelterPets"]["items"]["$ref"],
+                "#/components/schemas/ShelterPet"
+            );
+            let item = &value["paths"]["/pets/{id}"];
+            assert_eq!(
+                item["get"]["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+                "#/components/schemas/ShelterPet"
+            );
+            assert_eq!(item["get"]["security"], json!([{"key": []}]));
+            assert_eq!(
+                item["servers"],
+                json!([{"url": "https://shelter.example.com/"}])
+            );
+            assert_eq!(value["tags"], json!([{"name": "pets"}]));
+        }
+
+        #[test]
+        fn matches_paths_by_template() {
+            let mut spec = spec(
+                "Store",
+                json!({"/pets/{id}": get("Pet")}),
+                components(json!({})),
+            );
+            let other = self::spec(
+                "Shelter",
+                json!({"/pets/{petId}": get("Pet")}),
+                components(json!({})),
+            );
+            let conflicts = spec.merge(other, &MergeOptions::default());
+            assert_eq!(
+                conflicts,
+                [MergeConflict::PathTemplate {
+                    path: "/pets/{petId}".to_string(),
+                    existing: "/pets/{id}".to_string(),
+                }]
+            );
+            assert_eq!(
+                conflicts[0].to_string(),
+                "path `/pets/{petId}` is already defined as `/pets/{id}`"
+            );
+            assert_eq!(spec.paths.keys().collect::<Vec<_>>(), ["/pets/{id}"]);
+        }
+
+        #[test]
+        fn prefixes_paths() {
+            let mut spec = spec("Store", json!({"/pets": get("Pet")}), components(json!({})));
+            let other = self::spec(
+                "Shelter",
+                json!({"/pets": get("Pet")}),
+                components(json!({})),
+            );
+            let options = MergeOptions {
+                path_prefix: Some("/shelter/".to_string()),
+                servers: ServerRewrite::Drop,
+                ..MergeOptions::default()
+            };
+            assert_eq!(spec.merge(other, &options), []);
+            assert_eq!(
+                spec.paths.keys().collect::<Vec<_>>(),
+                ["/pets", "/shelter/pets"]
+            );
+            assert!(spec.paths["/shelter/pets"].servers.is_none());
+            assert_eq!(spec.components.unwrap().schemas.unwrap().len(), 3);
+        }
+    }
+
+    mod overlay {
+        use crate::{OpenAPIV3, Overlay, OverlayError};
+        use serde_json::json;
+
+        fn spec() -> OpenAPIV3 {
+            serde_json::from_str(include_str!("../examples/v3.0/json/petstore.json")).unwrap()
+        }
+
+        #[test]
+        fn updates_and_removes_targets() {
+            let overlay: Overlay = serde_json::from_value(json!({
+                "overlay": "1.0.0",
+                "info": {"title": "Publish", "version": "1.0.0"},
+                "actions": [
+                    {"target": "$.paths.*.get", "update": {"x-public": true}},
+                    {"target": "$.paths['/pets'].get.tags", "update": "public"},
+                    {"target": "$.info", "update": {"title": "Public pets", "license": {"name": "MIT"}}},
+                    {"target": "$.paths['/pets/{petId}']", "remove": true},
+                ],
+            }))
+            .unwrap();
+            let spec = overlay.apply(&spec()).unwrap().to_value();
+            assert_eq!(spec["paths"]["/pets"]["get"]["x-public"], true);
+            assert_eq!(
+                spec["paths"]["/pets"]["get"]["tags"],
+                json!(["pets", "public"])
+            );
+            assert_eq!(spec["info"]["title"], "Public pets");
+            assert_eq!(spec["info"]["license"]["name"], "MIT");
+            assert!(spec["paths"].get("/pets/{petId}").is_none());
+        }
+
+        #[test]
+        fn removes_array_items() {
+            let overlay: Overlay = serde_json::from_value(json!({
+                "overlay": "1.0.0",
+                "info": {"title": "Strip", "version": "1.0.0"},
+                "actions": [
+                    {"target": "$..parameters[0,-1]", "remove": true},
+                    {"target": "$.paths", "update": []},
+                    {"target": "paths", "remove": true},
+                ],
+            }))
+            .unwrap();
+            assert_eq!(
+                overlay.apply(&spec()).unwrap_err(),
+                OverlayError::InvalidUpdate { action: 1 }
+            );
+            let mut document = spec().to_value();
+            let mut strip = overlay.clone();
+            strip.actions.truncate(1);
+            strip.apply_to_value(&mut document).unwrap();
+            assert!(document["paths"]["/pets"]["get"]["parameters"]
+                .as_array()
+                .unwrap()
+                .is_empty());
+            strip.actions = overlay.actions[2..].to_vec();
+            assert!(matches!(
+                strip.apply_to_value(&mut document),
+                Err(OverlayError::InvalidTarget { action: 0, .. })
+            ));
+        }
+    }
+
+    mod hoist {
+        use super::document;
+        use crate::{HoistedSchema, OpenAPIV3};
+        use serde_json::json;
+
+        fn spec() -> OpenAPIV3 {
+            let pet = json!({"type": "object", "properties": {"name": {"type": "string"}}});
+            document(json!({
+                "paths": {
+                    "/pets": {
+                        "get": {
+                            "operationId": "listPets",
+                            "parameters": [{
+                                "name": "sort",
+                                "in": "query",
+                                "schema": {"type": "string", "enum": ["name", "age"]},
+                            }, {
+                                "name": "limit",
+                                "in": "query",
+                                "schema": {"type": "integer"},
+                            }],
+                            "responses": {"200": {"description": "ok", "content": {
+                                "application/json": {"schema": {"type": "array", "items": pet}},
+                            }}},
+                        },
+                        "post": {
+                            "requestBody": {"content": {"application/json": {"schema": pet}}},
+                            "responses": {"201": {"description": "ok", "content": {
+                                "application/json": {"schema": {"$ref": "#/components/schemas/Id"}},
+                            }}},
+                        },
+                    },
+                    "/pets/{id}": {
+                        "put": {
+                            "operationId": "updatePet",
+                            "requestBody": {"content": {"application/json": {"schema": {
+                                "type": "object",
+                                "properties": {"id": {"type": "integer"}},
+                            }}}},
+                            "responses": {"200": {"description": "ok"}},
+                        },
+                    },
+                },
+                "components": {
+                    "schemas": {
+                        "Id": {"type": "object", "properties": {"id": {"type": "integer"}}},
+                    },
+                },
+            }))
+        }
+
+        #[test]
+        fn hoists_and_deduplicates_inline_schemas() {
+            let mut spec = spec();
+            let hoisted = spec.hoist_schemas();
+            assert_eq!(
+                hoisted,
+                [
+                    HoistedSchema {
+                        name: "ListPetsSort".to_string(),
+                        pointers: vec!["/paths/~1pets/get/parameters/0/schema".to_string()],
+                    },
+                    HoistedSchema {
+                        name: "ListPets200ResponseItem".to_string(),
+                        pointers: vec![
+                            "/paths/~1pets/get/responses/200/content/application~1json/schema/items"
+                                .to_string(),
+                            "/paths/~1pets/post/requestBody/content/application~1json/schema"
+                                .to_string(),
+                        ],
+                    },
+                    HoistedSchema {
+                        name: "Id".to_string(),
+                        pointers: vec![
+                            "/paths/~1pets~1{id}/put/requestBody/content/application~1json/schema"
+                                .to_string(),
+                        ],
+                    },
+                ]
+            );
+            let spec = spec.to_value();
+            assert_eq!(
+                spec["paths"]["/pets"]["post"]["requestBody"]["content"]["application/json"]
+                    ["schema"],
+                json!({"$ref": "#/components/schemas/ListPets200ResponseItem"})
+            );
+            assert_eq!(
+                spec["paths"]["/pets"]["get"]["parameters"][1]["schema"],
+                json!({"type": "integer"})
+            );
+            assert_eq!(
+                spec["components"]["schemas"]
+                    .as_object()
+                    .unwrap()
+                    .keys()
+                    .collect::<Vec<_>>(),
+                ["Id", "ListPets200ResponseItem", "ListPetsSort"]
+            );
+        }
+
+        #[test]
+        fn names_schemas_without_conflicts() {
+            let mut spec = spec();
+            spec.paths.get_mut("/pets").unwrap().get = None;
+            let components = spec.components.as_mut().unwrap();
+            let id = components.schemas.as_ref().unwrap()["Id"].clone();
+            components
+                .schemas
+                .as_mut()
+                .unwrap()
+                .insert("PostPetsBody".to_string(), id);
+            let hoisted = spec.hoist_schemas();
+            assert_eq!(hoisted[0].name, "PostPetsBody2");
+            assert!(spec.hoist_schemas().is_empty());
+        }
+    }
+
+    mod jsonpath {
+        use crate::{JsonPath, OpenAPIV3};
+        use serde_json::json;
+
+        #[test]
+        fn queries_documents() {
+            let spec: OpenAPIV3 =
+                serde_json::from_str(include_str!("../examples/v3.0/json/petstore.json")).unwrap();
+            let pointers = |query: &str| {
+                spec.query(query)
+                    .unwrap()
+                    .into_iter()
+                    .map(|(pointer, _)| pointer)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                pointers("$.paths.*[?@.operationId == 'listPets']"),
+                ["/paths/~1pets/get"]
+            );
+            assert_eq!(
+                pointers(
+                    "$.paths.*[?match(@.operationId, 'show.*') || @.operationId == 'createPets']"
+                ),
+                ["/paths/~1pets/post", "/paths/~1pets~1{petId}/get"]
+            );
+            assert_eq!(
+                pointers("$..parameters[?@.in == 'query' && @.required != true].name"),
+                ["/paths/~1pets/get/parameters/0/name"]
+            );
+            let (_, value) = spec
+                .query("$.paths['/pets'].get.operationId")
+                .unwrap()
+                .remove(0);
+            assert_eq!(value, "listPets");
+        }
+
+        #[test]
+        fn evaluates_slices_and_functions() {
+            let document = json!({
+                "items": [1, 2.0, "three", {"four": [4]}, [5, 5]],
+                "limit": 2,
+            });
+            let values = |query: &str| {
+                JsonPath::parse(query)
+                    .unwrap()
+                    .query(&document)
+                    .into_iter()
+                    .map(|node| node.value.clone())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(values("$.items[1:3]"), [json!(2.0), json!("three")]);
+            assert_eq!(
+                values("$.items[::-2]"),
+                [json!([5, 5]), json!("three"), json!(1)]
+            );
+            assert_eq!(values("$.items[?@ == 2]"), [json!(2.0)]);
+            assert_eq!(values("$.items[?@ < $.limit]"), [json!(1)]);
+            assert_eq!(values("$.items[?length(@) == 2]"), [json!([5, 5])]);
+            assert_eq!(values("$.items[?count(@.*) == 1]"), [json!({"four": [4]})]);
+            assert_eq!(
+                values("$.items[?value(@..four[0]) == 4].four"),
+                [json!([4])]
+            );
+            assert_eq!(values("$.items[?search(@, 'hr')]"), [json!("three")]);
+        }
+
+        #[test]
+        fn evaluates_extreme_slices() {
+            let document = json!([0, 1, 2, 3, 4, 5, 6, 7]);
+            let values = |query: &str| {
+                JsonPath::parse(query)
+                    .unwrap()
+                    .query(&document)
+                    .into_iter()
+                    .map(|node| node.value.clone())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(values("$[5::9223372036854775807]"), [json!(5)]);
+            assert_eq!(values("$[2::-9223372036854775808]"), [json!(2)]);
+            assert_eq!(
+                values("$[-9223372036854775808:9223372036854775807:4]"),
+                [json!(0), json!(4)]
+            );
+            assert_eq!(
+                values("$[9223372036854775807:-9223372036854775808:-7]"),
+                [json!(7), json!(0)]
+            );
+            assert!(values("$[-9223372036854775808]").is_empty());
+        }
+
+        #[test]
+        fn rejects_invalid_queries() {
+            for query in [
+                "paths",
+                "$.items[?@.* == 1]",
+                "$.items[?length(@)]",
+                "$.items[?match(@)]",
+                "$.items[?count(1) == 1]",
+                "$.items[?unknown(@)]",
+                "$.items[?1]",
+                "$.items[1:2",
+            ] {
+                assert!(JsonPath::parse(query).is_err(), "{query}");
+            }
+            let error = JsonPath::parse("$.items[?@.a ==]").unwrap_err();
+            assert_eq!(error.position, 15);
+        }
+    }
+
+    mod lint {
+        use super::document;
+        use crate::{LintConfig, LintRule, Linter, OpenAPIV3, Severity, Violation};
+        use serde_json::json;
+
+        fn spec() -> OpenAPIV3 {
+            document(json!({
+                "servers": [{"url": "http://pets.example.com"}, {"url": "http://localhost:8080"}],
+                "paths": {
+                    "/pets": {
+                        "get": {
+                            "operationId": "list_pets",
+                            "summary": "List the pets",
+                            "tags": ["pets"],
+                            "responses": {"200": {"description": "ok"}},
+                        },
+                        "post": {
+                            "operationId": "list_pets",
+                            "description": "Create a pet",
+                            "tags": ["admin"],
+                            "responses": {"201": {"description": "ok", "content": {
+                                "application/json": {
+                                    "schema": {"type": "integer"},
+                                    "example": "one",
+                                },
+                            }}},
+                        },
+                        "delete": {"responses": {"204": {"description": "ok"}}},
+                    },
+                },
+                "components": {"schemas": {"Pet": {"type": "object"}}},
+                "tags": [{"name": "pets"}],
+            }))
+        }
+
+        #[test]
+        fn reports_built_in_rules() {
+            let report = spec().lint(&LintConfig::default());
+            let issues = report
+                .issues
+                .iter()
+                .map(|issue| (issue.rule.as_str(), issue.violation.pointer.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                issues,
+                [
+                    ("operation-id-unique", "/paths/~1pets/post/operationId"),
+                    (
+                        "valid-examples",
+                        "/paths/~1pets/post/responses/201/content/application~1json/example"
+                    ),
+                    ("operation-id", "/paths/~1pets/delete"),
+                    ("operation-id-camel-case", "/paths/~1pets/get/operationId"),
+                    ("operation-id-camel-case", "/paths/~1pets/post/operationId"),
+                    ("operation-description", "/paths/~1pets/delete"),
+                    ("operation-tags-declared", "/paths/~1pets/post/tags/0"),
+                    ("no-unused-components", "/components/schemas/Pet"),
+                    ("servers-https", "/servers/0/url"),
+                ]
+            );
+            assert!(report.fails(Severity::Error));
+            assert_eq!(
+                report.issues[2].to_string(),
+                "warning[operation-id]: /paths/~1pets/delete: operation `DELETE /pets` has no operationId"
+            );
+        }
+
+        #[test]
+        fn configures_severities_and_rules() {
+            struct Versioned;
+
+            impl LintRule for Versioned {
+                fn name(&self) -> &str {
+                    "info-version-semver"
+                }
+
+                fn check(&self, spec: &OpenAPIV3) -> Vec<Violation> {
+                    match semver::Version::parse(&spec.info.version) {
+                        Ok(_) => vec![],
+                        Err(_) => vec![Violation::new("/info/version", "not a semantic version")],
+                    }
+                }
+            }
+
+            let config: LintConfig = serde_json::from_value(json!({"severities": {
+                "operation-id-unique": "off",
+                "valid-examples": "info",
+                "operation-id": "off",
+                "operation-id-camel-case": "off",
+                "operation-description": "off",
+                "operation-tags-declared": "off",
+                "no-unused-components": "off",
+                "servers-https": "error",
+            }}))
+            .unwrap();
+            let mut spec = spec();
+            spec.info.version = "1".to_string();
+            let report = Linter::new(config).with_rule(Versioned).lint(&spec);
+            let issues = report
+                .issues
+                .iter()
+                .map(|issue| (issue.severity, issue.rule.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                issues,
+                [
+                    (Severity::Error, "servers-https"),
+                    (Severity::Warning, "info-version-semver"),
+                    (Severity::Info, "valid-examples"),
+                ]
+            );
+            assert!(report.fails(Severity::Error));
+        }
+    }
+
+    mod source_map {
+        use crate::{OpenAPIV3, Position, SourceMap};
+
+        const TEXT: &str = r#"{
+  "openapi": "3.0.3",
+  "info": {"title": "Pétstore", "version": "1.0.0"},
+  "paths": {
+    "/pets": {
+      "get": {
+        "tags": ["pets", "a\"b"],
+        "responses": {"200": {"description": "ok"}}
+      }
+    }
+  }
+}"#;
+
+        #[test]
+        fn records_spans_of_values_and_keys() {
+            let (spec, map) = OpenAPIV3::from_json_with_source_map(TEXT).unwrap();
+            assert_eq!(spec.info.version, "1.0.0");
+            let span = map.span("/info/version").unwrap();
+            assert_eq!(
+                (span.start, span.end),
+                (
+                    Position {
+                        line: 3,
+                        column: 44,
+                        offset: 68
+                    },
+                    Position {
+                        line: 3,
+                        column: 51,
+                        offset: 75
+                    },
+                )
+            );
+            assert_eq!(&TEXT[span.start.offset..span.end.offset], "\"1.0.0\"");
+            let key = map.key_span("/paths/~1pets/get").unwrap();
+            assert_eq!(key.to_string(), "6:7");
+            let span = map.span("/paths/~1pets/get/tags/1").unwrap();
+            assert_eq!(&TEXT[span.start.offset..span.end.offset], r#""a\"b""#);
+            let span = map.span("").unwrap();
+            assert_eq!(
+                (span.start.line, span.end.line, span.end.column),
+                (1, 12, 2)
+            );
+        }
+
+        #[test]
+        fn finds_the_nearest_existing_value() {
+            let map = SourceMap::from_json(TEXT).unwrap();
+            let (pointer, span) = map
+                .nearest("/paths/~1pets/get/responses/200/content/application~1json")
+                .unwrap();
+            assert_eq!(pointer, "/paths/~1pets/get/responses/200");
+            assert_eq!(span.to_string(), "8:30");
+            assert!(SourceMap::default().nearest("/paths").is_none());
+            assert!(SourceMap::from_json("{").is_err());
+        }
+
+        #[test]
+        fn counts_columns_in_characters_on_long_lines() {
+            let text = format!(r#"{{"é":"{}","b":[1,"ü",2]}}"#, "x".repeat(10_000));
+            let map = SourceMap::from_json(&text).unwrap();
+            let span = map.span("/b/2").unwrap();
+            assert_eq!(&text[span.start.offset..span.end.offset], "2");
+            assert_eq!((span.start.line, span.start.column), (1, 10_020));
+        }
+
+        #[test]
+        fn records_spans_of_yaml_values() {
+            let text = "\
+# Pets
+openapi: 3.0.3
+info:
+  title: 'Pet''s store'   # the title
+  description: |
+    Some
+      text
+
+  version: 1.0.0
+paths:
+  /pets:
+    get:
+      tags: [pets, {name: \"a, b\"}]
+      parameters:
+      - name: limit
+        in: query
+      -
+        $ref: '#/components/parameters/Offset'
+      responses:
+        '200':
+          description: a long
+            description
+";
+            let map = SourceMap::from_yaml(text);
+            let value = |pointer: &str| {
+                let span = map.span(pointer).unwrap();
+                &text[span.start.offset..span.end.offset]
+            };
+            assert_eq!(value("/openapi"), "3.0.3");
+            assert_eq!(value("/info/title"), "'Pet''s store'");
+            assert_eq!(value("/info/description"), "|\n    Some\n      text");
+            assert_eq!(value("/info/version"), "1.0.0");
+            assert_eq!(value("/paths/~1pets/get/tags/1/name"), "\"a, b\"");
+            assert_eq!(value("/paths/~1pets/get/parameters/0/in"), "query");
+            assert_eq!(
+                value("/paths/~1pets/get/parameters/1/$ref"),
+                "'#/components/parameters/Offset'"
+            );
+            assert_eq!(
+                value("/paths/~1pets/get/responses/200/description"),
+                "a long\n            description"
+            );
+            assert_eq!(
+                map.span("/paths/~1pets/get/parameters")
+                    .unwrap()
+                    .to_string(),
+                "15:7"
+            );
+            assert_eq!(map.key_span("/paths/~1pets").unwrap().to_string(), "11:3");
+            assert_eq!(map.span("").unwrap().end.line, 22);
+        }
+    }
+
+    mod mock {
+        use super::document;
+        use crate::{Mock, MockRequest, MockServer, OpenAPIV3};
+        use serde_json::json;
+        use std::io::{Read, Write};
+        use std::net::TcpStream;
+
+        fn petstore() -> Mock {
+            Mock::new(
+                serde_json::from_str::<OpenAPIV3>(include_str!(
+                    "../examples/v3.0/json/petstore.json"
+                ))
+                .unwrap(),
+            )
+        }
+
+        fn examples() -> Mock {
+            Mock::new(document(json!({
+                "info": {"title": "Examples", "version": "1.0.0"},
+                "paths": {
+                    "/pets/{id}": {
+                        "get": {
+                            "parameters": [
+                                {"name": "id", "in": "path", "required": true, "schema": {"type": "integer"}}
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "A pet",
+                                    "content": {
+                                        "application/json": {
+                                            "examples": {
+                                                "cat": {"value": {"name": "Tom"}},
+                                                "dog": {"value": {"name": "Rex"}}
+                                            }
+                                        }
+                                    }
+                                },
+                                "404": {
+                                    "description": "Not found",
+                                    "content": {
+                                        "application/json": {"example": {"message": "no such pet"}}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })))
+        }
+
+        #[test]
+        fn answers_with_a_sample_of_the_schema() {
+            let response = petstore().handle(&MockRequest::new("GET", "/v1/pets/1"));
+
+            assert_eq!(response.status, 200);
+            assert_eq!(response.header("content-type"), Some("application/json"));
+            assert_eq!(
+                response.json(),
+                Some(json!([{"id": 0, "name": "string", "tag": "string"}]))
+            );
+        }
+
+        #[test]
+        fn prefer_selects_the_status_code_and_example() {
+            let mock = examples();
+
+            let response = mock.handle(&MockRequest::new("GET", "/pets/1"));
+            assert_eq!(response.json(), Some(json!({"name": "Tom"})));
+
+            let request = MockRequest::new("GET", "/pets/1").with_header("Prefer", "example=dog");
+            assert_eq!(mock.handle(&request).json(), Some(json!({"name": "Rex"})));
+
+            let request = MockRequest::new("GET", "/pets/1").with_header("Prefer", "code=404");
+            let response = mock.handle(&request);
+            assert_eq!(response.status, 404);
+            assert_eq!(response.json(), Some(json!({"message": "no such pet"})));
+        }
+
+        #[test]
+        fn rejects_invalid_requests() {
+            let mock = petstore();
+
+            let response = mock.handle(&MockRequest::new("GET", "/v1/pets?limit=many"));
+            assert_eq!(response.status, 400);
+            assert_eq!(response.json().unwrap()["errors"][0]["name"], "limit");
+
+            let response = mock.handle(&MockRequest::new("DELETE", "/v1/pets"));
+            assert_eq!(response.status, 405);
+            assert_eq!(response.header("allow"), Some("GET, POST"));
+
+            let response = mock.handle(&MockRequest::new("GET", "/v1/owners"));
+            assert_eq!(response.status, 404);
+        }
+
+        #[test]
+        fn serves_over_http() {
+            let spec = serde_json::from_str::<OpenAPIV3>(include_str!(
+                "../examples/v3.0/json/petstore.json"
+            ))
+            .unwrap();
+            let server = MockServer::start(spec).unwrap();
+
+            let mut stream = TcpStream::connect(server.address()).unwrap();
+            stream
+                .write_all(
+                    b"POST /v1/pets HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\n\r\n",
+                )
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+
+            assert!(response.starts_with("HTTP/1.1 201 Created\r\n"));
+            server.stop();
+        }
+
+        #[test]
+        fn rejects_oversized_requests() {
+            let spec = serde_json::from_str::<OpenAPIV3>(include_str!(
+                "../examples/v3.0/json/petstore.json"
+            ))
+            .unwrap();
+            let server = MockServer::start(spec).unwrap();
+            let send = |request: &[u8]| {
+                let mut stream = TcpStream::connect(server.address()).unwrap();
+                stream.write_all(request).unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                response
+            };
+
+            let response =
+                send(b"POST /v1/pets HTTP/1.1\r\nContent-Length: 18446744073709551615\r\n\r\n");
+            assert!(response.starts_with("HTTP/1.1 413 Content Too Large\r\n"));
+            let response = send(
+                b"POST /v1/pets HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffffffff\r\n",
+            );
+            assert!(response.starts_with("HTTP/1.1 413 Content Too Large\r\n"));
+            let mut request = b"GET /v1/pets HTTP/1.1\r\nX-Padding: ".to_vec();
+            request.resize(100_000, b'a');
+            let response = send(&request);
+            assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+            server.stop();
+        }
+
+        #[test]
+        fn times_out_slow_requests() {
+            let spec = serde_json::from_str::<OpenAPIV3>(include_str!(
+                "../examples/v3.0/json/petstore.json"
+            ))
+            .unwrap();
+            let server = MockServer::start(spec).unwrap();
+
+            // each byte comes before the read times out, but the request takes too long
+            let mut stream = TcpStream::connect(server.address()).unwrap();
+            stream.write_all(b"GET /v1/pets HTTP/1.1\r\n").unwrap();
+            for byte in b"X-Slow: 1234" {
+                std::thread::sleep(std::time::Duration::from_millis(150));
+                if stream.write_all(&[*byte]).is_err() {
+                    break;
+                }
+            }
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+
+            assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+            server.stop();
+        }
+    }
+
+    mod extract {
+        use crate::{ExtractError, Method, OpenAPIV3, ParameterIn, Router};
+
+        #[test]
+        fn extracts_typed_parameters() {
+            let spec: OpenAPIV3 =
+                serde_json::from_str(include_str!("../examples/v3.0/json/petstore-expanded.json"))
+                    .unwrap();
+            let router = Router::new(&spec);
+
+            let route = router.route(Method::Get, "/api/pets?limit=3").unwrap();
+            let parts = route.extract(&spec, "/api/pets?limit=3", &[], &[]).unwrap();
+            assert_eq!(
+                parts.parameter::<i32>(ParameterIn::Query, "limit"),
+                Ok(Some(3))
+            );
+            assert_eq!(
+                parts.parameter::<Vec<String>>(ParameterIn::Query, "tags"),
+                Ok(None)
+            );
+
+            let route = router.route(Method::Post, "/api/pets").unwrap();
+            let error = route.extract(&spec, "/api/pets", &[], b"{}").unwrap_err();
+            assert_eq!(error.status(), 400);
+            let ExtractError::Invalid(errors) = error else {
+                panic!("expected invalid request");
+            };
+            assert_eq!(
+                errors[0].to_string(),
+                "body: missing required property `name`"
+            );
+        }
+
+        #[test]
+        fn parses_exploded_objects_with_dotted_values() {
+            let parameter = |style: &str| {
+                serde_json::from_value::<crate::Parameter>(serde_json::json!({
+                    "name": "item",
+                    "in": "path",
+                    "required": true,
+                    "style": style,
+                    "explode": true,
+                    "schema": {
+                        "type": "object",
+                        "properties": {"price": {"type": "number"}, "mail": {"type": "string"}}
+                    }
+                }))
+                .unwrap()
+            };
+            let expected = serde_json::json!({"price": 1.5, "mail": "a@b.com"});
+
+            for (style, raw) in [
+                ("simple", "price=1.5,mail=a@b.com"),
+                ("matrix", ";price=1.5;mail=a@b.com"),
+            ] {
+                assert_eq!(parameter(style).parse(raw, None), Some(expected.clone()));
+            }
+            assert_eq!(
+                parameter("label").parse(".price=2.mail=a@b,c", None),
+                Some(serde_json::json!({"price": 2, "mail": "a@b,c"}))
+            );
+        }
+    }
+
+    mod compatibility {
+        use super::document;
+        use crate::{ChangeRule, CompatibilityConfig, OpenAPIV3, Severity};
+        use serde_json::{json, Value};
+
+        fn spec(operation: Value) -> OpenAPIV3 {
+            document(json!({
+                "paths": {"/pets": {"post": operation}, "/owners": {"get": {"responses": {}}}},
+            }))
+        }
+
+        fn operation(status: Value, limit: Value, name: Value, kind: Value) -> Value {
+            json!({
+                "parameters": [{"name": "limit", "in": limit, "schema": {"type": "integer"}}],
+                "requestBody": {"content": {"application/json": {"schema": {
+                    "type": "object",
+                    "properties": {"name": name, "status": status},
+                }}}},
+                "responses": {"200": {"description": "OK", "content": {"application/json": {"schema": {
+                    "type": "object",
+                    "properties": {"kind": kind, "id": {"type": "integer"}},
+                }}}}},
+            })
+        }
+
+        #[test]
+        fn classifies_breaking_changes() {
+            let old = spec(operation(
+                json!({"type": "string", "enum": ["available", "sold"]}),
+                json!("query"),
+                json!({"type": "string", "maxLength": 20}),
+                json!({"type": "string", "enum": ["cat"]}),
+            ));
+            let mut new = spec(operation(
+                json!({"type": "string", "enum": ["available"]}),
+                json!("header"),
+                json!({"type": "string", "maxLength": 10}),
+                json!({"type": "string", "enum": ["cat", "dog"]}),
+            ));
+            new.paths.retain(|path, _| path != "/owners");
+            let report = old.check_compatibility(&new, &CompatibilityConfig::default());
+            let rules = report
+                .breaking()
+                .map(|change| change.rule.unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                rules,
+                [
+                    ChangeRule::RemovedPath,
+                    ChangeRule::ChangedParameterLocation,
+                    ChangeRule::ChangedParameterLocation,
+                    ChangeRule::TightenedRequestConstraint,
+                    ChangeRule::NarrowedEnum,
+                    ChangeRule::WidenedResponseEnum,
+                ]
+            );
+            assert_eq!(report.max_severity(), Some(Severity::Error));
+            assert!(report.fails(Severity::Error));
+            assert_eq!(
+                report.breaking().nth(3).unwrap().to_string(),
+                "error[tightened-request-constraint]: changed Schema `maxLength` \
+                 /paths/~1pets/post/requestBody/content/application~1json/schema/properties/name -> \
+                 /paths/~1pets/post/requestBody/content/application~1json/schema/properties/name: 20 -> 10"
+            );
+        }
+
+        #[test]
+        fn severities_are_configurable() {
+            let old = spec(operation(
+                json!({"type": "string"}),
+                json!("query"),
+                json!({"type": "string"}),
+                json!({"type": "string", "enum": ["cat"]}),
+            ));
+            let new = spec(operation(
+                json!({"type": "string"}),
+                json!("query"),
+                json!({"type": "string", "maxLength": 30}),
+                json!({"type": "string", "enum": ["cat", "dog"]}),
+            ));
+            let config: CompatibilityConfig = serde_json::from_value(json!({
+                "severities": {"tightened-request-constraint": "warning", "widened-response-enum": "off"},
+            }))
+            .unwrap();
+            let report = old.check_compatibility(&new, &config);
+            assert_eq!(report.changes.len(), 2);
+            assert_eq!(report.max_severity(), Some(Severity::Warning));
+            assert!(!report.fails(Severity::Error));
+            assert_eq!(report.breaking().count(), 1);
+        }
+
+        #[test]
+        fn compares_negative_minimums() {
+            let spec = |name: Value| {
+                self::spec(operation(
+                    json!({"type": "string"}),
+                    json!("query"),
+                    name,
+                    json!({"type": "string"}),
+                ))
+            };
+            let breaking = |old: Value, new: Value| {
+                spec(old)
+                    .check_compatibility(&spec(new), &CompatibilityConfig::default())
+                    .breaking()
+                    .map(|change| change.rule.unwrap())
+                    .collect::<Vec<_>>()
+            };
+            let (none, low, high) = (
+                json!({"type": "integer"}),
+                json!({"type": "integer", "minimum": -10}),
+                json!({"type": "integer", "minimum": -5}),
+            );
+            assert_eq!(breaking(high.clone(), none.clone()), []);
+            assert_eq!(breaking(high.clone(), low.clone()), []);
+            assert_eq!(
+                breaking(none, high.clone()),
+                [ChangeRule::TightenedRequestConstraint]
+            );
+            assert_eq!(
+                breaking(low, high),
+                [ChangeRule::TightenedRequestConstraint]
+            );
+        }
+
+        #[test]
+        fn compares_security_requirements() {
+            let breaking = |old: Value, new: Value| {
+                let spec = |security: Value| {
+                    let mut operation = json!({"responses": {}});
+                    if !security.is_null() {
+                        operation["security"] = security;
+                    }
+                    self::spec(operation)
+                };
+                spec(old)
+                    .check_compatibility(&spec(new), &CompatibilityConfig::default())
+                    .breaking()
+                    .map(|change| change.rule.unwrap())
+                    .collect::<Vec<_>>()
+            };
+            let (key, oauth) = (json!({"key": []}), json!({"oauth": ["read", "write"]}));
+            assert_eq!(breaking(json!([key, oauth]), json!([])), []);
+            assert_eq!(breaking(json!([key, oauth]), Value::Null), []);
+            assert_eq!(breaking(json!([oauth]), json!([{"oauth": ["read"]}])), []);
+            assert_eq!(breaking(json!([key, oauth]), json!([oauth, {}])), []);
+            assert_eq!(
+                breaking(json!([key, oauth]), json!([oauth])),
+                [ChangeRule::StricterSecurity]
+            );
+            assert_eq!(
+                breaking(json!([]), json!([key])),
+                [ChangeRule::StricterSecurity]
+            );
+        }
+    }
+
+    mod canonical {
+        use super::document;
+        use crate::{CanonicalOptions, Defaults, ParameterIn, Referenceable, Style};
+        use serde_json::json;
+
+        fn options(defaults: Defaults) -> CanonicalOptions {
+            CanonicalOptions { defaults }
+        }
+
+        #[test]
+        fn ignores_the_order_of_keys_and_defaults() {
+            let spec_a = document(json!({
+                "paths": {
+                    "/pets": {
+                        "post": {"responses": {"201": {"description": "created"}}},
+                        "get": {
+                            "deprecated": false,
+                            "parameters": [{"name": "limit", "in": "query", "style": "form", "schema": {}}],
+                            "responses": {
+                                "404": {"description": "missing"},
+                                "200": {"description": "ok"},
+                                "default": {"description": "error"},
+                            },
+                        },
+                    },
+                    "/owners": {"get": {"responses": {"200": {"description": "ok"}}}},
+                },
+                "info": {"version": "1.0.0", "title": "Pets"},
+                "openapi": "3.0.3",
+            }));
+            let spec_b = document(json!({
+                "openapi": "3.0.3",
+                "info": {"title": "Pets", "version": "1.0.0"},
+                "paths": {
+                    "/owners": {"get": {"responses": {"200": {"description": "ok"}}}},
+                    "/pets": {
+                        "get": {
+                            "parameters": [{"name": "limit", "in": "query", "required": false, "schema": {}}],
+                            "responses": {
+                                "default": {"description": "error"},
+                                "200": {"description": "ok"},
+                                "404": {"description": "missing"},
+                            },
+                        },
+                        "post": {"responses": {"201": {"description": "created"}}},
+                    },
+                },
+            }));
+
+            let canonical = spec_a.to_canonical_string(&options(Defaults::Strip));
+            assert_eq!(
+                canonical,
+                spec_b.to_canonical_string(&options(Defaults::Strip))
+            );
+            assert!(canonical.starts_with(
+                r#"{"openapi":"3.0.3","info":{"title":"Pets","version":"1.0.0"},"paths":{"/owners""#
+            ));
+            assert!(
+                canonical.contains(r#""parameters":[{"name":"limit","in":"query","schema":{}}],"#)
+            );
+            assert!(canonical.contains(r#"{"default":{"description":"error"},"200":"#));
+            assert_ne!(
+                spec_a.to_canonical_string(&options(Defaults::Keep)),
+                spec_b.to_canonical_string(&options(Defaults::Keep))
+            );
+        }
+
+        #[test]
+        fn makes_defaults_explicit() {
+            let mut spec = document(json!({
+                "paths": {"/pets/{id}": {
+                    "parameters": [
+                        {"name": "id", "in": "path", "required": true, "schema": {"type": "string"}},
+                        {"name": "tags", "in": "query", "style": "spaceDelimited", "schema": {
+                            "type": "array", "items": {"type": "string", "maximum": 3},
+                        }},
+                    ],
+                }},
+            }));
+            spec.canonicalize(&options(Defaults::Explicit));
+
+            let parameters = spec.paths["/pets/{id}"]
+                .parameters
+                .iter()
+                .flatten()
+                .map(|parameter| match parameter {
+                    Referenceable::Data(parameter) => parameter,
+                    Referenceable::Reference(_) => panic!("Should be inline"),
+                })
+                .collect::<Vec<_>>();
+            let (id, tags) = (parameters[0], parameters[1]);
+            assert_eq!(id._in, ParameterIn::Path);
+            assert_eq!(
+                (&id.style, id.explode, id.allow_reserved),
+                (&Some(Style::Simple), Some(false), None)
+            );
+            assert_eq!(
+                (
+                    &tags.style,
+                    tags.explode,
+                    tags.required,
+                    tags.allow_reserved
+                ),
+                (
+                    &Some(Style::SpaceDelimited),
+                    Some(false),
+                    Some(false),
+                    Some(false)
+                )
+            );
+            let value = serde_json::to_value(&tags.schema).unwrap();
+            assert_eq!(value["minItems"], 0);
+            assert_eq!(value["uniqueItems"], false);
+            assert_eq!(value["items"]["nullable"], false);
+            assert_eq!(value["items"]["exclusiveMaximum"], false);
+            assert_eq!(value["items"]["minLength"], 0);
+        }
+    }
+
+    #[cfg(feature = "preserve_order")]
+    mod preserve_order {
+        use crate::OpenAPIV3;
+        use serde_json::json;
+
+        #[test]
+        fn round_trips_the_order_of_keys() {
+            let value = json!({
+                "openapi": "3.0.3",
+                "info": {"title": "Pets", "version": "1.0.0"},
+                "paths": {
+                    "/pets": {
+                        "get": {
+                            "responses": {
+                                "404": {"description": "missing"},
+                                "200": {"description": "ok", "content": {"application/json": {
+                                    "schema": {"$ref": "#/components/schemas/Pet"},
+                                }}},
+                            },
+                            "x-rate-limit": 10,
+                            "x-internal": false,
+                        },
+                    },
+                    "/owners": {"get": {"responses": {"default": {"description": "ok"}}}},
+                },
+                "components": {
+                    "schemas": {
+                        "Pet": {"type": "object", "properties": {
+                            "name": {"type": "string"},
+                            "id": {"type": "integer"},
+                        }},
+                        "Owner": {"type": "object"},
+                    },
+                },
+            });
+            let spec: OpenAPIV3 = serde_json::from_value(value.clone()).unwrap();
+
+            assert_eq!(spec.paths.keys().collect::<Vec<_>>(), ["/pets", "/owners"]);
+            // `Value` ignores the order of the keys when compared
+            assert_eq!(
+                serde_json::to_string(&spec).unwrap(),
+                serde_json::to_string(&value).unwrap()
+            );
+        }
+    }
+
+    mod diff {
+        use super::document;
+        use crate::{ChangeKind, Direction, Element, OpenAPIV3};
+        use serde_json::{json, Value};
+
+        fn spec(paths: Value, schemas: Value) -> OpenAPIV3 {
+            document(json!({
+                "paths": paths,
+                "components": {"schemas": schemas},
+            }))
+        }
+
+        fn pet_response(schema: Value) -> Value {
+            json!({
+                "/pets/{id}": {
+                    "get": {
+                        "parameters": [{"name": "id", "in": "path", "required": true, "schema": {"type": "integer"}}],
+                        "responses": {
+                            "200": {"description": "OK", "content": {"application/json": {"schema": schema}}},
+                        },
+                    },
+                },
+            })
+        }
+
+        #[test]
+        fn follows_references() {
+            let pet = json!({
+                "type": "object",
+                "properties": {"name": {"type": "string"}, "tag": {"type": "string"}},
+            });
+            let old = spec(pet_response(pet.clone()), json!({}));
+            let new = spec(
+                pet_response(json!({"$ref": "#/components/schemas/Pet"})),
+                json!({"Pet": pet}),
+            );
+            assert_eq!(old.diff(&new), vec![]);
+
+            let mut changed = new.clone();
+            let schemas = changed
+                .components
+                .as_mut()
+                .unwrap()
+                .schemas
+                .as_mut()
+                .unwrap();
+            let crate::Referenceable::Data(pet) = schemas.get_mut("Pet").unwrap() else {
+                panic!("expected a schema");
+            };
+            pet.properties
+                .as_mut()
+                .unwrap()
+                .retain(|name, _| name != "tag");
+            pet.required = Some(vec!["name".to_string()]);
+            let changes = old.diff(&changed);
+            assert_eq!(
+                changes
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
+                [
+                    "changed Schema `required` /paths/~1pets~1{id}/get/responses/200/content/application~1json/schema -> /components/schemas/Pet: nothing -> [\"name\"]",
+                    "removed Property /paths/~1pets~1{id}/get/responses/200/content/application~1json/schema/properties/tag",
+                ]
+            );
+            assert_eq!(changes[1].direction, Some(Direction::Response));
+            assert_eq!(changes[1].new, "/components/schemas/Pet");
+        }
+
+        #[test]
+        fn compares_operations_and_parameters() {
+            let old = spec(pet_response(json!({"type": "object"})), json!({}));
+            let mut paths = pet_response(json!({"type": "object"}));
+            paths["/pets/{petId}"] = paths["/pets/{id}"].take();
+            paths.as_object_mut().unwrap().remove("/pets/{id}");
+            paths["/pets/{petId}"]["get"]["parameters"] = json!([
+                {"name": "petId", "in": "path", "required": true, "schema": {"type": "integer"}},
+                {"name": "fields", "in": "query", "required": true, "schema": {"type": "string"}},
+            ]);
+            paths["/pets/{petId}"]["delete"] =
+                json!({"responses": {"204": {"description": "Deleted"}}});
+            paths["/pets/{petId}"]["get"]["security"] = json!([{"apiKey": []}]);
+            let new = spec(paths, json!({}));
+
+            let changes = old.diff(&new);
+            let summary = changes
+                .iter()
+                .map(|change| (change.kind, change.element, change.new.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                summary,
+                [
+                    (
+                        ChangeKind::Added,
+                        Element::Parameter,
+                        "/paths/~1pets~1{petId}/get/parameters/1"
+                    ),
+                    (
+                        ChangeKind::Added,
+                        Element::SecurityRequirement,
+                        "/paths/~1pets~1{petId}/get/security/0"
+                    ),
+                    (
+                        ChangeKind::Added,
+                        Element::Operation,
+                        "/paths/~1pets~1{petId}/delete"
+                    ),
+                ]
+            );
+            assert_eq!(changes[0].direction, Some(Direction::Request));
+        }
+
+        #[test]
+        fn compares_required_and_enum_as_sets() {
+            let schema = |required: Value, kind: Value| {
+                json!({
+                    "type": "object",
+                    "required": required,
+                    "properties": {"name": {"type": "string"}, "kind": {"enum": kind}},
+                })
+            };
+            let old = spec(
+                pet_response(schema(json!(["name", "kind"]), json!(["cat", "dog"]))),
+                json!({}),
+            );
+            let new = spec(
+                pet_response(schema(json!(["kind", "name"]), json!(["dog", "cat"]))),
+                json!({}),
+            );
+            assert_eq!(old.diff(&new), vec![]);
+
+            let new = spec(
+                pet_response(schema(json!(["name"]), json!(["dog", "cat", "bird"]))),
+                json!({}),
+            );
+            let fields = old
+                .diff(&new)
+                .into_iter()
+                .map(|change| change.field.unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(fields, ["required", "enum"]);
+        }
+
+        #[test]
+        fn compares_response_headers() {
+            let paths = |headers: Value| {
+                let mut paths = pet_response(json!({"type": "object"}));
+                paths["/pets/{id}"]["get"]["responses"]["200"]["headers"] = headers;
+                paths
+            };
+            let old = spec(
+                paths(json!({
+                    "X-Rate-Limit": {"schema": {"type": "integer"}},
+                    "X-Request-Id": {"schema": {"type": "string"}},
+                })),
+                json!({}),
+            );
+            let new = spec(
+                paths(json!({
+                    "x-rate-limit": {"required": true, "schema": {"type": "string"}},
+                    "ETag": {"schema": {"type": "string"}},
+                })),
+                json!({}),
+            );
+            let changes = old.diff(&new);
+            assert_eq!(
+                changes
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
+                [
+                    "changed Header `required` /paths/~1pets~1{id}/get/responses/200/headers/X-Rate-Limit -> /paths/~1pets~1{id}/get/responses/200/headers/x-rate-limit: nothing -> true",
+                    "changed Schema `type` /paths/~1pets~1{id}/get/responses/200/headers/X-Rate-Limit/schema -> /paths/~1pets~1{id}/get/responses/200/headers/x-rate-limit/schema: \"integer\" -> \"string\"",
+                    "removed Header /paths/~1pets~1{id}/get/responses/200/headers/X-Request-Id",
+                    "added Header /paths/~1pets~1{id}/get/responses/200/headers/ETag",
+                ]
+            );
+            assert_eq!(changes[1].direction, Some(Direction::Response));
+        }
+
+        #[test]
+        fn compares_recursive_schemas() {
+            let node = |child: &str| {
+                json!({
+                    "type": "object",
+                    "properties": {"children": {"type": "array", "items": {"$ref": "#/components/schemas/Node"}}, "value": {"type": child}},
+                })
+            };
+            let old = spec(json!({}), json!({"Node": node("string")}));
+            let new = spec(json!({}), json!({"Node": node("integer")}));
+            let changes = old.diff(&new);
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].field.as_deref(), Some("type"));
+            assert_eq!(changes[0].old, "/components/schemas/Node/properties/value");
+            assert_eq!(changes[0].new_value, Some(json!("integer")));
+        }
+    }
+
+    mod unused {
+        use super::document;
+        use crate::{OpenAPIV3, UnusedComponent};
+        use serde_json::json;
+
+        fn spec() -> OpenAPIV3 {
+            document(json!({
+                "paths": {
+                    "/pets": {
+                        "post": {
+                            "requestBody": {"$ref": "#/components/requestBodies/NewPet"},
+                            "callbacks": {"created": {"$ref": "#/components/callbacks/Created"}},
+                            "responses": {"200": {"description": "ok"}},
+                        },
+                    },
+                },
+                "security": [{"key": []}],
+                "components": {
+                    "schemas": {
+                        "Bird": {"allOf": [{"$ref": "#/components/schemas/Pet"}]},
+                        "Cat": {"type": "object"},
+                        "Dead": {"$ref": "#/components/schemas/Deader"},
+                        "Deader": {"type": "object"},
+                        "Dog": {"type": "object"},
+                        "Event": {"type": "object"},
+                        "Pet": {
+                            "oneOf": [{"$ref": "#/components/schemas/Cat"}],
+                            "discriminator": {"propertyName": "kind", "mapping": {"dog": "Dog"}},
+                        },
+                    },
+                    "requestBodies": {
+                        "NewPet": {"content": {"application/json": {
+                            "schema": {"$ref": "#/components/schemas/Pet"},
+                        }}},
+                    },
+                    "callbacks": {
+                        "Created": {"{$request.body#/url}": {"post": {
+                            "requestBody": {"content": {"application/json": {
+                                "schema": {"$ref": "#/components/schemas/Event"},
+                            }}},
+                            "security": [{"signature": []}],
+                            "responses": {"200": {"description": "ok"}},
+                        }}},
+                    },
+                    "parameters": {
+                        "Limit": {"name": "limit", "in": "query", "schema": {"type": "integer"}},
+                    },
+                    "securitySchemes": {
+                        "basic": {"type": "http", "scheme": "basic"},
+                        "key": {"type": "apiKey", "name": "key", "in": "header"},
+                        "signature": {"type": "http", "scheme": "bearer"},
+                    },
+                },
+            }))
+        }
+
+        fn component(kind: &str, name: &str) -> UnusedComponent {
+            UnusedComponent {
+                kind: kind.to_string(),
+                name: name.to_string(),
+            }
+        }
+
+        #[test]
+        fn finds_unreachable_components() {
+            assert_eq!(
+                spec().unused_components(),
+                [
+                    component("parameters", "Limit"),
+                    component("schemas", "Dead"),
+                    component("schemas", "Deader"),
+                    component("securitySchemes", "basic"),
+                ]
+            );
+            assert_eq!(
+                component("schemas", "Dead").to_string(),
+                "#/components/schemas/Dead is never used"
+            );
+        }
+
+        #[test]
+        fn removes_unused_components() {
+            let mut spec = spec();
+            assert_eq!(spec.remove_unused_components().len(), 4);
+            assert!(spec.unused_components().is_empty());
+            assert!(spec.remove_unused_components().is_empty());
+            let components = spec.components.unwrap();
+            assert_eq!(
+                components.schemas.unwrap().keys().collect::<Vec<_>>(),
+                ["Bird", "Cat", "Dog", "Event", "Pet"]
+            );
+            assert_eq!(
+                components
+                    .security_schemes
+                    .unwrap()
+                    .keys()
+                    .collect::<Vec<_>>(),
+                ["key", "signature"]
+            );
+        }
+    }
+
+    mod sample {
+        use crate::{Components, Sampler, Schema};
+        use serde_json::json;
+
+        fn components() -> Components {
+            serde_json::from_value(json!({
+                "schemas": {
+                    "Cat": {
+                        "type": "object",
+                        "required": ["kind", "lives"],
+                        "properties": {
+                            "kind": {"type": "string"},
+                            "lives": {"type": "integer", "minimum": 1, "maximum": 9}
+                        }
+                    },
+                    "Dog": {
+                        "type": "object",
+                        "required": ["kind"],
+                        "properties": {"kind": {"type": "string"}}
+                    },
+                    "Pet": {
+                        "oneOf": [
+                            {"$ref": "#/components/schemas/Cat"},
+                            {"$ref": "#/components/schemas/Dog"}
+                        ],
+                        "discriminator": {"propertyName": "kind", "mapping": {"dog": "Dog"}}
+                    }
+                }
+            }))
+            .unwrap()
+        }
+
+        fn schema() -> Schema {
+            serde_json::from_value(json!({
+                "type": "object",
+                "required": ["id", "name", "tags", "pet"],
+                "properties": {
+                    "id": {"type": "string", "format": "uuid"},
+                    "name": {"type": "string", "minLength": 3, "maxLength": 5},
+                    "email": {"type": "string", "format": "email"},
+                    "ip": {"type": "string", "format": "ipv4"},
+                    "born": {"type": "string", "format": "date-time"},
+                    "score": {"type": "number", "minimum": 0, "exclusiveMinimum": true, "maximum": 1},
+                    "count": {"type": "integer", "multipleOf": 5, "minimum": 12},
+                    "half": {"type": "integer", "multipleOf": 2.5, "minimum": 6},
+                    "tags": {
+                        "type": "array",
+                        "minItems": 2,
+                        "uniqueItems": true,
+                        "items": {"type": "string", "enum": ["a", "b", "c"]}
+                    },
+                    "pet": {"$ref": "#/components/schemas/Pet"},
+                    "base": {
+                        "allOf": [
+                            {"type": "object", "properties": {"a": {"type": "integer", "example": 1}}},
+                            {"type": "object", "properties": {"b": {"type": "boolean", "default": false}}}
+                        ]
+                    }
+                }
+            }))
+            .unwrap()
+        }
+
+        #[test]
+        fn samples_conform_to_the_schema() {
+            let (schema, components) = (schema(), components());
+
+            let sample = schema.sample(Some(&components));
+            assert_eq!(sample["id"], "3fa85f64-5717-4562-b3fc-2c963f66afa6");
+            assert_eq!(sample["name"], "strin");
+            assert_eq!(sample["count"], 15);
+            assert_eq!(sample["half"], 10);
+            assert_eq!(sample["base"], json!({"a": 1, "b": false}));
+            assert_eq!(sample["pet"], json!({"kind": "Cat", "lives": 1}));
+            schema.validate(&sample, Some(&components)).unwrap();
+
+            for seed in 0..64 {
+                let sample = Sampler::seeded(seed).sample(&schema, Some(&components));
+                if let Err(errors) = schema.validate(&sample, Some(&components)) {
+                    panic!("{sample} is invalid: {errors:?}");
+                }
+            }
+        }
+
+        #[test]
+        fn seeded_samples_are_deterministic() {
+            let (schema, components) = (schema(), components());
+            let sample = |seed| Sampler::seeded(seed).sample(&schema, Some(&components));
+
+            assert_eq!(sample(7), sample(7));
+            assert_ne!(sample(7), sample(8));
+            assert!((0..16).any(|seed| sample(seed)["pet"]["kind"] == "dog"));
+        }
+
+        #[test]
+        fn optional_properties_can_be_left_out() {
+            let sampler = Sampler {
+                optional_properties: false,
+                ..Sampler::default()
+            };
+
+            let sample = sampler.sample(&schema(), Some(&components()));
+
+            let mut keys = sample.as_object().unwrap().keys().collect::<Vec<_>>();
+            keys.sort();
+            assert_eq!(keys, vec!["id", "name", "pet", "tags"]);
+        }
+    }
+
+    mod examples {
+        use super::document;
+        use crate::OpenAPIV3;
+        use serde_json::json;
+
+        #[test]
+        fn reports_examples_not_matching_their_schema() {
+            let spec: OpenAPIV3 = document(json!({
+                "info": {"title": "Examples", "version": "1.0.0"},
+                "paths": {
+                    "/pets": {
+                        "get": {
+                            "parameters": [
+                                {"name": "limit", "in": "query", "schema": {"type": "integer"}, "example": "ten"},
+                                {"$ref": "#/components/parameters/Offset"}
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "Pets",
+                                    "headers": {
+                                        "X-Rate": {"schema": {"type": "integer"}, "example": 10}
+                                    },
+                                    "content": {
+                                        "application/json": {
+                                            "schema": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+                                            "examples": {
+                                                "inline": {"value": [{"name": "Tom"}]},
+                                                "stale": {"$ref": "#/components/examples/Stale"}
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "Pet": {"type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}}
+                    },
+                    "parameters": {
+                        "Offset": {"name": "offset", "in": "query", "schema": {"type": "integer", "minimum": 0}, "example": -1}
+                    },
+                    "examples": {
+                        "Stale": {"value": [{"nickname": "Tom"}]}
+                    }
+                }
+            }));
+
+            let errors = spec.validate_examples();
+            let errors = errors
+                .iter()
+                .map(|error| (error.pointer.as_str(), error.error.pointer.as_str()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                errors,
+                vec![
+                    ("/paths/~1pets/get/parameters/0/example", ""),
+                    ("/components/examples/Stale/value", "/0"),
+                    ("/components/parameters/Offset/example", ""),
+                ]
+            );
+            assert_eq!(
+                spec.validate_examples()[1].reference.as_deref(),
+                Some("/paths/~1pets/get/responses/200/content/application~1json/examples/stale")
+            );
+        }
+
+        #[test]
+        fn leaves_out_read_only_and_write_only_properties() {
+            let spec: OpenAPIV3 = document(json!({
+                "info": {"title": "Examples", "version": "1.0.0"},
+                "paths": {
+                    "/users": {
+                        "post": {
+                            "requestBody": {"content": {"application/json": {
+                                "schema": {"$ref": "#/components/schemas/User"},
+                                "examples": {
+                                    "new": {"value": {"name": "Tom", "password": "secret"}},
+                                    "anonymous": {"value": {"password": "secret"}}
+                                }
+                            }}},
+                            "responses": {
+                                "201": {
+                                    "description": "Created",
+                                    "content": {"application/json": {
+                                        "schema": {"$ref": "#/components/schemas/User"},
+                                        "examples": {
+                                            "created": {"value": {"id": 1, "name": "Tom"}},
+                                            "unnamed": {"value": {"name": "Tom"}}
+                                        }
+                                    }}
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "User": {
+                            "type": "object",
+                            "required": ["id", "name", "password"],
+                            "properties": {
+                                "id": {"type": "integer", "readOnly": true},
+                                "name": {"type": "string"},
+                                "password": {"type": "string", "writeOnly": true}
+                            }
+                        }
+                    }
+                }
+            }));
+
+            let errors = spec.validate_examples();
+            let errors = errors
+                .iter()
+                .map(|error| (error.pointer.as_str(), error.error.message.as_str()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                errors,
+                vec![
+                    (
+                        "/paths/~1users/post/requestBody/content/application~1json/examples/anonymous/value",
+                        "missing required property `name`"
+                    ),
+                    (
+                        "/paths/~1users/post/responses/201/content/application~1json/examples/unnamed/value",
+                        "missing required property `id`"
+                    ),
+                ]
+            );
+        }
+
+        #[test]
+        fn bundled_examples_are_valid() {
+            let spec: OpenAPIV3 =
+                serde_json::from_str(include_str!("../examples/v3.0/json/api-with-examples.json"))
+                    .unwrap();
+
+            assert_eq!(spec.validate_examples(), vec![]);
+        }
+    }
+
+    mod codegen {
+        use super::document;
+        use crate::{
+            Components, Model, OpenAPIV3, RustGenerator, Style, TypeKind, TypeRef,
+            TypeScriptGenerator,
+        };
+        use serde_json::json;
+
+        fn components() -> Components {
+            serde_json::from_value(json!({
+                "schemas": {
+                    "Cat": {
+                        "type": "object",
+                        "description": "A cat.",
+                        "required": ["petType", "name"],
+                        "properties": {
+                            "petType": {"type": "string"},
+                            "name": {"type": "string"},
+                            "born": {"type": "string", "format": "date-time"},
+                            "chip": {"type": "string", "format": "uuid", "nullable": true},
+                            "status": {"type": "string", "enum": ["available", "sold-out"]},
+                            "mother": {"$ref": "#/components/schemas/Cat"},
+                            "type": {"type": "integer", "format": "int32"}
+                        }
+                    },
+                    "Dog": {
+                        "type": "object",
+                        "required": ["petType"],
+                        "properties": {
+                            "petType": {"type": "string"},
+                            "owner": {
+                                "type": "object",
+                                "properties": {"homepage": {"type": "string", "format": "uri"}}
+                            }
+                        },
+                        "additionalProperties": {"type": "string"}
+                    },
+                    "Id": {"oneOf": [{"type": "string"}, {"type": "integer"}]},
+                    "Pet": {
+                        "oneOf": [
+                            {"$ref": "#/components/schemas/Cat"},
+                            {"$ref": "#/components/schemas/Dog"}
+                        ],
+                        "discriminator": {"propertyName": "petType", "mapping": {"dog": "#/components/schemas/Dog"}}
+                    }
+                }
+            }))
+            .unwrap()
+        }
+
+        #[test]
+        fn lowers_schemas_to_named_types() {
+            let model = Model::from_components(&components());
+
+            let names = model
+                .types
+                .iter()
+                .map(|ty| ty.name.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                names,
+                vec!["Cat", "CatStatus", "Dog", "DogOwner", "Id", "Pet", "PetCat"]
+            );
+            // `Cat` is also the type of `mother`, so `Pet` gets a copy of it without the tag
+            let has_tag = |name: &str| match &model.get(name).unwrap().kind {
+                TypeKind::Struct { fields, .. } => {
+                    fields.iter().any(|field| field.name == "petType")
+                }
+                kind => panic!("Should be a struct, got {kind:?}"),
+            };
+            assert!(has_tag("Cat"));
+            assert!(!has_tag("PetCat"));
+            assert!(!has_tag("Dog"));
+        }
+
+        #[test]
+        fn keeps_the_tag_of_variants_used_by_operations() {
+            let spec: OpenAPIV3 = document(json!({
+                "paths": {"/dog": {"get": {"responses": {"200": {
+                    "description": "ok",
+                    "content": {"application/json": {
+                        "schema": {"$ref": "#/components/schemas/Dog"},
+                    }},
+                }}}}},
+                "components": components(),
+            }));
+            let model = Model::new(&spec);
+
+            let has_tag = |name: &str| match &model.get(name).unwrap().kind {
+                TypeKind::Struct { fields, .. } => {
+                    fields.iter().any(|field| field.name == "petType")
+                }
+                kind => panic!("Should be a struct, got {kind:?}"),
+            };
+            assert!(has_tag("Dog"));
+            assert!(!has_tag("PetDog"));
+            match &model.component("Pet").unwrap().kind {
+                TypeKind::Union { variants, .. } => assert_eq!(
+                    variants
+                        .iter()
+                        .map(|variant| variant.ty.clone())
+                        .collect::<Vec<_>>(),
+                    [
+                        TypeRef::Named("PetCat".to_string()),
+                        TypeRef::Named("PetDog".to_string())
+                    ]
+                ),
+                kind => panic!("Should be a union, got {kind:?}"),
+            }
+        }
+
+        #[test]
+        fn generates_rust_types() {
+            let code = RustGenerator::default().types(&Model::from_components(&components()));
+
+            syn::parse_file(&code).unwrap();
+            for expected in [
+                "/// A cat.\n#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]\npub struct Cat {",
+                "    pub born: Option<chrono::DateTime<chrono::Utc>>,",
+                "    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub chip: Option<uuid::Uuid>,",
+                "    pub mother: Option<Box<Cat>>,",
+                "    pub name: String,",
+                "    #[serde(rename = \"type\", default, skip_serializing_if = \"Option::is_none\")]\n    pub _type: Option<i32>,",
+                "    #[serde(rename = \"sold-out\")]\n    SoldOut,",
+                "    #[serde(flatten)]\n    pub additional_properties: std::collections::BTreeMap<String, String>,",
+                "    pub homepage: Option<url::Url>,",
+                "#[serde(untagged)]\npub enum Id {\n    String(String),\n    Integer(i64),\n}",
+                "#[serde(tag = \"petType\")]\npub enum Pet {\n    #[serde(rename = \"Cat\")]\n    Cat(PetCat),\n    #[serde(rename = \"dog\")]\n    Dog(Dog),\n}",
+            ] {
+                assert!(code.contains(expected), "{expected}\nnot found in\n{code}");
+            }
+        }
+
+        #[test]
+        fn generates_a_rust_client() {
+            let spec: OpenAPIV3 =
+                serde_json::from_str(include_str!("../examples/v3.0/json/petstore.json")).unwrap();
+
+            let model = Model::new(&spec);
+            let types = RustGenerator::default().types(&model);
+            let code = RustGenerator::default().client(&model);
+
+            syn::parse_file(&code).unwrap();
+            assert!(types.contains("pub enum ListPetsResponse {\n    /// An paged array of pets\n    Ok(Pets),\n    /// unexpected error\n    Default(u16, Error),\n}"));
+            for expected in [
+                "pub const SERVERS: &'static [&'static str] = &[\"http://petstore.swagger.io/v1\"];",
+                "pub async fn list_pets(&self, limit: Option<i32>) -> Result<ListPetsResponse, ClientError> {",
+                "query.push(oas::Style::Form.serialize_query(\"limit\", &serde_json::to_value(&value)?, true, false));",
+                "let url = format!(\"{}/pets/{}\", self.base_url, oas::Style::Simple.serialize_path(\"petId\", &serde_json::to_value(&pet_id)?, false));",
+                "201 => Ok(CreatePetsResponse::Created),",
+            ] {
+                assert!(code.contains(expected), "{expected}\nnot found in\n{code}");
+            }
+        }
+
+        #[test]
+        fn encodes_form_bodies_with_their_encoding() {
+            let spec: OpenAPIV3 = document(json!({
+                "info": {"title": "Forms", "version": "1.0.0"},
+                "paths": {"/search": {"post": {
+                    "operationId": "search",
+                    "requestBody": {"required": true, "content": {"application/x-www-form-urlencoded": {
+                        "schema": {
+                            "type": "object",
+                            "properties": {
+                                "tags": {"type": "array", "items": {"type": "string"}},
+                                "filter": {"type": "object", "properties": {"age": {"type": "integer"}}},
+                                "query": {"type": "string"}
+                            }
+                        },
+                        "encoding": {
+                            "tags": {"style": "pipeDelimited", "explode": false},
+                            "filter": {"style": "deepObject", "allowReserved": true},
+                            "query": {"contentType": "text/plain"}
+                        }
+                    }}},
+                    "responses": {"204": {"description": "Found"}}
+                }}}
+            }));
+            let model = Model::new(&spec);
+
+            let code = RustGenerator::default().client(&model);
+            syn::parse_file(&code).unwrap();
+            for expected in [
+                "\"filter\" => oas::Style::DeepObject.serialize_query(name, value, false, true),",
+                "\"tags\" => oas::Style::PipeDelimited.serialize_query(name, value, false, false),",
+                "_ => oas::Style::Form.serialize_query(name, value, true, false),",
+            ] {
+                assert!(code.contains(expected), "{expected}\nnot found in\n{code}");
+            }
+
+            let code = TypeScriptGenerator::default().client(&model);
+            let expected = "Object.entries(body).map(([name, value]) => \
+                name === \"filter\" ? serializeQuery(\"deepObject\", name, value, false, true) : \
+                name === \"tags\" ? serializeQuery(\"pipeDelimited\", name, value, false, false) : \
+                serializeQuery(\"form\", name, value, true, false)).join(\"&\")";
+            assert!(code.contains(expected), "{expected}\nnot found in\n{code}");
+        }
+
+        fn upload() -> OpenAPIV3 {
+            document(json!({
+                "info": {"title": "Uploads", "version": "1.0.0"},
+                "paths": {"/pets/{petId}/photos": {"post": {
+                    "operationId": "uploadPhoto",
+                    "parameters": [{"name": "petId", "in": "path", "required": true, "schema": {"type": "integer"}}],
+                    "requestBody": {"required": true, "content": {"multipart/form-data": {"schema": {
+                        "type": "object",
+                        "required": ["photo"],
+                        "properties": {
+                            "photo": {"type": "string", "format": "binary"},
+                            "tags": {"type": "array", "items": {"type": "string"}}
+                        }
+                    }}}},
+                    "responses": {
+                        "201": {"description": "Uploaded", "content": {"multipart/form-data": {"schema": {"type": "object"}}}}
+                    }
+                }}}
+            }))
+        }
+
+        #[test]
+        fn generates_typed_multipart_bodies() {
+            let model = Model::new(&upload());
+
+            let types = RustGenerator::default().types(&model);
+            assert!(types.contains("pub struct UploadPhotoBody {"), "{types}");
+            let code = RustGenerator::default().client(&model);
+            syn::parse_file(&code).unwrap();
+            for expected in [
+                "pub async fn upload_photo(&self, pet_id: i64, body: &UploadPhotoBody) -> Result<UploadPhotoResponse, ClientError> {",
+                ".map(|(name, value)| (name, oas::FormValue::Value(value)))",
+                "let body = oas::MultipartBody::new(&fields);",
+                "201 => Ok(UploadPhotoResponse::Created(oas::EncodedBody { content_type, body: bytes.to_vec() })),",
+            ] {
+                assert!(code.contains(expected), "{expected}\nnot found in\n{code}");
+            }
+            let code = RustGenerator::default().server(&model);
+            assert!(code.contains("let body: UploadPhotoBody = parts.required_body()?;"));
+        }
+
+        #[test]
+        fn generated_code_builds() {
+            let petstore: OpenAPIV3 =
+                serde_json::from_str(include_str!("../examples/v3.0/json/petstore.json")).unwrap();
+            let directory = std::env::temp_dir().join("oas-codegen");
+            std::fs::create_dir_all(&directory).unwrap();
+            let tests = trybuild::TestCases::new();
+            for (name, spec) in [("petstore", petstore), ("upload", upload())] {
+                let model = Model::new(&spec);
+                let generator = RustGenerator::default();
+                let code = [
+                    generator.types(&model),
+                    generator.client(&model),
+                    generator.server(&model),
+                    "fn main() {}\n".to_string(),
+                ]
+                .join("\n");
+                let path = directory.join(format!("{name}.rs"));
+                std::fs::write(&path, code).unwrap();
+                tests.pass(path);
+            }
+        }
+
+        #[test]
+        fn generates_a_rust_server() {
+            let spec: OpenAPIV3 =
+                serde_json::from_str(include_str!("../examples/v3.0/json/petstore.json")).unwrap();
+            let generator = RustGenerator {
+                trait_per_tag: true,
+                ..RustGenerator::default()
+            };
+
+            let code = generator.server(&Model::new(&spec));
+
+            syn::parse_file(&code).unwrap();
+            for expected in [
+                "/// The operations tagged `pets`.\npub trait PetsApi {",
+                "fn show_pet_by_id(&self, pet_id: String) -> impl std::future::Future<Output = ShowPetByIdResponse> + Send;",
+                "impl<S: PetsApi + Send + Sync> Server<S> {",
+                "(\"/pets/{petId}\", oas::Method::Get) => self.show_pet_by_id(&parts, bytes).await,",
+                "let limit: Option<i32> = parts.parameter(oas::ParameterIn::Query, \"limit\")?;",
+                "ListPetsResponse::Default(status, body) => json_response(status, \"application/json\", &body),",
+                "CreatePetsResponse::Created => response(201, None, Vec::new()),",
+            ] {
+                assert!(code.contains(expected), "{expected}\nnot found in\n{code}");
+            }
+        }
+
+        #[test]
+        fn generates_typescript_types() {
+            let code = TypeScriptGenerator::default().types(&Model::from_components(&components()));
+
+            for expected in [
+                "/** A cat. */\nexport interface Cat {",
+                "  chip?: string | null;",
+                "  mother?: Cat;",
+                "export interface Dog {\n  owner?: DogOwner;\n  [key: string]: unknown;\n}",
+                "export type CatStatus = \"available\" | \"sold-out\";",
+                "export type Pet =\n  | ({ petType: \"Cat\" } & PetCat)\n  | ({ petType: \"dog\" } & Dog);",
+            ] {
+                assert!(code.contains(expected), "{expected}\nnot found in\n{code}");
+            }
+        }
+
+        #[test]
+        fn generates_a_typescript_client() {
+            let spec: OpenAPIV3 =
+                serde_json::from_str(include_str!("../examples/v3.0/json/petstore.json")).unwrap();
+            let generator = TypeScriptGenerator {
+                types_module: Some("./types".to_string()),
+            };
+            let model = Model::new(&spec);
+
+            let types = generator.types(&model);
+            let code = generator.client(&model);
+
+            assert!(types.contains("export type ListPetsResponse =\n  | { status: 200; body: Pets }\n  | { status: number; body: Error };"));
+            for expected in [
+                "import type { CreatePetsResponse, ListPetsResponse, ShowPetByIdResponse } from \"./types\";",
+                "async listPets(params: { limit?: number } = {}): Promise<ListPetsResponse> {",
+                "query.push(serializeQuery(\"form\", \"limit\", params.limit, true, false));",
+                "const path = `/pets/${serializePath(\"simple\", \"petId\", params.petId, false)}`;",
+                "if (status === 201) {\n      return { status: 201 };\n    }",
+            ] {
+                assert!(code.contains(expected), "{expected}\nnot found in\n{code}");
+            }
+        }
+
+        #[test]
+        fn serializes_path_parameters() {
+            let value = json!({"name": "Alex", "role": "admin"});
+
+            assert_eq!(
+                Style::Simple.serialize_path("id", &json!([3, 4]), false),
+                "3,4"
+            );
+            assert_eq!(
+                Style::Label.serialize_path("id", &json!([3, 4]), true),
+                ".3.4"
+            );
+            assert_eq!(
+                Style::Matrix.serialize_path("id", &json!([3, 4]), true),
+                ";id=3;id=4"
+            );
+            assert_eq!(
+                Style::Matrix.serialize_path("id", &value, false),
+                ";id=name,Alex,role,admin"
+            );
+            assert_eq!(
+                Style::Simple.serialize_header("id", &value, true),
+                "name=Alex,role=admin"
+            );
+            assert_eq!(
+                Style::Simple.serialize_path("id", &json!("a b"), false),
+                "a%20b"
+            );
+        }
+    }
 }
@@ -1,10 +1,8 @@
-use std::collections::BTreeMap;
-
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use url::Url;
 
-use crate::{Any, Parseable};
+use crate::{Any, Map, Parseable};
 
 /// License information for the exposed API.
 #[skip_serializing_none]
@@ -15,5 +13,5 @@ pub struct License {
     /// A URL to the license used for the API. MUST be in the format of a URL.
     pub url: Option<Parseable<Url>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
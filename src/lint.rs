@@ -0,0 +1,392 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pointer;
+use crate::{Method, OpenAPIV3, Severity};
+
+/// A problem found by a [LintRule].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// The JSON Pointer to the offending value in the document, e.g. `/paths/~1pets/get`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(pointer: impl Into<String>, message: impl Into<String>) -> Violation {
+        Violation {
+            pointer: pointer.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// A check of a document beyond its validity, run by a [Linter].
+pub trait LintRule {
+    /// The name of the rule in configurations, e.g. `operation-id`.
+    fn name(&self) -> &str;
+
+    /// The severity of the rule unless configured otherwise.
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, spec: &OpenAPIV3) -> Vec<Violation>;
+}
+
+/// The severities of the rules, e.g. `{"severities": {"servers-https": "error"}}`. Rules which
+/// are not listed keep their [default severity](LintRule::default_severity), and rules set to
+/// [Severity::Off] are not run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LintConfig {
+    #[serde(default)]
+    pub severities: BTreeMap<String, Severity>,
+}
+
+impl LintConfig {
+    pub fn severity(&self, rule: &dyn LintRule) -> Severity {
+        self.severities
+            .get(rule.name())
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+/// A violation of a rule, with the configured severity of the rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintIssue {
+    pub rule: String,
+    pub severity: Severity,
+    #[serde(flatten)]
+    pub violation: Violation,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {}: {}",
+            self.severity, self.rule, self.violation.pointer, self.violation.message
+        )
+    }
+}
+
+/// The issues found by a [Linter], by decreasing severity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintReport {
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.issues.iter().map(|issue| issue.severity).max()
+    }
+
+    /// Whether an issue has at least the given severity, to fail a CI job.
+    pub fn fails(&self, threshold: Severity) -> bool {
+        self.max_severity()
+            .is_some_and(|severity| severity >= threshold)
+    }
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs rules against documents.
+pub struct Linter {
+    rules: Vec<Box<dyn LintRule>>,
+    config: LintConfig,
+}
+
+impl Linter {
+    /// A linter with the built-in rules:
+    ///
+    /// - `operation-id`: every operation has an `operationId`.
+    /// - `operation-id-unique`: operation ids are unique (error).
+    /// - `operation-id-camel-case`: operation ids are in camelCase.
+    /// - `operation-description`: every operation has a summary or a description.
+    /// - `operation-tags-declared`: the tags of the operations are declared in `tags`.
+    /// - `no-unused-components`: every component is used, see [OpenAPIV3::unused_components].
+    /// - `servers-https`: absolute server URLs use `https`, except for `localhost`.
+    /// - `valid-examples`: examples conform to their schema, see
+    ///   [OpenAPIV3::validate_examples] (error).
+    pub fn new(config: LintConfig) -> Linter {
+        Linter {
+            rules: vec![
+                Box::new(OperationId),
+                Box::new(OperationIdUnique),
+                Box::new(OperationIdCamelCase),
+                Box::new(OperationDescription),
+                Box::new(OperationTagsDeclared),
+                Box::new(NoUnusedComponents),
+                Box::new(ServersHttps),
+                Box::new(ValidExamples),
+            ],
+            config,
+        }
+    }
+
+    /// A linter without rules.
+    pub fn empty(config: LintConfig) -> Linter {
+        Linter {
+            rules: vec![],
+            config,
+        }
+    }
+
+    /// Adds a rule, replacing the rule with the same name if any.
+    pub fn with_rule(mut self, rule: impl LintRule + 'static) -> Linter {
+        self.rules.retain(|existing| existing.name() != rule.name());
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn lint(&self, spec: &OpenAPIV3) -> LintReport {
+        let mut issues = vec![];
+        for rule in &self.rules {
+            let severity = self.config.severity(rule.as_ref());
+            if severity == Severity::Off {
+                continue;
+            }
+            issues.extend(rule.check(spec).into_iter().map(|violation| LintIssue {
+                rule: rule.name().to_string(),
+                severity,
+                violation,
+            }));
+        }
+        // stable, so that the issues of a rule stay in document order
+        issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+        LintReport { issues }
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Linter::new(LintConfig::default())
+    }
+}
+
+impl OpenAPIV3 {
+    /// Checks the document with the built-in rules of [Linter::new].
+    pub fn lint(&self, config: &LintConfig) -> LintReport {
+        Linter::new(config.clone()).lint(self)
+    }
+}
+
+/// The pointer to an operation, e.g. `/paths/~1pets/get`.
+fn operation_pointer(path: &str, method: Method) -> String {
+    pointer::join(&pointer::join("/paths", path), method.as_str())
+}
+
+struct OperationId;
+
+impl LintRule for OperationId {
+    fn name(&self) -> &str {
+        "operation-id"
+    }
+
+    fn check(&self, spec: &OpenAPIV3) -> Vec<Violation> {
+        spec.operations()
+            .filter(|(_, _, operation)| operation.operation_id.is_none())
+            .map(|(path, method, _)| {
+                Violation::new(
+                    operation_pointer(path, method),
+                    format!("operation `{method} {path}` has no operationId"),
+                )
+            })
+            .collect()
+    }
+}
+
+struct OperationIdUnique;
+
+impl LintRule for OperationIdUnique {
+    fn name(&self) -> &str {
+        "operation-id-unique"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, spec: &OpenAPIV3) -> Vec<Violation> {
+        let mut seen = BTreeSet::new();
+        spec.operations()
+            .filter_map(|(path, method, operation)| {
+                let id = operation.operation_id.as_ref()?;
+                (!seen.insert(id)).then(|| {
+                    Violation::new(
+                        pointer::join(&operation_pointer(path, method), "operationId"),
+                        format!("operationId `{id}` is already used by another operation"),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+struct OperationIdCamelCase;
+
+impl LintRule for OperationIdCamelCase {
+    fn name(&self) -> &str {
+        "operation-id-camel-case"
+    }
+
+    fn check(&self, spec: &OpenAPIV3) -> Vec<Violation> {
+        spec.operations()
+            .filter_map(|(path, method, operation)| {
+                let id = operation.operation_id.as_ref()?;
+                let camel_case = id.starts_with(|c: char| c.is_ascii_lowercase())
+                    && id.chars().all(|c| c.is_ascii_alphanumeric());
+                (!camel_case).then(|| {
+                    Violation::new(
+                        pointer::join(&operation_pointer(path, method), "operationId"),
+                        format!("operationId `{id}` is not in camelCase"),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+struct OperationDescription;
+
+impl LintRule for OperationDescription {
+    fn name(&self) -> &str {
+        "operation-description"
+    }
+
+    fn check(&self, spec: &OpenAPIV3) -> Vec<Violation> {
+        let blank =
+            |text: &Option<String>| text.as_deref().is_none_or(|text| text.trim().is_empty());
+        spec.operations()
+            .filter(|(_, _, operation)| blank(&operation.summary) && blank(&operation.description))
+            .map(|(path, method, _)| {
+                Violation::new(
+                    operation_pointer(path, method),
+                    format!("operation `{method} {path}` has no summary nor description"),
+                )
+            })
+            .collect()
+    }
+}
+
+struct OperationTagsDeclared;
+
+impl LintRule for OperationTagsDeclared {
+    fn name(&self) -> &str {
+        "operation-tags-declared"
+    }
+
+    fn check(&self, spec: &OpenAPIV3) -> Vec<Violation> {
+        let declared = spec
+            .tags
+            .iter()
+            .flatten()
+            .map(|tag| tag.name.as_str())
+            .collect::<BTreeSet<_>>();
+        let mut violations = vec![];
+        for (path, method, operation) in spec.operations() {
+            let pointer = pointer::join(&operation_pointer(path, method), "tags");
+            for (index, tag) in operation.tags.iter().flatten().enumerate() {
+                if !declared.contains(tag.as_str()) {
+                    violations.push(Violation::new(
+                        pointer::join(&pointer, index.to_string()),
+                        format!("tag `{tag}` is not declared in the tags of the document"),
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+struct NoUnusedComponents;
+
+impl LintRule for NoUnusedComponents {
+    fn name(&self) -> &str {
+        "no-unused-components"
+    }
+
+    fn check(&self, spec: &OpenAPIV3) -> Vec<Violation> {
+        spec.unused_components()
+            .into_iter()
+            .map(|unused| {
+                let kind = pointer::join("/components", &unused.kind);
+                Violation::new(
+                    pointer::join(&kind, &unused.name),
+                    format!("component `{}` is never used", unused.name),
+                )
+            })
+            .collect()
+    }
+}
+
+struct ServersHttps;
+
+impl LintRule for ServersHttps {
+    fn name(&self) -> &str {
+        "servers-https"
+    }
+
+    fn check(&self, spec: &OpenAPIV3) -> Vec<Violation> {
+        let mut servers = vec![("/servers".to_string(), &spec.servers)];
+        for (path, item) in &spec.paths {
+            let pointer = pointer::join("/paths", path);
+            servers.push((pointer::join(&pointer, "servers"), &item.servers));
+            for (method, operation) in item.operations() {
+                let pointer = pointer::join(&pointer, method.as_str());
+                servers.push((pointer::join(&pointer, "servers"), &operation.servers));
+            }
+        }
+        let mut violations = vec![];
+        for (pointer, servers) in servers {
+            for (index, server) in servers.iter().flatten().enumerate() {
+                let url = server.url_template();
+                let local = ["http://localhost", "http://127.0.0.1"]
+                    .iter()
+                    .any(|prefix| url.starts_with(prefix));
+                if url.starts_with("http://") && !local {
+                    violations.push(Violation::new(
+                        pointer::join(&pointer::join(&pointer, index.to_string()), "url"),
+                        format!("server `{url}` does not use https"),
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+struct ValidExamples;
+
+impl LintRule for ValidExamples {
+    fn name(&self) -> &str {
+        "valid-examples"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, spec: &OpenAPIV3) -> Vec<Violation> {
+        spec.validate_examples()
+            .into_iter()
+            .map(|error| {
+                let message = match &error.reference {
+                    Some(reference) => format!("{} (referenced from {reference})", error.error),
+                    None => error.error.to_string(),
+                };
+                Violation::new(error.pointer, message)
+            })
+            .collect()
+    }
+}
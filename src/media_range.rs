@@ -0,0 +1,309 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use mediatype::{MediaTypeBuf, MediaTypeError, MediaTypeList, Name, ReadParams};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Map;
+use crate::MediaType;
+use crate::Parseable;
+
+/// The key of a `content` map: a media type such as `application/json`, or a media type range
+/// using wildcards such as `image/*` or `*/*`. When more than one key matches a given media type,
+/// the most specific one is applicable.
+///
+/// Keys compare by their exact text so that a document keeps its keys when it is re-serialized.
+/// A document whose keys are not valid media types still deserializes: such keys are kept as
+/// [Parseable::Invalid] and never match any media type.
+#[derive(Debug, Clone)]
+pub struct MediaRange(Parseable<MediaTypeBuf>);
+
+impl MediaRange {
+    /// The parsed media type, which may contain `*` as type or subtype, or `None` when the key
+    /// is not a valid media type.
+    pub fn media_type(&self) -> Option<&MediaTypeBuf> {
+        match &self.0 {
+            Parseable::Valid(media_type) => Some(media_type),
+            Parseable::Invalid(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Parseable::Valid(media_type) => media_type.as_str(),
+            Parseable::Invalid(value) => value,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.0.is_valid()
+    }
+
+    /// Whether the type or the subtype is the `*` wildcard.
+    pub fn is_wildcard(&self) -> bool {
+        self.media_type()
+            .is_some_and(|media_type| media_type.ty() == "*" || media_type.subty() == "*")
+    }
+
+    /// How specific this range is: `*/*` is the least and a media type with parameters the most
+    /// specific. Used to pick between several matching keys.
+    pub fn specificity(&self) -> u8 {
+        self.media_type().map_or(0, specificity)
+    }
+
+    /// Whether the given media type falls within this range. Parameters of the range must be
+    /// present in the media type with the same value, while extra parameters are ignored.
+    pub fn matches(&self, media_type: &MediaTypeBuf) -> bool {
+        self.media_type()
+            .is_some_and(|range| covers(range, media_type))
+    }
+}
+
+impl PartialEq for MediaRange {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for MediaRange {}
+
+impl Ord for MediaRange {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl PartialOrd for MediaRange {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for MediaRange {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl fmt::Display for MediaRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The error returned when a `content` key is not a valid media type or media type range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidMediaRange {
+    pub value: String,
+    pub error: Option<MediaTypeError>,
+}
+
+impl fmt::Display for InvalidMediaRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Some(error) => write!(f, "invalid media type `{}`: {error}", self.value),
+            None => write!(f, "invalid media type `{}`", self.value),
+        }
+    }
+}
+
+impl std::error::Error for InvalidMediaRange {}
+
+impl FromStr for MediaRange {
+    type Err = InvalidMediaRange;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |error| InvalidMediaRange {
+            value: s.to_string(),
+            error,
+        };
+        let media_type = MediaTypeBuf::from_str(s).map_err(|error| invalid(Some(error)))?;
+        // the parser stops at the first character it does not understand, reject the leftovers
+        if media_type.as_str().len() != s.len() {
+            return Err(invalid(None));
+        }
+        Ok(MediaRange(Parseable::Valid(media_type)))
+    }
+}
+
+impl Serialize for MediaRange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for MediaRange {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(value
+            .parse()
+            .unwrap_or(MediaRange(Parseable::Invalid(value))))
+    }
+}
+
+/// A comma-separated list of media types or media type ranges, as used by
+/// [Encoding::content_type](crate::Encoding::content_type), e.g. `image/png, image/jpeg`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MediaRangeList(pub Vec<MediaRange>);
+
+impl MediaRangeList {
+    /// The first entry which is not a wildcard range, i.e. a media type usable in a
+    /// `Content-Type` header.
+    pub fn preferred(&self) -> Option<&MediaRange> {
+        self.0.iter().find(|range| !range.is_wildcard())
+    }
+}
+
+impl fmt::Display for MediaRangeList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, range) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(range.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MediaRangeList {
+    type Err = InvalidMediaRange;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ranges = MediaTypeList::new(s)
+            .map(|range| {
+                range
+                    .map(|range| MediaRange(Parseable::Valid(range.into())))
+                    .map_err(|error| InvalidMediaRange {
+                        value: s.to_string(),
+                        error: Some(error),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if ranges.is_empty() {
+            return Err(InvalidMediaRange {
+                value: s.to_string(),
+                error: None,
+            });
+        }
+        Ok(MediaRangeList(ranges))
+    }
+}
+
+impl Serialize for MediaRangeList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MediaRangeList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Selects the applicable [MediaType] of a `content` map for a request or a response.
+pub trait ContentNegotiation {
+    /// Picks the entry that best satisfies an `Accept` header. Each entry is weighted by the `q`
+    /// value of the most specific range of the header matching it; ties are broken by the
+    /// specificity of that range, then the specificity of the key, then the order of the header.
+    /// Entries matched only by ranges with `q=0` are never selected. An empty header accepts
+    /// anything.
+    fn negotiate(&self, accept: &str) -> Option<(&MediaRange, &MediaType)>;
+
+    /// Picks the most specific entry whose key matches the `Content-Type` of a payload.
+    fn for_content_type(&self, content_type: &str) -> Option<(&MediaRange, &MediaType)>;
+}
+
+impl ContentNegotiation for Map<MediaRange, MediaType> {
+    fn negotiate(&self, accept: &str) -> Option<(&MediaRange, &MediaType)> {
+        let mut ranges = MediaTypeList::new(accept)
+            .filter_map(Result::ok)
+            .map(MediaTypeBuf::from)
+            .collect::<Vec<_>>();
+        if ranges.is_empty() {
+            ranges.push(MediaTypeBuf::from_str("*/*").expect("`*/*` should be valid"));
+        }
+
+        let mut best = None;
+        for (key, media_type) in self {
+            let Some(key_type) = key.media_type() else {
+                continue;
+            };
+            let accepted = ranges
+                .iter()
+                .enumerate()
+                .filter(|(_, range)| compatible(range, key_type))
+                .max_by(|(a_index, a), (b_index, b)| {
+                    specificity(a)
+                        .cmp(&specificity(b))
+                        .then(b_index.cmp(a_index))
+                });
+            let Some((index, range)) = accepted else {
+                continue;
+            };
+            let q = quality(range);
+            if q == 0 {
+                continue;
+            }
+            let score = (q, specificity(range), key.specificity(), usize::MAX - index);
+            if best.as_ref().is_none_or(|(best, _)| score > *best) {
+                best = Some((score, (key, media_type)));
+            }
+        }
+        best.map(|(_, entry)| entry)
+    }
+
+    fn for_content_type(&self, content_type: &str) -> Option<(&MediaRange, &MediaType)> {
+        let content_type = MediaTypeBuf::from_str(content_type.trim()).ok()?;
+        self.iter()
+            .filter(|(key, _)| key.matches(&content_type))
+            .max_by_key(|(key, _)| key.specificity())
+    }
+}
+
+fn specificity(media_type: &MediaTypeBuf) -> u8 {
+    if media_type.ty() == "*" {
+        0
+    } else if media_type.subty() == "*" {
+        1
+    } else if media_type.params().any(|(name, _)| name != "q") {
+        3
+    } else {
+        2
+    }
+}
+
+/// Whether `range` covers `media_type`, treating wildcards in `range` only.
+fn covers(range: &MediaTypeBuf, media_type: &MediaTypeBuf) -> bool {
+    let type_matches = range.ty() == "*" || range.ty() == media_type.ty();
+    let subtype_matches = range.subty() == "*"
+        || (range.subty() == media_type.subty() && range.suffix() == media_type.suffix());
+    type_matches
+        && subtype_matches
+        && range
+            .params()
+            .filter(|(name, _)| *name != "q")
+            .all(|(name, value)| {
+                media_type
+                    .get_param(name)
+                    .is_some_and(|other| other.unquoted_str() == value.unquoted_str())
+            })
+}
+
+/// Whether two ranges overlap, e.g. an `Accept` of `image/png` and a key of `image/*`.
+fn compatible(accept: &MediaTypeBuf, key: &MediaTypeBuf) -> bool {
+    covers(accept, key) || covers(key, accept)
+}
+
+/// The `q` parameter of an `Accept` range in thousandths, defaulting to `1`.
+fn quality(range: &MediaTypeBuf) -> u16 {
+    range
+        .get_param(Name::new_unchecked("q"))
+        .and_then(|q| q.unquoted_str().parse::<f32>().ok())
+        .filter(|q| (0.0..=1.0).contains(q))
+        .map_or(1000, |q| (q * 1000.0).round() as u16)
+}
@@ -0,0 +1,435 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::codegen::pascal_case;
+use crate::diff::normalize;
+use crate::pointer;
+use crate::{Method, OpenAPIV3, PathItem, Server};
+
+/// What to do with the `servers` of a merged document.
+#[derive(Debug, Clone, Default)]
+pub enum ServerRewrite {
+    /// Moves the servers of the merged document to its path items, unless they are the same as
+    /// the ones of the target document, so that its operations keep pointing to their service.
+    #[default]
+    Keep,
+    /// Drops the servers of the merged document, e.g. when a gateway serves its paths.
+    Drop,
+    /// Sets the servers of the path items of the merged document.
+    Replace(Vec<Server>),
+}
+
+/// How to merge a document into another one with [OpenAPIV3::merge].
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    /// A prefix for the paths of the merged document, e.g. `/billing`.
+    pub path_prefix: Option<String>,
+    /// The prefix of the components of the merged document which are renamed because another
+    /// component has the same name, e.g. `Billing` to rename `Invoice` to `BillingInvoice`.
+    /// Defaults to the title of the merged document in PascalCase.
+    pub component_prefix: Option<String>,
+    pub servers: ServerRewrite,
+}
+
+/// A conflict between two merged documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeConflict {
+    /// Both documents define the operation. The one of the target document is kept.
+    Operation { path: String, method: Method },
+    /// Both documents define a path with different summaries, descriptions, servers or
+    /// parameters. The ones of the target document are kept.
+    PathItem { path: String },
+    /// Both documents define the same path with differently named parameters, e.g. `/pets/{id}`
+    /// and `/pets/{petId}`. The path item of the target document is kept and the one of the
+    /// merged document is dropped.
+    PathTemplate { path: String, existing: String },
+    /// Both documents define a component with the same name but different definitions, so the
+    /// one of the merged document is renamed and the references to it are rewritten.
+    RenamedComponent {
+        kind: String,
+        name: String,
+        renamed: String,
+    },
+    /// Both documents define a tag with different descriptions. The one of the target document
+    /// is kept.
+    Tag { name: String },
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeConflict::Operation { path, method } => {
+                write!(f, "operation `{method} {path}` is defined twice")
+            }
+            MergeConflict::PathItem { path } => {
+                write!(f, "path `{path}` is defined twice with different fields")
+            }
+            MergeConflict::PathTemplate { path, existing } => {
+                write!(f, "path `{path}` is already defined as `{existing}`")
+            }
+            MergeConflict::RenamedComponent {
+                kind,
+                name,
+                renamed,
+            } => write!(
+                f,
+                "component `{kind}/{name}` is defined twice, renamed to `{renamed}`"
+            ),
+            MergeConflict::Tag { name } => {
+                write!(
+                    f,
+                    "tag `{name}` is defined twice with different descriptions"
+                )
+            }
+        }
+    }
+}
+
+impl OpenAPIV3 {
+    /// Merges another document into this one: its paths, components, tags and security
+    /// requirements are added to the ones of this document.
+    ///
+    /// Conflicts are reported rather than overwritten: the definitions of this document are kept,
+    /// except for components with the same name and different definitions, which are renamed in
+    /// the merged document along with the references to them. Identical components are shared.
+    /// Paths are matched by their template, regardless of the names of their parameters.
+    /// The top-level security requirements of the other document are moved to its operations
+    /// when they differ from the ones of this document.
+    pub fn merge(&mut self, other: OpenAPIV3, options: &MergeOptions) -> Vec<MergeConflict> {
+        let mut conflicts = vec![];
+        let mut other = other.to_value();
+        let ours = self.to_value();
+
+        // keep the meaning of the servers and security requirements of the other document
+        if other.get("security") != ours.get("security") {
+            let security = other
+                .get("security")
+                .cloned()
+                .unwrap_or(Value::Array(vec![]));
+            for operation in operations_mut(&mut other) {
+                if operation.get("security").is_none() {
+                    operation["security"] = security.clone();
+                }
+            }
+        }
+        let servers = match &options.servers {
+            ServerRewrite::Keep => other
+                .get("servers")
+                .filter(|servers| Some(*servers) != ours.get("servers"))
+                .cloned(),
+            ServerRewrite::Drop => None,
+            ServerRewrite::Replace(servers) => {
+                Some(serde_json::to_value(servers).expect("the servers can be serialized"))
+            }
+        };
+        if let Some(servers) = servers {
+            for item in other["paths"]
+                .as_object_mut()
+                .into_iter()
+                .flat_map(Map::values_mut)
+            {
+                let replace = matches!(options.servers, ServerRewrite::Replace(_));
+                if replace || item.get("servers").is_none() {
+                    item["servers"] = servers.clone();
+                }
+            }
+        }
+
+        let renames = renames(&ours, &other, options, &mut conflicts);
+        rewrite(&mut other, &renames);
+        let other: OpenAPIV3 =
+            serde_json::from_value(other).expect("the rewritten document is valid");
+
+        self.merge_paths(other.paths, options, &mut conflicts);
+        self.merge_components(other.components);
+
+        for tag in other.tags.into_iter().flatten() {
+            let tags = self.tags.get_or_insert_with(Vec::new);
+            match tags.iter_mut().find(|existing| existing.name == tag.name) {
+                Some(existing) => match (&existing.description, &tag.description) {
+                    (None, Some(_)) => *existing = tag,
+                    (Some(ours), Some(theirs)) if ours != theirs => {
+                        conflicts.push(MergeConflict::Tag { name: tag.name })
+                    }
+                    _ => {}
+                },
+                None => tags.push(tag),
+            }
+        }
+        conflicts
+    }
+
+    fn merge_paths(
+        &mut self,
+        paths: crate::Map<String, PathItem>,
+        options: &MergeOptions,
+        conflicts: &mut Vec<MergeConflict>,
+    ) {
+        for (path, item) in paths {
+            let path = match &options.path_prefix {
+                Some(prefix) => format!("{}{path}", prefix.trim_end_matches('/')),
+                None => path,
+            };
+            let template = normalize(&path);
+            let Some((existing_path, existing)) = self
+                .paths
+                .iter_mut()
+                .find(|(existing, _)| normalize(existing) == template)
+            else {
+                self.paths.insert(path, item);
+                continue;
+            };
+            if *existing_path != path {
+                conflicts.push(MergeConflict::PathTemplate {
+                    path,
+                    existing: existing_path.clone(),
+                });
+                continue;
+            }
+            let fields = |item: &PathItem| {
+                let mut fields = item.to_value();
+                if let Some(fields) = fields.as_object_mut() {
+                    fields.retain(|key, _| key.parse::<Method>().is_err());
+                }
+                fields
+            };
+            if fields(existing) != fields(&item) {
+                conflicts.push(MergeConflict::PathItem { path: path.clone() });
+            }
+            for (method, operation) in item.operations() {
+                let slot = existing.operation_mut(method);
+                match slot {
+                    Some(_) => conflicts.push(MergeConflict::Operation {
+                        path: path.clone(),
+                        method,
+                    }),
+                    None => *slot = Some(operation.clone()),
+                }
+            }
+        }
+    }
+
+    fn merge_components(&mut self, components: Option<crate::Components>) {
+        let Some(components) = components else {
+            return;
+        };
+        let mut ours = self
+            .components
+            .take()
+            .map(|components| components.to_value())
+            .unwrap_or_else(|| Value::Object(Map::new()));
+        for (kind, entries) in component_kinds(&components.to_value()) {
+            let target = ours
+                .as_object_mut()
+                .expect("components are objects")
+                .entry(kind)
+                .or_insert_with(|| Value::Object(Map::new()));
+            for (name, value) in entries {
+                // identical components are shared, and the others were renamed
+                target
+                    .as_object_mut()
+                    .expect("components are objects")
+                    .entry(name)
+                    .or_insert(value);
+            }
+        }
+        self.components =
+            Some(serde_json::from_value(ours).expect("the merged components are valid"));
+    }
+}
+
+/// The operations of the paths of a serialized document.
+fn operations_mut(spec: &mut Value) -> impl Iterator<Item = &mut Value> {
+    spec["paths"]
+        .as_object_mut()
+        .into_iter()
+        .flat_map(Map::values_mut)
+        .filter_map(Value::as_object_mut)
+        .flat_map(|item| {
+            item.iter_mut()
+                .filter(|(method, _)| method.parse::<Method>().is_ok())
+                .map(|(_, operation)| operation)
+        })
+}
+
+/// The components of each kind, e.g. `schemas`, of serialized components.
+fn component_kinds(components: &Value) -> Vec<(String, Map<String, Value>)> {
+    components
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(kind, _)| !kind.starts_with("x-"))
+        .filter_map(|(kind, entries)| Some((kind.clone(), entries.as_object()?.clone())))
+        .collect()
+}
+
+/// The new names of the components of `other` which collide with different components of
+/// `ours`, by kind and name. Renaming a component changes the components referencing it, so
+/// this is repeated until no more components need to be renamed.
+fn renames(
+    ours: &Value,
+    other: &Value,
+    options: &MergeOptions,
+    conflicts: &mut Vec<MergeConflict>,
+) -> BTreeMap<(String, String), String> {
+    let prefix = match &options.component_prefix {
+        Some(prefix) => prefix.clone(),
+        None => pascal_case(other["info"]["title"].as_str().unwrap_or("Merged")),
+    };
+    let ours = component_kinds(ours.get("components").unwrap_or(&Value::Null));
+    let mut renames = BTreeMap::new();
+    loop {
+        let mut rewritten = other.get("components").cloned().unwrap_or(Value::Null);
+        rewrite(&mut rewritten, &renames);
+        let mut renamed = false;
+        for (kind, entries) in component_kinds(&rewritten) {
+            let Some((_, existing)) = ours.iter().find(|(ours, _)| *ours == kind) else {
+                continue;
+            };
+            for (name, value) in entries {
+                if renames.contains_key(&(kind.clone(), name.clone())) {
+                    continue;
+                }
+                if existing
+                    .get(&name)
+                    .is_some_and(|existing| *existing != value)
+                {
+                    let taken = |candidate: &str| {
+                        existing.contains_key(candidate)
+                            || other["components"][&kind].get(candidate).is_some()
+                            || renames
+                                .iter()
+                                .any(|((k, _), renamed)| *k == kind && renamed == candidate)
+                    };
+                    let mut new_name = format!("{prefix}{name}");
+                    let mut index = 2;
+                    while taken(&new_name) {
+                        new_name = format!("{prefix}{name}{index}");
+                        index += 1;
+                    }
+                    conflicts.push(MergeConflict::RenamedComponent {
+                        kind: kind.clone(),
+                        name: name.clone(),
+                        renamed: new_name.clone(),
+                    });
+                    renames.insert((kind.clone(), name), new_name);
+                    renamed = true;
+                }
+            }
+        }
+        if !renamed {
+            return renames;
+        }
+    }
+}
+
+/// Renames components in a serialized document or part of it: their keys in `components`,
+/// the references to them, the names of schemas in discriminator mappings and the names of
+/// security schemes in security requirements.
+fn rewrite(value: &mut Value, renames: &BTreeMap<(String, String), String>) {
+    if renames.is_empty() {
+        return;
+    }
+    let references = renames
+        .iter()
+        .map(|((kind, name), renamed)| {
+            let prefix = format!("#/components/{kind}");
+            (
+                pointer::join(&prefix, name),
+                pointer::join(&prefix, renamed),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+    let schemes = renames
+        .iter()
+        .filter(|((kind, _), _)| kind == "securitySchemes")
+        .map(|((_, name), renamed)| (name.as_str(), renamed.as_str()))
+        .collect::<BTreeMap<_, _>>();
+    let schemas = renames
+        .iter()
+        .filter(|((kind, _), _)| kind == "schemas")
+        .map(|((_, name), renamed)| (name.as_str(), renamed.as_str()))
+        .collect::<BTreeMap<_, _>>();
+
+    if let Some(components) = value.get_mut("components").and_then(Value::as_object_mut) {
+        for ((kind, name), renamed) in renames {
+            if let Some(entries) = components.get_mut(kind).and_then(Value::as_object_mut) {
+                if let Some(component) = entries.remove(name) {
+                    entries.insert(renamed.clone(), component);
+                }
+            }
+        }
+    }
+    // the components of a document, or the kinds of components when rewriting them alone
+    for kind in renames
+        .keys()
+        .map(|(kind, _)| kind)
+        .collect::<BTreeSet<_>>()
+    {
+        if let Some(entries) = value.get_mut(kind).and_then(Value::as_object_mut) {
+            for ((renamed_kind, name), renamed) in renames {
+                if renamed_kind == kind {
+                    if let Some(component) = entries.remove(name) {
+                        entries.insert(renamed.clone(), component);
+                    }
+                }
+            }
+        }
+    }
+
+    fn walk(
+        value: &mut Value,
+        key: Option<&str>,
+        references: &BTreeMap<String, String>,
+        schemes: &BTreeMap<&str, &str>,
+        schemas: &BTreeMap<&str, &str>,
+    ) {
+        match value {
+            Value::Object(object) => {
+                if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                    if let Some(renamed) = references.get(reference.as_str()) {
+                        *reference = renamed.clone();
+                    }
+                }
+                if key == Some("mapping") {
+                    for target in object.values_mut() {
+                        if let Value::String(target) = target {
+                            match references.get(target.as_str()) {
+                                Some(renamed) => *target = renamed.clone(),
+                                None => {
+                                    if let Some(renamed) = schemas.get(target.as_str()) {
+                                        *target = renamed.to_string();
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                for (key, value) in object.iter_mut() {
+                    walk(value, Some(key), references, schemes, schemas);
+                }
+            }
+            Value::Array(array) if key == Some("security") && !schemes.is_empty() => {
+                for requirement in array.iter_mut().filter_map(Value::as_object_mut) {
+                    let renamed = std::mem::take(requirement)
+                        .into_iter()
+                        .map(|(name, scopes)| match schemes.get(name.as_str()) {
+                            Some(renamed) => (renamed.to_string(), scopes),
+                            None => (name, scopes),
+                        })
+                        .collect();
+                    *requirement = renamed;
+                }
+            }
+            Value::Array(array) => {
+                for value in array {
+                    walk(value, None, references, schemes, schemas);
+                }
+            }
+            _ => {}
+        }
+    }
+    walk(value, None, &references, &schemes, &schemas);
+}
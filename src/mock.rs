@@ -0,0 +1,653 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use mediatype::MediaTypeBuf;
+use serde_json::{json, Value};
+
+use crate::style::primitive_to_string;
+use crate::{
+    Components, ContentNegotiation, Header, MediaRange, MediaType, Method, OpenAPIV3, Operation,
+    Referenceable, Response, RouteError, RouteMatch, Router, Sampler, StatusCode,
+};
+
+/// An HTTP request handled by a [Mock].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockRequest {
+    pub method: String,
+    /// The path of the request, including the query string.
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    pub fn new(method: impl Into<String>, target: impl Into<String>) -> MockRequest {
+        MockRequest {
+            method: method.into(),
+            target: target.into(),
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    /// Adds a header to the request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the body of the request.
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// The value of a header, looked up case insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        header(&self.headers, name)
+    }
+}
+
+/// An HTTP response produced by a [Mock].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// The value of a header, looked up case insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        header(&self.headers, name)
+    }
+
+    /// The body parsed as JSON, if it is JSON.
+    pub fn json(&self) -> Option<Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+
+    fn json_error(status: u16, error: Value) -> MockResponse {
+        MockResponse {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: error.to_string().into_bytes(),
+        }
+    }
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Answers requests from the operations of a document.
+///
+/// A request is routed to an operation using `paths`, and its parameters and body are validated
+/// against the operation, which results in a `400` listing the errors. The response is the one
+/// for the first success status code of the operation, its media type is negotiated with the
+/// `Accept` header, and its body is the `example`, the first of the `examples`, or a sample
+/// generated from the schema.
+///
+/// Like other mock servers, the `Prefer` header of the request selects another response:
+/// `code=404` picks the status code, `example=name` picks an entry of `examples`, and
+/// `dynamic=true` generates a random body from the schema even if there are examples.
+#[derive(Debug, Clone)]
+pub struct Mock {
+    spec: OpenAPIV3,
+    router: Router,
+}
+
+/// The preferences of the `Prefer` header of a request.
+#[derive(Debug, Default)]
+struct Preferences {
+    code: Option<u16>,
+    example: Option<String>,
+    dynamic: bool,
+}
+
+impl Preferences {
+    fn parse(request: &MockRequest) -> Preferences {
+        let mut preferences = Preferences::default();
+        for preference in request
+            .headers
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("prefer"))
+            .flat_map(|(_, value)| value.split([',', ';']))
+        {
+            let (key, value) = preference.split_once('=').unwrap_or((preference, ""));
+            let value = value.trim().trim_matches('"');
+            match key.trim().to_ascii_lowercase().as_str() {
+                "code" => preferences.code = value.parse().ok(),
+                "example" => preferences.example = Some(value.to_string()),
+                "dynamic" => preferences.dynamic = value == "true",
+                _ => {}
+            }
+        }
+        preferences
+    }
+}
+
+impl Mock {
+    pub fn new(spec: OpenAPIV3) -> Mock {
+        let router = Router::new(&spec);
+        Mock { spec, router }
+    }
+
+    pub fn spec(&self) -> &OpenAPIV3 {
+        &self.spec
+    }
+
+    /// Answers a request.
+    pub fn handle(&self, request: &MockRequest) -> MockResponse {
+        let Ok(method) = request.method.parse::<Method>() else {
+            return MockResponse::json_error(405, json!({ "error": "method not allowed" }));
+        };
+        let route = match self.router.route(method, &request.target) {
+            Ok(route) => route,
+            Err(error @ RouteError::NotFound) => {
+                return MockResponse::json_error(404, json!({ "error": error.to_string() }))
+            }
+            Err(error @ RouteError::MethodNotAllowed(_)) => {
+                let RouteError::MethodNotAllowed(allowed) = &error else {
+                    unreachable!()
+                };
+                let allowed = allowed.iter().map(Method::to_string).collect::<Vec<_>>();
+                let mut response =
+                    MockResponse::json_error(405, json!({ "error": error.to_string() }));
+                response
+                    .headers
+                    .push(("Allow".to_string(), allowed.join(", ")));
+                return response;
+            }
+        };
+        let (_, operation) = route
+            .operation(&self.spec)
+            .expect("the router is built from the document");
+
+        if let Err(response) = self.validate(request, &route) {
+            return response;
+        }
+        self.respond(request, operation)
+    }
+
+    fn components(&self) -> Option<&Components> {
+        self.spec.components.as_ref()
+    }
+
+    fn validate(&self, request: &MockRequest, route: &RouteMatch) -> Result<(), MockResponse> {
+        let extracted = route.extract(&self.spec, &request.target, &request.headers, &request.body);
+        extracted
+            .map(|_| ())
+            .map_err(|error| MockResponse::json_error(error.status(), error.to_json()))
+    }
+
+    fn respond(&self, request: &MockRequest, operation: &Operation) -> MockResponse {
+        let components = self.components();
+        let preferences = Preferences::parse(request);
+        let responses = &operation.responses;
+        let selected = match preferences.code {
+            Some(code) => responses.for_status(code).map(|response| (code, response)),
+            None => default_response(responses),
+        };
+        let Some((status, response)) = selected else {
+            return MockResponse::json_error(
+                500,
+                json!({ "error": "the operation has no response for the requested status code" }),
+            );
+        };
+        let Some(response) = response.resolve(components) else {
+            return MockResponse::json_error(
+                500,
+                json!({ "error": "the response cannot be resolved" }),
+            );
+        };
+
+        let mut headers = response_headers(response, components);
+        let content = response
+            .content
+            .as_ref()
+            .filter(|content| !content.is_empty());
+        let Some(content) = content else {
+            return MockResponse {
+                status,
+                headers,
+                body: vec![],
+            };
+        };
+        let accept = request.header("accept").unwrap_or("");
+        let Some((key, media_type)) = content.negotiate(accept) else {
+            return MockResponse::json_error(
+                406,
+                json!({ "error": format!("no response matches `Accept: {accept}`") }),
+            );
+        };
+        let body = match body_value(media_type, &preferences, components) {
+            Ok(body) => body,
+            Err(message) => return MockResponse::json_error(500, json!({ "error": message })),
+        };
+        let content_type = response_content_type(key);
+        let body = serialize_body(&content_type, body);
+        headers.insert(0, ("Content-Type".to_string(), content_type));
+        MockResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+}
+
+/// The response of the first success status code, then of the `default`, then of the first code.
+/// Ranges are answered with their first code, e.g. `200` for `2XX`.
+fn default_response(responses: &crate::Responses) -> Option<(u16, &Referenceable<Response>)> {
+    let status = |code: &StatusCode| match code {
+        StatusCode::Code(code) => *code,
+        StatusCode::Range(class) => *class as u16 * 100,
+    };
+    responses
+        .data
+        .iter()
+        .find(|(code, _)| code.class() == 2)
+        .map(|(code, response)| (status(code), response))
+        .or_else(|| responses.default.as_ref().map(|response| (200, response)))
+        .or_else(|| {
+            responses
+                .data
+                .iter()
+                .next()
+                .map(|(code, response)| (status(code), response))
+        })
+}
+
+fn body_value(
+    media_type: &MediaType,
+    preferences: &Preferences,
+    components: Option<&Components>,
+) -> Result<Value, String> {
+    if let Some(name) = &preferences.example {
+        let example = media_type
+            .examples
+            .as_ref()
+            .and_then(|examples| examples.get(name))
+            .and_then(|example| example.resolve(components))
+            .ok_or_else(|| format!("the response has no example named `{name}`"))?;
+        return Ok(example.value.clone().unwrap_or(Value::Null));
+    }
+    let example = media_type.example.clone().or_else(|| {
+        media_type
+            .examples
+            .iter()
+            .flatten()
+            .find_map(|(_, example)| example.resolve(components)?.value.clone())
+    });
+    match (example, &media_type.schema) {
+        (Some(example), _) if !preferences.dynamic => Ok(example),
+        (_, Some(schema)) if preferences.dynamic => {
+            // a different sample for every request
+            let sampler = Sampler {
+                seed: Some(RandomState::new().build_hasher().finish()),
+                examples: false,
+                ..Sampler::default()
+            };
+            Ok(sampler.sample_referenceable(schema, components))
+        }
+        (_, Some(schema)) => Ok(schema.sample(components)),
+        (example, None) => Ok(example.unwrap_or(Value::Null)),
+    }
+}
+
+fn response_headers(response: &Response, components: Option<&Components>) -> Vec<(String, String)> {
+    response
+        .headers
+        .iter()
+        .flatten()
+        .filter(|(name, _)| !name.eq_ignore_ascii_case("content-type"))
+        .filter_map(|(name, header)| {
+            let header = header.resolve(components)?;
+            Some((
+                name.clone(),
+                primitive_to_string(&header_value(header, components)?),
+            ))
+        })
+        .collect()
+}
+
+fn header_value(header: &Header, components: Option<&Components>) -> Option<Value> {
+    header
+        .example
+        .clone()
+        .or_else(|| {
+            header
+                .examples
+                .iter()
+                .flatten()
+                .find_map(|(_, example)| example.resolve(components)?.value.clone())
+        })
+        .or_else(|| Some(header.schema.as_ref()?.sample(components)))
+}
+
+/// The `Content-Type` of a response, which is JSON for wildcard keys such as `*/*`.
+fn response_content_type(key: &MediaRange) -> String {
+    match key.is_wildcard() {
+        true => "application/json".to_string(),
+        false => key.to_string(),
+    }
+}
+
+fn serialize_body(content_type: &str, body: Value) -> Vec<u8> {
+    let media_type = content_type.parse::<MediaTypeBuf>().ok();
+    let json = media_type.as_ref().is_some_and(|media_type| {
+        media_type.subty() == "json" || media_type.suffix().is_some_and(|suffix| suffix == "json")
+    });
+    match body {
+        Value::String(text) if !json => text.into_bytes(),
+        body => body.to_string().into_bytes(),
+    }
+}
+
+/// A [Mock] served over HTTP/1.1 on a background thread, for use in tests. Connections are
+/// handled by a pool of 8 threads, further ones wait to be accepted, and they are closed after
+/// one response. The server stops when dropped.
+///
+/// Request bodies larger than 16 MiB are answered with `413`, and request heads larger than
+/// 64 KiB with `400`. A client which has not sent its whole request 10 seconds after its
+/// connection was accepted is answered with `408`.
+#[derive(Debug)]
+pub struct MockServer {
+    address: SocketAddr,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Serves the document on a free port of `127.0.0.1`.
+    pub fn start(spec: OpenAPIV3) -> io::Result<MockServer> {
+        MockServer::bind(spec, "127.0.0.1:0")
+    }
+
+    /// Serves the document on the given address.
+    pub fn bind(spec: OpenAPIV3, address: impl ToSocketAddrs) -> io::Result<MockServer> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let running = Arc::new(AtomicBool::new(true));
+        let mock = Arc::new(Mock::new(spec));
+        let (sender, receiver) = mpsc::sync_channel::<(TcpStream, Instant)>(WORKERS);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..WORKERS {
+            let (mock, receiver) = (mock.clone(), receiver.clone());
+            // the workers stop once the listener thread drops the sender
+            thread::spawn(move || loop {
+                let Ok((stream, accepted)) = receiver
+                    .lock()
+                    .expect("the lock is not held while serving")
+                    .recv()
+                else {
+                    break;
+                };
+                // the client went away or sent garbage, there is nobody to tell
+                let _ = serve(&mock, stream, accepted + TIMEOUT);
+            });
+        }
+        let thread = {
+            let running = running.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if !running.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    if sender.send((stream, Instant::now())).is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+        Ok(MockServer {
+            address,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The base URL of the server, e.g. `http://127.0.0.1:8080`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Stops accepting connections and waits for the server thread to finish.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        // wake up the listener blocked in `accept`
+        let _ = TcpStream::connect(self.address);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// The largest request body accepted by a [MockServer], larger ones are answered with `413`.
+const BODY_LIMIT: usize = 16 * 1024 * 1024;
+/// The largest request line and headers accepted by a [MockServer], and the longest chunk size
+/// line, larger ones are answered with `400`.
+const HEAD_LIMIT: u64 = 64 * 1024;
+/// How long a [MockServer] waits for a client to send its request from the time it is accepted,
+/// or to read the response.
+const TIMEOUT: Duration = match cfg!(test) {
+    true => Duration::from_secs(1),
+    false => Duration::from_secs(10),
+};
+/// The number of connections a [MockServer] handles at once.
+const WORKERS: usize = 8;
+
+/// A connection whose reads fail once a deadline has passed, so that a client sending its request
+/// byte by byte cannot hold a worker.
+struct Deadline {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Why a request could not be read.
+enum Rejection {
+    /// The connection failed, there is nobody to answer.
+    Io(io::Error),
+    /// The request is malformed or too large, it is answered with a status code.
+    Status(u16, &'static str),
+}
+
+impl From<io::Error> for Rejection {
+    fn from(error: io::Error) -> Rejection {
+        match error.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+                Rejection::Status(408, "request timeout")
+            }
+            _ => Rejection::Io(error),
+        }
+    }
+}
+
+fn serve(mock: &Mock, stream: TcpStream, deadline: Instant) -> io::Result<()> {
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(Deadline {
+        stream: stream.try_clone()?,
+        deadline,
+    });
+    let (response, request) = match read_request(&mut reader) {
+        Ok(request) => (mock.handle(&request), Some(request)),
+        Err(Rejection::Status(status, error)) => (
+            MockResponse::json_error(status, json!({ "error": error })),
+            None,
+        ),
+        Err(Rejection::Io(error)) => return Err(error),
+    };
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        response.status,
+        reason(response.status)
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+    let mut stream = stream;
+    stream.write_all(head.as_bytes())?;
+    let Some(request) = request else {
+        stream.write_all(&response.body)?;
+        // closing the connection with unread data resets it, which may discard the response
+        stream.shutdown(Shutdown::Write)?;
+        reader.get_mut().deadline = Instant::now() + TIMEOUT;
+        io::copy(&mut reader.take(BODY_LIMIT as u64), &mut io::sink())?;
+        return Ok(());
+    };
+    if !request.method.eq_ignore_ascii_case("head") {
+        stream.write_all(&response.body)?;
+    }
+    stream.flush()
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<MockRequest, Rejection> {
+    let malformed = |error| Rejection::Status(400, error);
+    let too_large = || Rejection::Status(413, "request body too large");
+
+    let mut line = String::new();
+    let mut remaining = HEAD_LIMIT;
+    read_line(reader, &mut line, &mut remaining)?;
+    let mut request_line = line.split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err(malformed("malformed request line"));
+    };
+    let mut request = MockRequest::new(method, target);
+    loop {
+        read_line(reader, &mut line, &mut remaining)?;
+        let header = line.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| malformed("malformed header"))?;
+        request
+            .headers
+            .push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+    {
+        loop {
+            remaining = HEAD_LIMIT;
+            read_line(reader, &mut line, &mut remaining)?;
+            let size = line.trim().split(';').next().unwrap_or_default();
+            let size =
+                usize::from_str_radix(size, 16).map_err(|_| malformed("malformed chunk size"))?;
+            request
+                .body
+                .len()
+                .checked_add(size)
+                .filter(|length| *length <= BODY_LIMIT)
+                .ok_or_else(too_large)?;
+            // the chunk is followed by a line break
+            let mut chunk = vec![0; size.checked_add(2).ok_or_else(too_large)?];
+            reader.read_exact(&mut chunk)?;
+            if size == 0 {
+                break;
+            }
+            request.body.extend_from_slice(&chunk[..size]);
+        }
+    } else if let Some(length) = request.header("content-length") {
+        let length = length
+            .parse::<usize>()
+            .map_err(|_| malformed("malformed content length"))?;
+        if length > BODY_LIMIT {
+            return Err(too_large());
+        }
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body)?;
+    }
+    Ok(request)
+}
+
+/// Reads a line of at most `remaining` bytes, which are deducted from `remaining`.
+fn read_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    remaining: &mut u64,
+) -> Result<(), Rejection> {
+    line.clear();
+    let read = reader.take(*remaining).read_line(line)?;
+    *remaining -= read as u64;
+    if line.ends_with('\n') {
+        Ok(())
+    } else if *remaining == 0 {
+        Err(Rejection::Status(400, "request head too large"))
+    } else {
+        Err(Rejection::Io(io::ErrorKind::UnexpectedEof.into()))
+    }
+}
+
+pub(crate) fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        413 => "Content Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
@@ -1,11 +1,9 @@
-use std::collections::BTreeMap;
-
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
 use crate::{
-    Any, Components, ExternalDocumentation, Info, Parseable, PathItem, SecurityRequirement, Server,
-    Tag,
+    Any, Components, ExternalDocumentation, Info, Map, Parseable, PathItem, SecurityRequirement,
+    Server, Tag,
 };
 
 #[skip_serializing_none]
@@ -20,7 +18,7 @@ pub struct OpenAPIV3 {
     /// An array of Server Objects, which provide connectivity information to a target server. If the `servers` property is not provided, or is an empty array, the default value would be a `Server` Object with a url value of `/`.
     pub servers: Option<Vec<Server>>,
     /// The available paths and operations for the API.
-    pub paths: BTreeMap<String, PathItem>,
+    pub paths: Map<String, PathItem>,
     /// An element to hold various schemas for the specification.
     pub components: Option<Components>,
     /// A declaration of which security mechanisms can be used across the API. The list of values includes alternative security requirement objects that can be used. Only one of the security requirement objects need to be satisfied to authorize a request. Individual operations can override this definition. To make security optional, an empty security requirement (`{}`) can be included in the array.
@@ -30,5 +28,5 @@ pub struct OpenAPIV3 {
     /// Additional external documentation.
     pub external_docs: Option<ExternalDocumentation>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
@@ -0,0 +1,156 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+use crate::jsonpath::{self, JsonPath, Location};
+use crate::{Any, Map, OpenAPIV3};
+
+/// An [Overlay](https://spec.openapis.org/overlay/v1.0.0.html): a list of actions updating or
+/// removing parts of an OpenAPI document, e.g. to add vendor extensions or to strip internal
+/// operations before publishing it.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Overlay {
+    /// The version of the Overlay Specification, e.g. `1.0.0`.
+    pub overlay: String,
+    pub info: OverlayInfo,
+    /// The URL of the document the overlay is meant for.
+    pub extends: Option<String>,
+    /// The actions, applied in order.
+    pub actions: Vec<OverlayAction>,
+    #[serde(flatten)]
+    pub extras: Option<Map<String, Any>>,
+}
+
+/// Metadata about an overlay.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayInfo {
+    pub title: String,
+    /// The version of the overlay itself.
+    pub version: String,
+    #[serde(flatten)]
+    pub extras: Option<Map<String, Any>>,
+}
+
+/// Updates or removes the values selected by a JSONPath expression.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayAction {
+    /// A JSONPath expression selecting the values to act on, e.g. `$.paths['/pets'].get`.
+    pub target: String,
+    pub description: Option<String>,
+    /// Merged into the selected objects, or appended to the selected arrays.
+    pub update: Option<Any>,
+    /// Removes the selected values from their parents. Takes precedence over `update`.
+    pub remove: Option<bool>,
+    #[serde(flatten)]
+    pub extras: Option<Map<String, Any>>,
+}
+
+/// The reasons why an overlay cannot be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverlayError {
+    /// The target of the action with the given index is not a valid JSONPath expression.
+    InvalidTarget { action: usize, message: String },
+    /// The action with the given index updates a value which is neither an object nor an
+    /// array, or an object with something else than an object.
+    InvalidUpdate { action: usize },
+    /// The document is not a valid OpenAPI document once the overlay is applied.
+    InvalidDocument(String),
+}
+
+impl fmt::Display for OverlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverlayError::InvalidTarget { action, message } => {
+                write!(f, "invalid target of action {action}: {message}")
+            }
+            OverlayError::InvalidUpdate { action } => {
+                write!(f, "action {action} updates a value which cannot be merged")
+            }
+            OverlayError::InvalidDocument(message) => {
+                write!(f, "invalid document after applying the overlay: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OverlayError {}
+
+impl Overlay {
+    /// Applies the actions to a document. Targets selecting nothing are ignored.
+    pub fn apply(&self, spec: &OpenAPIV3) -> Result<OpenAPIV3, OverlayError> {
+        let mut value = spec.to_value();
+        self.apply_to_value(&mut value)?;
+        serde_json::from_value(value)
+            .map_err(|error| OverlayError::InvalidDocument(error.to_string()))
+    }
+
+    /// Applies the actions to any JSON document.
+    pub fn apply_to_value(&self, document: &mut Value) -> Result<(), OverlayError> {
+        for (index, action) in self.actions.iter().enumerate() {
+            let target =
+                JsonPath::parse(&action.target).map_err(|error| OverlayError::InvalidTarget {
+                    action: index,
+                    message: error.to_string(),
+                })?;
+            let mut locations = target.locate(document);
+            if action.remove == Some(true) {
+                // the last locations first, so that removing items does not shift the others
+                locations.sort();
+                locations.dedup();
+                for location in locations.into_iter().rev() {
+                    remove(document, &location);
+                }
+            } else if let Some(update) = &action.update {
+                for location in locations {
+                    let Some(value) = jsonpath::get_mut(document, &location) else {
+                        continue;
+                    };
+                    match (value, update) {
+                        (Value::Array(array), update) => array.push(update.clone()),
+                        (value @ Value::Object(_), Value::Object(_)) => merge(value, update),
+                        _ => return Err(OverlayError::InvalidUpdate { action: index }),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Merges objects recursively, replacing the other values.
+fn merge(target: &mut Value, update: &Value) {
+    match (target, update) {
+        (Value::Object(target), Value::Object(update)) => {
+            for (key, update) in update {
+                match target.get_mut(key) {
+                    Some(target) => merge(target, update),
+                    None => {
+                        target.insert(key.clone(), update.clone());
+                    }
+                }
+            }
+        }
+        (target, update) => *target = update.clone(),
+    }
+}
+
+fn remove(document: &mut Value, location: &[Location]) {
+    let Some((last, parent)) = location.split_last() else {
+        // the root cannot be removed
+        return;
+    };
+    match (jsonpath::get_mut(document, parent), last) {
+        (Some(Value::Object(object)), Location::Key(key)) => {
+            object.remove(key);
+        }
+        (Some(Value::Array(array)), Location::Index(index)) if *index < array.len() => {
+            array.remove(*index);
+        }
+        _ => {}
+    }
+}
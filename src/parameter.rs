@@ -1,13 +1,11 @@
-use std::collections::BTreeMap;
-
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
-use crate::{Any, Example, MediaType, Referenceable, Schema};
+use crate::{Any, Example, Map, MediaRange, MediaType, Referenceable, Schema};
 
 /// The location of the parameter
 #[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ParameterIn {
     Query,
@@ -50,11 +48,11 @@ pub struct Parameter {
     /// Example of the parameter's potential value.
     pub example: Option<Any>,
     /// Examples of the parameter's potential value.
-    pub examples: Option<BTreeMap<String, Referenceable<Example>>>,
+    pub examples: Option<Map<String, Referenceable<Example>>>,
     /// A map containing the representations for the parameter. The key is the media type and the value describes it.
-    pub content: Option<BTreeMap<String, MediaType>>,
+    pub content: Option<Map<MediaRange, MediaType>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// Refer to the [documentation](https://swagger.io/specification/#style-values) for more information.
@@ -1,11 +1,12 @@
-use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
 use crate::{
-    Any, Callback, ExternalDocumentation, Parameter, Referenceable, RequestBody, Responses,
-    SecurityRequirement, Server,
+    Any, Callback, Components, ExternalDocumentation, Map, OpenAPIV3, Parameter, Referenceable,
+    RequestBody, Responses, SecurityRequirement, Server,
 };
 
 /// Describes the operations available on a single path. A Path Item MAY be empty, due to ACL constraints. The path itself is still exposed to the documentation viewer but they will not know which operations and parameters are available.
@@ -40,7 +41,7 @@ pub struct PathItem {
     /// A list of parameters that are applicable for all the operations described under this path. These parameters can be overridden at the operation level, but cannot be removed there. The list MUST NOT include duplicated parameters. A unique parameter is defined by a combination of a name and location. The list can use the Reference Object to link to parameters that are defined at the OpenAPI Object's components/parameters.
     pub parameters: Option<Vec<Referenceable<Parameter>>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// Describes a single API operation on a path.
@@ -65,7 +66,7 @@ pub struct Operation {
     /// The list of possible responses as they are returned from executing this operation.
     pub responses: Responses,
     /// A map of possible out-of band callbacks related to the parent operation. The key is a unique identifier for the Callback Object. Each value in the map is a Callback Object that describes a request that may be initiated by the API provider and the expected responses.
-    pub callbacks: Option<BTreeMap<String, Referenceable<Callback>>>,
+    pub callbacks: Option<Map<String, Referenceable<Callback>>>,
     /// Declares this operation to be deprecated. Consumers SHOULD refrain from usage of the declared operation. Default value is `false`.
     pub deprecated: Option<bool>,
     /// A declaration of which security mechanisms can be used for this operation. The list of values includes alternative security requirement objects that can be used. Only one of the security requirement objects need to be satisfied to authorize a request. To make security optional, an empty security requirement (`{}`) can be included in the array. This definition overrides any declared top-level security. To remove a top-level security declaration, an empty array can be used.
@@ -73,5 +74,143 @@ pub struct Operation {
     /// An alternative server array to service this operation. If an alternative server object is specified at the Path Item Object or Root level, it will be overridden by this value.
     pub servers: Option<Vec<Server>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
+}
+
+/// The HTTP methods for which a [PathItem] can define an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    Get,
+    Put,
+    Post,
+    Delete,
+    Options,
+    Head,
+    Patch,
+    Trace,
+}
+
+impl Method {
+    /// All the methods, in the order their fields are declared in a [PathItem].
+    pub const ALL: [Method; 8] = [
+        Method::Get,
+        Method::Put,
+        Method::Post,
+        Method::Delete,
+        Method::Options,
+        Method::Head,
+        Method::Patch,
+        Method::Trace,
+    ];
+
+    /// The lowercase name of the method, as used for the fields of a [PathItem].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "get",
+            Method::Put => "put",
+            Method::Post => "post",
+            Method::Delete => "delete",
+            Method::Options => "options",
+            Method::Head => "head",
+            Method::Patch => "patch",
+            Method::Trace => "trace",
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.as_str().to_ascii_uppercase())
+    }
+}
+
+impl FromStr for Method {
+    type Err = String;
+
+    /// Parses a method name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Method::ALL
+            .into_iter()
+            .find(|method| method.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown HTTP method `{s}`"))
+    }
+}
+
+impl PathItem {
+    /// The operation defined for a method, if any.
+    pub fn operation(&self, method: Method) -> Option<&Operation> {
+        self.operation_slot(method).as_ref()
+    }
+
+    /// A mutable reference to the field holding the operation of a method.
+    pub fn operation_mut(&mut self, method: Method) -> &mut Option<Operation> {
+        match method {
+            Method::Get => &mut self.get,
+            Method::Put => &mut self.put,
+            Method::Post => &mut self.post,
+            Method::Delete => &mut self.delete,
+            Method::Options => &mut self.options,
+            Method::Head => &mut self.head,
+            Method::Patch => &mut self.patch,
+            Method::Trace => &mut self.trace,
+        }
+    }
+
+    fn operation_slot(&self, method: Method) -> &Option<Operation> {
+        match method {
+            Method::Get => &self.get,
+            Method::Put => &self.put,
+            Method::Post => &self.post,
+            Method::Delete => &self.delete,
+            Method::Options => &self.options,
+            Method::Head => &self.head,
+            Method::Patch => &self.patch,
+            Method::Trace => &self.trace,
+        }
+    }
+
+    /// The operations defined on this path, in the order of [Method::ALL].
+    pub fn operations(&self) -> impl Iterator<Item = (Method, &Operation)> {
+        Method::ALL
+            .into_iter()
+            .filter_map(|method| Some((method, self.operation(method)?)))
+    }
+
+    /// The parameters applicable to an operation of this path: the ones of the path item, unless
+    /// overridden by a parameter of the operation with the same name and location, followed by
+    /// the ones of the operation. References which cannot be resolved are skipped.
+    pub fn parameters_for<'a>(
+        &'a self,
+        operation: &'a Operation,
+        components: Option<&'a Components>,
+    ) -> Vec<&'a Parameter> {
+        let resolve = |parameters: &'a Option<Vec<Referenceable<Parameter>>>| {
+            parameters
+                .iter()
+                .flatten()
+                .filter_map(|parameter| parameter.resolve(components))
+                .collect::<Vec<_>>()
+        };
+        let own = resolve(&operation.parameters);
+        let mut parameters = resolve(&self.parameters)
+            .into_iter()
+            .filter(|parameter| {
+                !own.iter()
+                    .any(|own| own.name == parameter.name && own._in == parameter._in)
+            })
+            .collect::<Vec<_>>();
+        parameters.extend(own);
+        parameters
+    }
+}
+
+impl OpenAPIV3 {
+    /// All the operations of the document with their path and method.
+    pub fn operations(&self) -> impl Iterator<Item = (&str, Method, &Operation)> {
+        self.paths.iter().flat_map(|(path, item)| {
+            item.operations()
+                .map(move |(method, operation)| (path.as_str(), method, operation))
+        })
+    }
 }
@@ -0,0 +1,8 @@
+/// Appends a reference token to a [JSON Pointer](https://www.rfc-editor.org/rfc/rfc6901),
+/// escaping `~` and `/` in the token.
+pub(crate) fn join(pointer: &str, token: impl AsRef<str>) -> String {
+    format!(
+        "{pointer}/{}",
+        token.as_ref().replace('~', "~0").replace('/', "~1")
+    )
+}
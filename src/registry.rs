@@ -0,0 +1,394 @@
+use std::str::FromStr;
+
+use crate::mock::reason;
+use crate::{
+    Components, Info, Map, MediaRange, MediaType, Method, OpenAPIV3, Operation, Parameter,
+    ParameterIn, Parseable, PathItem, Referenceable, RequestBody, Response, Responses, StatusCode,
+    ToSchema, Type,
+};
+
+/// A path parameter, named after the argument of the handler, or one parameter per property for
+/// structs.
+pub struct InPath<T>(pub T);
+
+/// A query parameter, named after the argument of the handler, or one parameter per property for
+/// structs.
+pub struct InQuery<T>(pub T);
+
+/// A header parameter, named after the argument of the handler, or one parameter per property for
+/// structs.
+pub struct InHeader<T>(pub T);
+
+/// A cookie parameter, named after the argument of the handler, or one parameter per property for
+/// structs.
+pub struct InCookie<T>(pub T);
+
+/// An `application/json` request body or response.
+pub struct Json<T>(pub T);
+
+/// An `application/x-www-form-urlencoded` request body.
+pub struct Form<T>(pub T);
+
+/// A response with another status than the one of `T`, e.g. `WithStatus<201, Json<Pet>>`.
+///
+/// The status must be between `100` and `599`, which is checked at compile time:
+///
+/// ```compile_fail
+/// use oas::{Components, Json, OperationOutput, WithStatus};
+///
+/// WithStatus::<600, Json<String>>::responses(&mut Components::default());
+/// ```
+pub struct WithStatus<const STATUS: u16, T>(pub T);
+
+/// An argument of a handler annotated with `#[operation]`, which describes part of the request.
+pub trait OperationArgument {
+    /// Adds the parameters or the request body described by the argument to the operation.
+    fn describe(name: &str, operation: &mut Operation, components: &mut Components);
+}
+
+/// The return type of a handler annotated with `#[operation]`, which describes the responses.
+pub trait OperationOutput {
+    fn responses(components: &mut Components) -> Responses;
+}
+
+/// An operation described by `#[operation(get, "/pets/{id}")]`, which implements this trait for
+/// a struct named after the handler.
+pub trait DescribedOperation {
+    const METHOD: Method;
+    const PATH: &'static str;
+
+    /// The operation, registering the schemas it uses.
+    fn operation(components: &mut Components) -> Operation;
+}
+
+fn parameters<T: ToSchema>(
+    name: &str,
+    _in: ParameterIn,
+    operation: &mut Operation,
+    components: &mut Components,
+) {
+    let parameter = |name: &str, schema: Referenceable<crate::Schema>, required: bool| {
+        Referenceable::Data(Parameter {
+            name: name.to_string(),
+            _in,
+            description: None,
+            required: (required || _in == ParameterIn::Path).then_some(true),
+            deprecated: None,
+            allow_empty_value: None,
+            style: None,
+            explode: None,
+            allow_reserved: None,
+            schema: Some(schema),
+            example: None,
+            examples: None,
+            content: None,
+            extras: None,
+        })
+    };
+    let schema = T::schema(components);
+    let parameters = operation.parameters.get_or_insert_with(Vec::new);
+    match schema.properties {
+        Some(properties) if schema._type == Some(Type::Object) => {
+            let required = schema.required.unwrap_or_default();
+            for (name, schema) in properties {
+                let required = required.contains(&name);
+                parameters.push(parameter(&name, schema, required));
+            }
+        }
+        _ => parameters.push(parameter(
+            name,
+            T::referenceable(components),
+            !T::optional(),
+        )),
+    }
+}
+
+macro_rules! impl_parameter {
+    ($($t:ident => $in:ident),+) => {
+        $(
+        impl<T: ToSchema> OperationArgument for $t<T> {
+            fn describe(name: &str, operation: &mut Operation, components: &mut Components) {
+                parameters::<T>(name, ParameterIn::$in, operation, components);
+            }
+        }
+        )+
+    };
+}
+
+impl_parameter!(InPath => Path, InQuery => Query, InHeader => Header, InCookie => Cookie);
+
+fn content<T: ToSchema>(
+    media_type: &str,
+    components: &mut Components,
+) -> Map<MediaRange, MediaType> {
+    let media_type = MediaRange::from_str(media_type).expect("the media type is valid");
+    let schema = T::referenceable(components);
+    Map::from([(
+        media_type,
+        MediaType {
+            schema: Some(schema),
+            example: None,
+            examples: None,
+            encoding: None,
+            extras: None,
+        },
+    )])
+}
+
+fn request_body<T: ToSchema>(
+    media_type: &str,
+    operation: &mut Operation,
+    components: &mut Components,
+) {
+    operation.request_body = Some(Referenceable::Data(RequestBody {
+        description: None,
+        required: (!T::optional()).then_some(true),
+        content: content::<T>(media_type, components),
+        extras: None,
+    }));
+}
+
+impl<T: ToSchema> OperationArgument for Json<T> {
+    fn describe(_: &str, operation: &mut Operation, components: &mut Components) {
+        request_body::<T>("application/json", operation, components);
+    }
+}
+
+impl<T: ToSchema> OperationArgument for Form<T> {
+    fn describe(_: &str, operation: &mut Operation, components: &mut Components) {
+        request_body::<T>("application/x-www-form-urlencoded", operation, components);
+    }
+}
+
+fn response(status: u16, content: Option<Map<MediaRange, MediaType>>) -> Responses {
+    let description = match reason(status) {
+        "" => "Response",
+        reason => reason,
+    };
+    let response = Response {
+        description: description.to_string(),
+        headers: None,
+        content,
+        links: None,
+        extras: None,
+    };
+    Responses {
+        default: None,
+        data: Map::from([(
+            StatusCode::code(status).expect("the status is valid"),
+            Referenceable::Data(response),
+        )]),
+        extras: None,
+    }
+}
+
+impl<T: ToSchema> OperationOutput for Json<T> {
+    fn responses(components: &mut Components) -> Responses {
+        response(200, Some(content::<T>("application/json", components)))
+    }
+}
+
+/// `text/plain`.
+impl OperationOutput for String {
+    fn responses(components: &mut Components) -> Responses {
+        response(200, Some(content::<String>("text/plain", components)))
+    }
+}
+
+/// `application/octet-stream`.
+impl OperationOutput for Vec<u8> {
+    fn responses(_: &mut Components) -> Responses {
+        let mut content = Map::new();
+        content.insert(
+            MediaRange::from_str("application/octet-stream").expect("the media type is valid"),
+            MediaType {
+                schema: Some(Referenceable::Data(crate::Schema {
+                    _type: Some(Type::String),
+                    format: Some(crate::Format::Other("binary".to_string())),
+                    ..crate::Schema::default()
+                })),
+                example: None,
+                examples: None,
+                encoding: None,
+                extras: None,
+            },
+        );
+        response(200, Some(content))
+    }
+}
+
+/// `204 No Content`.
+impl OperationOutput for () {
+    fn responses(_: &mut Components) -> Responses {
+        response(204, None)
+    }
+}
+
+impl<const STATUS: u16, T: OperationOutput> OperationOutput for WithStatus<STATUS, T> {
+    fn responses(components: &mut Components) -> Responses {
+        const {
+            assert!(
+                100 <= STATUS && STATUS <= 599,
+                "the status must be between 100 and 599"
+            )
+        }
+        let content =
+            T::responses(components)
+                .data
+                .into_values()
+                .find_map(|response| match response {
+                    Referenceable::Data(response) => response.content,
+                    Referenceable::Reference(_) => None,
+                });
+        response(STATUS, content)
+    }
+}
+
+/// The responses of both `T` and `E`. When they have the same status, e.g. in
+/// `Result<Json<Pet>, Json<Error>>`, the response has the media types of both, and the schema of
+/// a media type they share is the `anyOf` of theirs.
+impl<T: OperationOutput, E: OperationOutput> OperationOutput for Result<T, E> {
+    fn responses(components: &mut Components) -> Responses {
+        let mut responses = T::responses(components);
+        let errors = E::responses(components);
+        for (status, response) in errors.data {
+            match responses.data.get_mut(&status) {
+                Some(existing) => merge_response(existing, response),
+                None => {
+                    responses.data.insert(status, response);
+                }
+            }
+        }
+        match (&mut responses.default, errors.default) {
+            (Some(existing), Some(response)) => merge_response(existing, response),
+            (default @ None, response) => *default = response,
+            (Some(_), None) => {}
+        }
+        responses
+    }
+}
+
+/// Adds the media types and the headers of a response to another one with the same status.
+/// References cannot be merged, the first response is kept.
+fn merge_response(existing: &mut Referenceable<Response>, response: Referenceable<Response>) {
+    let (Referenceable::Data(existing), Referenceable::Data(response)) = (existing, response)
+    else {
+        return;
+    };
+    for (name, header) in response.headers.into_iter().flatten() {
+        let headers = existing.headers.get_or_insert_with(Map::new);
+        headers.entry(name).or_insert(header);
+    }
+    for (media_range, media_type) in response.content.into_iter().flatten() {
+        let content = existing.content.get_or_insert_with(Map::new);
+        let Some(existing) = content.get_mut(&media_range) else {
+            content.insert(media_range, media_type);
+            continue;
+        };
+        // a media type without a schema accepts any body
+        existing.schema = match (existing.schema.take(), media_type.schema) {
+            (Some(schema), Some(other)) if to_value(&schema) == to_value(&other) => Some(schema),
+            (Some(schema), Some(other)) => Some(Referenceable::Data(crate::Schema {
+                any_of: Some(vec![schema, other]),
+                ..crate::Schema::default()
+            })),
+            _ => None,
+        };
+    }
+}
+
+fn to_value(schema: &Referenceable<crate::Schema>) -> serde_json::Value {
+    serde_json::to_value(schema).expect("the schema can be serialized")
+}
+
+/// Collects the operations annotated with `#[operation]` into the paths of a document.
+#[derive(Debug, Clone, Default)]
+pub struct OperationRegistry {
+    paths: Map<String, PathItem>,
+    components: Components,
+}
+
+impl OperationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an operation, e.g. `registry.register::<get_pet>()` for a handler named `get_pet`.
+    pub fn register<O: DescribedOperation>(&mut self) -> &mut Self {
+        let operation = O::operation(&mut self.components);
+        self.add(O::PATH, O::METHOD, operation);
+        self
+    }
+
+    /// Adds an operation, returning the one it replaces.
+    pub fn add(&mut self, path: &str, method: Method, operation: Operation) -> Option<Operation> {
+        let item = self
+            .paths
+            .entry(path.to_string())
+            .or_insert_with(empty_path_item);
+        item.operation_mut(method).replace(operation)
+    }
+
+    pub fn paths(&self) -> &Map<String, PathItem> {
+        &self.paths
+    }
+
+    /// The components holding the schemas used by the operations.
+    pub fn components(&self) -> &Components {
+        &self.components
+    }
+
+    /// Adds the paths and the components to a document, replacing the operations and the
+    /// components with the same names.
+    pub fn merge_into(self, spec: &mut OpenAPIV3) {
+        for (path, item) in self.paths {
+            let target = spec.paths.entry(path).or_insert_with(empty_path_item);
+            for (method, operation) in item.operations() {
+                *target.operation_mut(method) = Some(operation.clone());
+            }
+        }
+        let components = spec.components.get_or_insert_with(Components::default);
+        if let Some(schemas) = self.components.schemas {
+            components
+                .schemas
+                .get_or_insert_with(Map::new)
+                .extend(schemas);
+        }
+    }
+
+    /// A document with the given info, holding the registered operations.
+    pub fn into_document(self, info: Info) -> OpenAPIV3 {
+        let mut spec = OpenAPIV3 {
+            openapi: Parseable::Valid(semver::Version::new(3, 0, 3)),
+            info,
+            servers: None,
+            paths: Map::new(),
+            components: None,
+            security: None,
+            tags: None,
+            external_docs: None,
+            extras: None,
+        };
+        self.merge_into(&mut spec);
+        spec
+    }
+}
+
+fn empty_path_item() -> PathItem {
+    PathItem {
+        _ref: None,
+        summary: None,
+        description: None,
+        get: None,
+        put: None,
+        post: None,
+        delete: None,
+        options: None,
+        head: None,
+        patch: None,
+        trace: None,
+        servers: None,
+        parameters: None,
+        extras: None,
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use regex::Regex;
+
+use crate::style::percent_decode;
+use crate::{Method, OpenAPIV3, Operation, PathItem};
+
+/// Matches requests to the operations of a document using its `paths`. Concrete paths take
+/// precedence over templated ones, and the base paths of the `servers` are stripped from the
+/// requested path.
+///
+/// The router does not borrow the document, so that it can be built once and kept along with it.
+#[derive(Debug, Clone)]
+pub struct Router {
+    base_paths: Vec<String>,
+    routes: Vec<Route>,
+}
+
+#[derive(Debug, Clone)]
+struct Route {
+    path: String,
+    methods: Vec<Method>,
+    regex: Regex,
+    variables: Vec<String>,
+}
+
+/// An operation matched by a [Router].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteMatch {
+    /// The templated path of the operation, e.g. `/pets/{petId}`, which is its key in `paths`.
+    pub path: String,
+    pub method: Method,
+    /// The values of the path parameters, percent-decoded.
+    pub parameters: BTreeMap<String, String>,
+}
+
+impl RouteMatch {
+    /// The path item and the operation matched, looked up in the document the router was built
+    /// from.
+    pub fn operation<'a>(&self, spec: &'a OpenAPIV3) -> Option<(&'a PathItem, &'a Operation)> {
+        let item = spec.paths.get(&self.path)?;
+        Some((item, item.operation(self.method)?))
+    }
+}
+
+/// The reasons why a request does not match an operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    /// No path matches the request.
+    NotFound,
+    /// A path matches, but it has no operation for the method. Holds the allowed methods.
+    MethodNotAllowed(Vec<Method>),
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::NotFound => write!(f, "no path matches the request"),
+            RouteError::MethodNotAllowed(allowed) => {
+                let allowed = allowed.iter().map(Method::to_string).collect::<Vec<_>>();
+                write!(
+                    f,
+                    "method not allowed, expected one of {}",
+                    allowed.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+impl Router {
+    pub fn new(spec: &OpenAPIV3) -> Router {
+        let mut routes = spec
+            .paths
+            .iter()
+            .map(|(path, item)| {
+                let (regex, variables) = path_regex(path);
+                Route {
+                    path: path.clone(),
+                    methods: item.operations().map(|(method, _)| method).collect(),
+                    regex,
+                    variables,
+                }
+            })
+            .collect::<Vec<_>>();
+        // fewer variables first, then longer literal text
+        routes.sort_by_key(|route| {
+            let literal = route.path.len()
+                - route
+                    .variables
+                    .iter()
+                    .map(|name| name.len() + 2)
+                    .sum::<usize>();
+            (route.variables.len(), usize::MAX - literal)
+        });
+        let mut base_paths = spec
+            .servers
+            .iter()
+            .flatten()
+            .map(|server| server.base_path())
+            .filter(|path| !path.is_empty())
+            .collect::<Vec<_>>();
+        base_paths.sort_by_key(|path| usize::MAX - path.len());
+        base_paths.dedup();
+        Router { base_paths, routes }
+    }
+
+    /// Finds the operation for a method and a path, which may include a query string.
+    pub fn route(&self, method: Method, path: &str) -> Result<RouteMatch, RouteError> {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let mut candidates = self
+            .base_paths
+            .iter()
+            .filter_map(|base| {
+                let stripped = path.strip_prefix(base.as_str())?;
+                match stripped.is_empty() || stripped.starts_with('/') {
+                    true => Some(stripped),
+                    false => None,
+                }
+            })
+            .map(|stripped| if stripped.is_empty() { "/" } else { stripped })
+            .collect::<Vec<_>>();
+        candidates.push(path);
+
+        let mut allowed = None;
+        for candidate in candidates {
+            for route in &self.routes {
+                let Some(captures) = route.regex.captures(candidate) else {
+                    continue;
+                };
+                if !route.methods.contains(&method) {
+                    allowed.get_or_insert_with(|| route.methods.clone());
+                    continue;
+                }
+                let parameters = route
+                    .variables
+                    .iter()
+                    .zip(captures.iter().skip(1))
+                    .map(|(name, value)| {
+                        let value = value.map_or("", |value| value.as_str());
+                        (name.clone(), percent_decode(value))
+                    })
+                    .collect();
+                return Ok(RouteMatch {
+                    path: route.path.clone(),
+                    method,
+                    parameters,
+                });
+            }
+        }
+        Err(allowed.map_or(RouteError::NotFound, RouteError::MethodNotAllowed))
+    }
+}
+
+/// A regular expression matching a templated path, along with the names of its variables in the
+/// order of the capture groups. A variable matches a non empty part of a single segment.
+fn path_regex(path: &str) -> (Regex, Vec<String>) {
+    let mut pattern = String::from("^");
+    let mut variables = vec![];
+    let mut rest = path;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        pattern.push_str(&regex::escape(&rest[..start]));
+        pattern.push_str("([^/]+)");
+        variables.push(rest[start + 1..start + end].to_string());
+        rest = &rest[start + end + 1..];
+    }
+    pattern.push_str(&regex::escape(rest));
+    pattern.push_str("/?$");
+    let regex = Regex::new(&pattern).expect("escaped paths should be valid patterns");
+    (regex, variables)
+}
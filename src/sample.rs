@@ -0,0 +1,475 @@
+use serde_json::{Map, Number, Value};
+
+use crate::{AdditionalProperties, Components, Format, Referenceable, Schema, Type};
+
+/// Generates sample values conforming to schemas.
+///
+/// Values are taken from the `example`, `default` or `enum` of a schema when there is one, and
+/// are otherwise generated from its type, `format`, length and range constraints. Objects get
+/// their `required` properties, `allOf` schemas are merged and one of the `oneOf` or `anyOf`
+/// schemas is selected, setting the property of its `discriminator`.
+///
+/// Without a seed, the samples are fixed placeholders such as `"string"` or `0`. With a seed,
+/// they are pseudo-random, and the same seed always produces the same samples.
+///
+/// `pattern` is not taken into account, so strings may not match it.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    /// The seed of the pseudo-random samples, or `None` for fixed placeholders.
+    pub seed: Option<u64>,
+    /// Whether properties which are not `required` are included. Defaults to `true`.
+    pub optional_properties: bool,
+    /// Whether the `example` and `default` values are used. Defaults to `true`.
+    pub examples: bool,
+    /// The depth at which nested schemas are no longer expanded, which stops recursive schemas.
+    pub max_depth: usize,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler {
+            seed: None,
+            optional_properties: true,
+            examples: true,
+            max_depth: 8,
+        }
+    }
+}
+
+impl Sampler {
+    /// A sampler producing pseudo-random samples from the seed.
+    pub fn seeded(seed: u64) -> Sampler {
+        Sampler {
+            seed: Some(seed),
+            ..Sampler::default()
+        }
+    }
+
+    /// A sample value for the schema.
+    pub fn sample(&self, schema: &Schema, components: Option<&Components>) -> Value {
+        self.generator(components).schema(schema, 0)
+    }
+
+    /// A sample value for the schema, or the schema the reference points to. Unresolvable
+    /// references produce `null`.
+    pub fn sample_referenceable(
+        &self,
+        schema: &Referenceable<Schema>,
+        components: Option<&Components>,
+    ) -> Value {
+        self.generator(components).referenceable(schema, 0)
+    }
+
+    fn generator<'a>(&'a self, components: Option<&'a Components>) -> Generator<'a> {
+        Generator {
+            sampler: self,
+            components,
+            random: self.seed.map(Random),
+        }
+    }
+}
+
+impl Schema {
+    /// A sample value for the schema, see [Sampler].
+    pub fn sample(&self, components: Option<&Components>) -> Value {
+        Sampler::default().sample(self, components)
+    }
+}
+
+impl Referenceable<Schema> {
+    /// A sample value for the schema, or the schema the reference points to, see [Sampler].
+    pub fn sample(&self, components: Option<&Components>) -> Value {
+        Sampler::default().sample_referenceable(self, components)
+    }
+}
+
+/// A SplitMix64 generator, which is small and good enough for samples.
+#[derive(Debug, Clone)]
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number between `low` and `high`, both included.
+    fn between(&mut self, low: i64, high: i64) -> i64 {
+        match high.checked_sub(low) {
+            Some(span) if span > 0 && span < i64::MAX => {
+                low + (self.next() % (span as u64 + 1)) as i64
+            }
+            _ => low,
+        }
+    }
+
+    fn choose<'a, T>(&mut self, values: &'a [T]) -> &'a T {
+        &values[self.between(0, values.len() as i64 - 1) as usize]
+    }
+}
+
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+    "kilo", "lima", "mike", "november", "oscar", "papa",
+];
+
+struct Generator<'a> {
+    sampler: &'a Sampler,
+    components: Option<&'a Components>,
+    random: Option<Random>,
+}
+
+impl<'a> Generator<'a> {
+    fn referenceable(&mut self, schema: &'a Referenceable<Schema>, depth: usize) -> Value {
+        match schema.resolve(self.components) {
+            Some(schema) if depth < self.sampler.max_depth => self.schema(schema, depth),
+            _ => Value::Null,
+        }
+    }
+
+    fn schema(&mut self, schema: &'a Schema, depth: usize) -> Value {
+        if self.sampler.examples {
+            if let Some(value) = schema.example.as_ref().or(schema.default.as_ref()) {
+                return value.clone();
+            }
+        }
+        if let Some(values) = schema._enum.as_ref().filter(|values| !values.is_empty()) {
+            return match &mut self.random {
+                Some(random) => random.choose(values).clone(),
+                None => values[0].clone(),
+            };
+        }
+        if let Some(all_of) = &schema.all_of {
+            return self.all_of(schema, all_of, depth);
+        }
+        if let Some(schemas) = schema
+            .one_of
+            .as_ref()
+            .or(schema.any_of.as_ref())
+            .filter(|schemas| !schemas.is_empty())
+        {
+            return self.one_of(schema, schemas, depth);
+        }
+        match &schema._type {
+            Some(Type::String) => Value::String(self.string(schema)),
+            Some(Type::Integer) => self.number(schema, true),
+            Some(Type::Number) => self.number(schema, false),
+            Some(Type::Boolean) => Value::Bool(match &mut self.random {
+                Some(random) => random.next() % 2 == 0,
+                None => true,
+            }),
+            Some(Type::Array) => self.array(schema, depth),
+            Some(Type::Null) => Value::Null,
+            Some(Type::Object) | None => self.object(schema, depth).unwrap_or(Value::Null),
+            Some(Type::Other(_)) => Value::Null,
+        }
+    }
+
+    /// Merges the objects sampled from the schemas of `allOf` and the schema itself. When they
+    /// are not objects, the first sample is used.
+    fn all_of(
+        &mut self,
+        schema: &'a Schema,
+        all_of: &'a [Referenceable<Schema>],
+        depth: usize,
+    ) -> Value {
+        let mut samples = all_of
+            .iter()
+            .map(|schema| self.referenceable(schema, depth + 1))
+            .collect::<Vec<_>>();
+        samples.extend(self.object(schema, depth));
+        let mut merged = Map::new();
+        let mut first = None;
+        for sample in samples {
+            match sample {
+                Value::Object(object) => merged.extend(object),
+                Value::Null => {}
+                sample => {
+                    first.get_or_insert(sample);
+                }
+            }
+        }
+        match first {
+            Some(sample) if merged.is_empty() => sample,
+            _ => Value::Object(merged),
+        }
+    }
+
+    /// Samples one of the schemas, and sets the discriminator property to the name of the
+    /// selected schema.
+    fn one_of(
+        &mut self,
+        schema: &'a Schema,
+        schemas: &'a [Referenceable<Schema>],
+        depth: usize,
+    ) -> Value {
+        let selected = match &mut self.random {
+            Some(random) => random.choose(schemas),
+            None => &schemas[0],
+        };
+        let mut sample = self.referenceable(selected, depth + 1);
+        if let (Some(discriminator), Referenceable::Reference(reference), Value::Object(object)) =
+            (&schema.discriminator, selected, &mut sample)
+        {
+            let name = reference.component_name("schemas");
+            let value = discriminator
+                .mapping
+                .iter()
+                .flatten()
+                .find(|(_, target)| {
+                    **target == reference._ref || Some(target.as_str()) == name.as_deref()
+                })
+                .map(|(value, _)| value.clone())
+                .or(name);
+            if let Some(value) = value {
+                object.insert(discriminator.property_name.clone(), Value::String(value));
+            }
+        }
+        sample
+    }
+
+    fn string(&mut self, schema: &Schema) -> String {
+        let Some(random) = &mut self.random else {
+            let value = match &schema.format {
+                Some(Format::DateTime) => "2024-01-01T12:00:00Z",
+                Some(Format::Date) => "2024-01-01",
+                Some(Format::Time) => "12:00:00",
+                Some(Format::Duration) => "P1D",
+                Some(Format::Email) | Some(Format::IdnEmail) => "user@example.com",
+                Some(Format::Hostname) | Some(Format::IdnHostname) => "example.com",
+                Some(Format::Ipv4) => "192.0.2.1",
+                Some(Format::Ipv6) => "2001:db8::1",
+                Some(Format::Uri) | Some(Format::Iri) => "https://example.com/",
+                Some(Format::UriReference) | Some(Format::IriReference) => "/example",
+                Some(Format::Uuid) => "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                Some(Format::UriTemplate) => "https://example.com/{id}",
+                Some(Format::JsonPointer) => "/example",
+                Some(Format::RelativeJsonPointer) => "0/example",
+                Some(Format::Regex) => "^.*$",
+                Some(Format::Other(format)) if format == "byte" => "c3RyaW5n",
+                _ => return fit("string", schema),
+            };
+            return value.to_string();
+        };
+        let word = *random.choose(WORDS);
+        match &schema.format {
+            Some(Format::DateTime) => {
+                // between 2000-01-01 and 2030-01-01
+                let seconds = random.between(946_684_800, 1_893_456_000);
+                let time = seconds.rem_euclid(86_400);
+                format!(
+                    "{}T{:02}:{:02}:{:02}Z",
+                    date(seconds.div_euclid(86_400)),
+                    time / 3600,
+                    time / 60 % 60,
+                    time % 60
+                )
+            }
+            Some(Format::Date) => date(random.between(10_957, 21_915)),
+            Some(Format::Time) => {
+                let time = random.between(0, 86_399);
+                format!("{:02}:{:02}:{:02}", time / 3600, time / 60 % 60, time % 60)
+            }
+            Some(Format::Duration) => format!("PT{}M", random.between(1, 120)),
+            Some(Format::Email) | Some(Format::IdnEmail) => {
+                format!("{word}{}@example.com", random.between(1, 999))
+            }
+            Some(Format::Hostname) | Some(Format::IdnHostname) => format!("{word}.example.com"),
+            Some(Format::Ipv4) => format!(
+                "{}.{}.{}.{}",
+                random.between(1, 223),
+                random.between(0, 255),
+                random.between(0, 255),
+                random.between(1, 254)
+            ),
+            Some(Format::Ipv6) => (0..8)
+                .map(|_| format!("{:x}", random.between(0, 0xffff)))
+                .collect::<Vec<_>>()
+                .join(":"),
+            Some(Format::Uri) | Some(Format::Iri) => format!("https://{word}.example.com/"),
+            Some(Format::UriReference) | Some(Format::IriReference) => format!("/{word}"),
+            Some(Format::Uuid) => {
+                let high = random.next() & 0xffff_ffff_ffff_0fff | 0x4000;
+                let low = random.next() & 0x3fff_ffff_ffff_ffff | 0x8000_0000_0000_0000;
+                format!(
+                    "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+                    high >> 32,
+                    high >> 16 & 0xffff,
+                    high & 0xffff,
+                    low >> 48,
+                    low & 0xffff_ffff_ffff
+                )
+            }
+            Some(Format::JsonPointer) => format!("/{word}"),
+            Some(Format::RelativeJsonPointer) => format!("0/{word}"),
+            Some(Format::Regex) => format!("^{word}$"),
+            _ => {
+                let min = schema.min_length.unwrap_or(1).max(1);
+                let max = schema.max_length.unwrap_or(min + 9).min(min + 9);
+                let length = random.between(min as i64, max as i64) as usize;
+                let mut text = String::new();
+                while text.len() < length {
+                    let word = *random.choose(WORDS);
+                    text.push_str(word);
+                }
+                text.truncate(length);
+                fit(&text, schema)
+            }
+        }
+    }
+
+    /// A number within `minimum` and `maximum`, and a multiple of `multipleOf`. Without a seed,
+    /// it is the closest valid number to zero.
+    fn number(&mut self, schema: &Schema, integer: bool) -> Value {
+        let step = schema
+            .multiple_of
+            .as_ref()
+            .and_then(Number::as_f64)
+            .filter(|step| *step > 0.0);
+        let step = match step {
+            // the least multiple of the step which is an integer, e.g. 5 for 2.5
+            Some(step) if integer => {
+                let Some(multiple) = (1..=1000)
+                    .map(|count| count as f64 * step)
+                    .find(|multiple| (multiple - multiple.round()).abs() < 1e-9 * multiple)
+                else {
+                    // no integer is a multiple of the step
+                    return Value::Null;
+                };
+                multiple.round()
+            }
+            Some(step) => step,
+            None if integer => 1.0,
+            None => 0.25,
+        };
+        let (mut low, mut high) = match schema.format {
+            Some(Format::Int32) => (i32::MIN as f64, i32::MAX as f64),
+            _ => (-1e15, 1e15),
+        };
+        if let Some(minimum) = schema.minimum.as_ref().and_then(Number::as_f64) {
+            let bound = (minimum / step).ceil();
+            let exclusive = schema.exclusive_minimum.unwrap_or(false) && bound * step == minimum;
+            low = low.max(if exclusive { bound + 1.0 } else { bound } * step);
+        }
+        if let Some(maximum) = schema.maximum.as_ref().and_then(Number::as_f64) {
+            let bound = (maximum / step).floor();
+            let exclusive = schema.exclusive_maximum.unwrap_or(false) && bound * step == maximum;
+            high = high.min(if exclusive { bound - 1.0 } else { bound } * step);
+        }
+        let (low, high) = ((low / step).ceil() as i64, (high / step).floor() as i64);
+        let multiple = match &mut self.random {
+            _ if low > high => low,
+            Some(random) => {
+                // a thousand steps around zero, or from the nearest bound
+                let start = 0.clamp(low, high.saturating_sub(1000).max(low));
+                random.between(start, high.min(start.saturating_add(1000)))
+            }
+            None => 0.clamp(low, high),
+        };
+        let value = multiple as f64 * step;
+        match integer {
+            true => Value::from(value as i64),
+            false => Number::from_f64(value).map_or(Value::Null, Value::Number),
+        }
+    }
+
+    /// An array with `minItems` items, and at least one if `maxItems` allows it. Items are
+    /// regenerated with other seeds when they must be unique, and left out if they cannot be.
+    fn array(&mut self, schema: &'a Schema, depth: usize) -> Value {
+        let Some(items) = &schema.items else {
+            return Value::Array(vec![]);
+        };
+        let min = schema.min_items.unwrap_or(0) as i64;
+        let max = schema.max_items.map_or(i64::MAX, |max| max as i64);
+        let count = match &mut self.random {
+            _ if depth + 1 >= self.sampler.max_depth => min,
+            Some(random) => random.between(min.max(1), min.max(1) + 2),
+            None => min.max(1),
+        }
+        .min(max);
+        let unique = schema.unique_items.unwrap_or(false);
+        let mut values = Vec::new();
+        for index in 0..count as u64 {
+            let mut value = self.referenceable(items, depth + 1);
+            for attempt in 0..16 {
+                if !unique || !values.contains(&value) {
+                    break;
+                }
+                let seed = match &mut self.random {
+                    Some(random) => random.next(),
+                    None => index * 16 + attempt,
+                };
+                let mut generator = Generator {
+                    sampler: self.sampler,
+                    components: self.components,
+                    random: Some(Random(seed)),
+                };
+                value = generator.referenceable(items, depth + 1);
+            }
+            // the items may have fewer distinct values than the count, e.g. a small enum
+            if !unique || !values.contains(&value) || values.len() < min as usize {
+                values.push(value);
+            }
+        }
+        Value::Array(values)
+    }
+
+    /// The object of the own `properties` of the schema, if it has properties or is an object.
+    fn object(&mut self, schema: &'a Schema, depth: usize) -> Option<Value> {
+        if schema.properties.is_none() && schema._type != Some(Type::Object) {
+            return None;
+        }
+        let required = schema.required.as_deref().unwrap_or_default();
+        let optional = self.sampler.optional_properties && depth + 1 < self.sampler.max_depth;
+        let mut object = Map::new();
+        for (name, property) in schema.properties.iter().flatten() {
+            if optional || required.contains(name) {
+                object.insert(name.clone(), self.referenceable(property, depth + 1));
+            }
+        }
+        let min = schema.min_properties.unwrap_or(0) as usize;
+        let additional = match &schema.additional_properties {
+            Some(AdditionalProperties::Allowed(false)) => None,
+            Some(AdditionalProperties::Schema(schema)) => Some(Some(schema.as_ref())),
+            _ => Some(None),
+        };
+        if let Some(additional) = additional {
+            let mut index = 1;
+            while object.len() < min {
+                let value = match additional {
+                    Some(schema) => self.referenceable(schema, depth + 1),
+                    None => Value::String("value".to_string()),
+                };
+                object.insert(format!("additionalProperty{index}"), value);
+                index += 1;
+            }
+        }
+        Some(Value::Object(object))
+    }
+}
+
+/// Repeats or truncates the text to fit within `minLength` and `maxLength`.
+fn fit(text: &str, schema: &Schema) -> String {
+    let min = schema.min_length.unwrap_or(0) as usize;
+    let max = schema.max_length.map_or(usize::MAX, |max| max as usize);
+    let length = text.chars().count().max(min).min(max);
+    text.chars().cycle().take(length).collect()
+}
+
+/// The date of a number of days since 1970-01-01, using the `civil_from_days` algorithm of
+/// Howard Hinnant.
+fn date(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -1,17 +1,136 @@
 use crate::Any;
-use crate::{Format, Type};
+use crate::{Components, Discriminator, ExternalDocumentation, Format, Map, Referenceable, Type};
 use serde::{Deserialize, Serialize};
+use serde_json::Number;
 use serde_with::skip_serializing_none;
-use std::collections::BTreeMap;
 
 /// The Schema Object allows the definition of input and output data types. These types can be objects, but also primitives and arrays.
 #[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Schema {
     #[serde(rename = "type")]
     pub _type: Option<Type>,
+    /// See [Data Type Formats](https://spec.openapis.org/oas/v3.0.3#dataTypeFormat) for further details. While relying on JSON Schema's defined formats, the OAS offers a few additional predefined formats.
     pub format: Option<Format>,
+    /// A true value adds `null` to the allowed types of the schema. Default value is `false`.
     pub nullable: Option<bool>,
+    pub title: Option<String>,
+    /// CommonMark syntax MAY be used for rich text representation.
+    pub description: Option<String>,
+    pub multiple_of: Option<Number>,
+    pub maximum: Option<Number>,
+    pub exclusive_maximum: Option<bool>,
+    pub minimum: Option<Number>,
+    pub exclusive_minimum: Option<bool>,
+    pub max_length: Option<u64>,
+    pub min_length: Option<u64>,
+    /// A regular expression, according to the ECMA 262 regular expression dialect.
+    pub pattern: Option<String>,
+    pub max_items: Option<u64>,
+    pub min_items: Option<u64>,
+    pub unique_items: Option<bool>,
+    pub max_properties: Option<u64>,
+    pub min_properties: Option<u64>,
+    pub required: Option<Vec<String>>,
+    #[serde(rename = "enum")]
+    pub _enum: Option<Vec<Any>>,
+    pub all_of: Option<Vec<Referenceable<Schema>>>,
+    pub one_of: Option<Vec<Referenceable<Schema>>>,
+    pub any_of: Option<Vec<Referenceable<Schema>>>,
+    pub not: Option<Box<Referenceable<Schema>>>,
+    /// MUST be present if the `type` is `array`.
+    pub items: Option<Box<Referenceable<Schema>>>,
+    pub properties: Option<Map<String, Referenceable<Schema>>>,
+    /// Whether properties other than the ones listed in `properties` are allowed, or the schema they must conform to.
+    pub additional_properties: Option<AdditionalProperties>,
+    /// The default value represents what would be assumed by the consumer of the input as the value of the schema if one is not provided.
+    pub default: Option<Any>,
+    /// Adds support for polymorphism. The discriminator is an object name that is used to differentiate between other schemas which may satisfy the payload description.
+    pub discriminator: Option<Discriminator>,
+    /// Declares the property as "read only". This means that it MAY be sent as part of a response but SHOULD NOT be sent as part of the request.
+    pub read_only: Option<bool>,
+    /// Declares the property as "write only". Therefore, it MAY be sent as part of a request but SHOULD NOT be sent as part of the response.
+    pub write_only: Option<bool>,
+    /// This MAY be used only on properties schemas. It has no effect on root schemas. Adds additional metadata to describe the XML representation of this property.
+    pub xml: Option<Xml>,
+    /// Additional external documentation for this schema.
+    pub external_docs: Option<ExternalDocumentation>,
+    /// A free-form property to include an example of an instance for this schema.
+    pub example: Option<Any>,
+    /// Specifies that a schema is deprecated and SHOULD be transitioned out of usage. Default value is `false`.
+    pub deprecated: Option<bool>,
+    #[serde(flatten)]
+    pub extras: Map<String, Any>,
+}
+
+/// The value of `additionalProperties`, which is either a boolean or a schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AdditionalProperties {
+    Allowed(bool),
+    Schema(Box<Referenceable<Schema>>),
+}
+
+/// A metadata object that allows for more fine-tuned XML model definitions.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Xml {
+    /// Replaces the name of the element/attribute used for the described schema property.
+    pub name: Option<String>,
+    /// The URI of the namespace definition.
+    pub namespace: Option<String>,
+    /// The prefix to be used for the name.
+    pub prefix: Option<String>,
+    /// Declares whether the property definition translates to an attribute instead of an element. Default value is `false`.
+    pub attribute: Option<bool>,
+    /// MAY be used only for an array definition. Signifies whether the array is wrapped. Default value is `false`.
+    pub wrapped: Option<bool>,
     #[serde(flatten)]
-    pub extras: BTreeMap<String, Any>,
+    pub extras: Option<Map<String, Any>>,
+}
+
+impl Schema {
+    /// Whether `null` is an allowed value.
+    pub fn is_nullable(&self) -> bool {
+        self.nullable.unwrap_or(false) || self._type == Some(Type::Null)
+    }
+
+    /// The schema of a property, looking into `properties` and then into the schemas of `allOf`.
+    pub fn property<'a>(
+        &'a self,
+        name: &str,
+        components: Option<&'a Components>,
+    ) -> Option<&'a Referenceable<Schema>> {
+        self.all_properties(components).get(name).copied()
+    }
+
+    /// The properties of the schema along with the ones of the schemas of `allOf`. When a
+    /// property is declared more than once, the first declaration is kept.
+    pub fn all_properties<'a>(
+        &'a self,
+        components: Option<&'a Components>,
+    ) -> Map<&'a str, &'a Referenceable<Schema>> {
+        let mut properties = Map::new();
+        self.collect_properties(components, &mut properties, 0);
+        properties
+    }
+
+    fn collect_properties<'a>(
+        &'a self,
+        components: Option<&'a Components>,
+        properties: &mut Map<&'a str, &'a Referenceable<Schema>>,
+        depth: usize,
+    ) {
+        for (name, property) in self.properties.iter().flatten() {
+            properties.entry(name.as_str()).or_insert(property);
+        }
+        if depth < 32 {
+            for schema in self.all_of.iter().flatten() {
+                if let Some(schema) = schema.resolve(components) {
+                    schema.collect_properties(components, properties, depth + 1);
+                }
+            }
+        }
+    }
 }
@@ -1,6 +1,4 @@
-use std::collections::BTreeMap;
-
-use crate::{ParameterIn, Parseable};
+use crate::{Map, ParameterIn, Parseable};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use url::Url;
@@ -27,7 +25,7 @@ pub enum SecurityType {
     },
     Oauth2 {
         /// An object containing configuration information for the flow types supported.
-        flows: OauthFlows,
+        flows: Box<OauthFlows>,
     },
     OpenIdConnect {
         /// OpenId Connect URL to discover OAuth2 configuration values. This MUST be in the form of a URL.
@@ -93,7 +91,7 @@ pub struct OauthFlow {
     /// The URL to be used for obtaining refresh tokens. This MUST be in the form of a URL.
     pub refresh_url: Option<Parseable<Url>>,
     /// The available scopes for the OAuth2 security scheme. A map between the scope name and a short description for it. The map MAY be empty.
-    pub scopes: BTreeMap<String, String>,
+    pub scopes: Map<String, String>,
 }
 
 /// Lists the required security schemes to execute this operation.
@@ -102,5 +100,5 @@ pub struct OauthFlow {
 #[serde(transparent)]
 pub struct SecurityRequirement {
     #[serde(flatten)]
-    pub data: BTreeMap<String, Vec<String>>,
+    pub data: Map<String, Vec<String>>,
 }
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use url::Url;
 
-use crate::{Any, Parseable};
+use crate::{Any, Map, Parseable};
 
 /// An object representing a Server.
 #[skip_serializing_none]
@@ -15,9 +15,9 @@ pub struct Server {
     /// An optional string describing the host designated by the URL. CommonMark syntax MAY be used for rich text representation.
     pub description: Option<String>,
     /// A map between a variable name and its value. The value is used for substitution in the server's URL template.
-    pub variables: Option<BTreeMap<String, ServerVariable>>,
+    pub variables: Option<Map<String, ServerVariable>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// An object representing a Server Variable for server URL template substitution.
@@ -32,5 +32,43 @@ pub struct ServerVariable {
     /// An optional description for the server variable. CommonMark syntax MAY be used for rich text representation.
     pub description: Option<String>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
+}
+
+impl Server {
+    /// The URL template as written in the document.
+    pub fn url_template(&self) -> &str {
+        match &self.url {
+            Parseable::Valid(url) => url.as_str(),
+            Parseable::Invalid(url) => url,
+        }
+    }
+
+    /// The URL with its variables substituted, using the given values and falling back to the
+    /// `default` of each [ServerVariable].
+    pub fn url_with(&self, values: &BTreeMap<String, String>) -> String {
+        let mut url = self.url_template().to_string();
+        for (name, variable) in self.variables.iter().flatten() {
+            let value = values.get(name).unwrap_or(&variable.default);
+            url = url.replace(&format!("{{{name}}}"), value);
+        }
+        url
+    }
+
+    /// The path of the URL with the default variables, without a trailing slash, e.g. `/v1` for
+    /// `https://{host}/v1/`. Relative URLs are supported.
+    pub fn base_path(&self) -> String {
+        let url = self.url_with(&BTreeMap::new());
+        let path = match Url::parse(&url) {
+            Ok(url) => url.path().to_string(),
+            Err(_) => match url.find("//") {
+                // a URL whose host is still a template, or which has no scheme
+                Some(start) => url[start + 2..]
+                    .find('/')
+                    .map_or(String::new(), |end| url[start + 2 + end..].to_string()),
+                None => url,
+            },
+        };
+        path.trim_end_matches('/').to_string()
+    }
 }
@@ -1,11 +1,17 @@
 use crate::parameter::Style;
 use crate::Any;
+use crate::Map;
+use crate::MediaRange;
+use crate::MediaRangeList;
 use crate::PathItem;
 use crate::Schema;
 use crate::Server;
-use serde::{Deserialize, Serialize};
+use crate::StatusCode;
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_with::skip_serializing_none;
-use std::collections::BTreeMap;
+use std::fmt;
 use url::Url;
 
 /// A value that represents a more specific type than the general type that it
@@ -33,8 +39,8 @@ impl<V> Parseable<V> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Referenceable<T> {
-    Data(T),
     Reference(Reference),
+    Data(T),
 }
 
 impl<T> Referenceable<T> {
@@ -55,7 +61,7 @@ pub struct ExternalDocumentation {
     /// The URL for the target documentation. Value MUST be in the format of a URL.
     pub url: Parseable<Url>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// Describes a single request body.
@@ -67,9 +73,9 @@ pub struct RequestBody {
     /// Determines if the request body is required in the request. Defaults to `false`.
     pub required: Option<bool>,
     /// The content of the request body.
-    pub content: BTreeMap<String, MediaType>,
+    pub content: Map<MediaRange, MediaType>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// Each Media Type Object provides schema and examples for the media type identified by its key.
@@ -81,11 +87,11 @@ pub struct MediaType {
     /// Example of the media type.
     pub example: Option<Any>,
     /// Examples of the media type.
-    pub examples: Option<BTreeMap<String, Referenceable<Example>>>,
+    pub examples: Option<Map<String, Referenceable<Example>>>,
     /// A map between a property name and its encoding information.
-    pub encoding: Option<BTreeMap<String, Encoding>>,
+    pub encoding: Option<Map<String, Encoding>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// A single encoding definition applied to a single schema property.
@@ -93,31 +99,96 @@ pub struct MediaType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Encoding {
-    /// The Content-Type for encoding a specific property.
-    pub content_type: Option<Parseable<MediaType>>,
+    /// The Content-Type for encoding a specific property. The value can be a specific media type (e.g. `application/json`), a wildcard media type (e.g. `image/*`), or a comma-separated list of the two types.
+    pub content_type: Option<Parseable<MediaRangeList>>,
     /// map allowing additional information to be provided as headers, for example `Content-Disposition`. `Content-Type` is described separately and SHALL be ignored in this section. This property SHALL be ignored if the request body media type is not a `multipart`.
-    pub headers: Option<BTreeMap<String, Referenceable<Header>>>,
+    pub headers: Option<Map<String, Referenceable<Header>>>,
     /// Describes how a specific property value will be serialized depending on its type.
     pub style: Option<Style>,
     pub explode: Option<bool>,
     pub allow_reserved: Option<bool>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// A container for the expected responses of an operation. The container maps a HTTP response code to the expected response.
 /// The documentation is not necessarily expected to cover all possible HTTP response codes because they may not be known in advance. However, documentation is expected to cover a successful operation response and any known errors.
 /// The default MAY be used as a default response object for all HTTP codes that are not covered individually by the specification.
 /// The Responses Object MUST contain at least one response code, and it SHOULD be the response for a successful operation call.
-#[skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct Responses {
     /// The documentation of responses other than the ones declared for specific HTTP response codes. Use this field to cover undeclared responses. A Reference Object can link to a response that the OpenAPI Object's components/responses section defines.
     pub default: Option<Referenceable<Response>>,
-    #[serde(flatten)]
-    pub data: BTreeMap<String, Referenceable<Response>>,
-    #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    /// The expected responses keyed by HTTP status code or status code range.
+    pub data: Map<StatusCode, Referenceable<Response>>,
+    /// Specification extensions, i.e. the keys starting with `x-`.
+    pub extras: Option<Map<String, Any>>,
+}
+
+impl Responses {
+    /// Finds the response documented for the given status code. A specific code takes
+    /// precedence over a range such as `2XX`, which in turn takes precedence over `default`.
+    /// A status outside `100..=599` is not documented by any of them.
+    pub fn for_status(&self, status: u16) -> Option<&Referenceable<Response>> {
+        let code = StatusCode::code(status)?;
+        self.data
+            .get(&code)
+            .or_else(|| self.data.get(&StatusCode::Range(code.class())))
+            .or(self.default.as_ref())
+    }
+}
+
+impl Serialize for Responses {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(default) = &self.default {
+            map.serialize_entry("default", default)?;
+        }
+        for (code, response) in &self.data {
+            map.serialize_entry(code, response)?;
+        }
+        for (key, value) in self.extras.iter().flatten() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Responses {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ResponsesVisitor;
+
+        impl<'de> Visitor<'de> for ResponsesVisitor {
+            type Value = Responses;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of status codes to responses")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Responses, A::Error> {
+                let mut default = None;
+                let mut data = Map::new();
+                let mut extras = Map::new();
+                while let Some(key) = access.next_key::<String>()? {
+                    if key == "default" {
+                        default = Some(access.next_value()?);
+                    } else if key.starts_with("x-") {
+                        extras.insert(key, access.next_value()?);
+                    } else {
+                        let code = key.parse::<StatusCode>().map_err(de::Error::custom)?;
+                        data.insert(code, access.next_value()?);
+                    }
+                }
+                Ok(Responses {
+                    default,
+                    data,
+                    extras: (!extras.is_empty()).then_some(extras),
+                })
+            }
+        }
+
+        deserializer.deserialize_map(ResponsesVisitor)
+    }
 }
 
 /// Describes a single response from an API Operation, including design-time, static `links` to operations based on the response.
@@ -127,13 +198,13 @@ pub struct Response {
     /// A short description of the response.
     pub description: String,
     /// Maps a header name to its definition.
-    pub headers: Option<BTreeMap<String, Referenceable<Header>>>,
+    pub headers: Option<Map<String, Referenceable<Header>>>,
     /// A map containing descriptions of potential response payloads.
-    pub content: Option<BTreeMap<String, MediaType>>,
+    pub content: Option<Map<MediaRange, MediaType>>,
     /// A map of operations links that can be followed from the response.
-    pub links: Option<BTreeMap<String, Referenceable<Link>>>,
+    pub links: Option<Map<String, Referenceable<Link>>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// A map of possible out-of band callbacks related to the parent operation.
@@ -145,9 +216,9 @@ pub struct Response {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Callback {
     #[serde(flatten)]
-    pub data: BTreeMap<String, PathItem>,
+    pub data: Map<String, PathItem>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 #[skip_serializing_none]
@@ -162,7 +233,7 @@ pub struct Example {
     pub value: Option<Any>,
     pub external_value: Option<String>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// represents a possible design-time link for a response.
@@ -175,7 +246,7 @@ pub struct Link {
     /// The name of an existing, resolvable OAS operation
     pub operation_id: String,
     /// A map representing parameters to pass to an operation as specified with `operation_id` or identified via `operation_ef`.
-    pub parameters: Option<BTreeMap<String, Any>>,
+    pub parameters: Option<Map<String, Any>>,
     /// A literal value or `{expression}` to use as a request body when calling the target operation.
     pub request_body: Option<Any>,
     /// A description of the link.
@@ -183,7 +254,7 @@ pub struct Link {
     /// A server object to be used by the target operation.
     pub server: Option<Server>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 #[skip_serializing_none]
@@ -199,10 +270,10 @@ pub struct Header {
     pub allow_reserved: Option<bool>,
     pub schema: Option<Referenceable<Schema>>,
     pub example: Option<Any>,
-    pub examples: Option<BTreeMap<String, Referenceable<Example>>>,
-    pub content: Option<BTreeMap<String, MediaType>>,
+    pub examples: Option<Map<String, Referenceable<Example>>>,
+    pub content: Option<Map<MediaRange, MediaType>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 /// Adds metadata to a single tag that is used by the `Operation` Object. It is not mandatory to have a Tag Object per tag defined in the Operation Object instances.
@@ -217,7 +288,7 @@ pub struct Tag {
     /// Additional external documentation for this tag.
     pub external_docs: Option<ExternalDocumentation>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
 }
 
 impl Tag {
@@ -299,7 +370,28 @@ pub struct Discriminator {
     /// The name of the property in the payload that will hold the discriminator value.
     pub property_name: String,
     /// An object to hold mappings between payload values and schema names or references.
-    pub mapping: Option<BTreeMap<String, String>>,
+    pub mapping: Option<Map<String, String>>,
     #[serde(flatten)]
-    pub extras: Option<BTreeMap<String, Any>>,
+    pub extras: Option<Map<String, Any>>,
+}
+
+impl Discriminator {
+    /// The schema for a discriminator value: the entry of `mapping` if there is one, which is
+    /// either a schema name or a reference, and otherwise the schema of `components` with the
+    /// same name as the value.
+    pub fn schema_for(&self, value: &str) -> Referenceable<Schema> {
+        let target = self
+            .mapping
+            .as_ref()
+            .and_then(|mapping| mapping.get(value))
+            .map(String::as_str)
+            .unwrap_or(value);
+        let reference = match target.contains('/') {
+            true => Reference {
+                _ref: target.to_string(),
+            },
+            false => Reference::component::<Schema>(target),
+        };
+        Referenceable::Reference(reference)
+    }
 }
@@ -0,0 +1,695 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::IgnoredAny;
+
+use crate::{pointer, OpenAPIV3};
+
+/// A place in a text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The line, starting at 1.
+    pub line: usize,
+    /// The column in characters, starting at 1.
+    pub column: usize,
+    /// The offset in bytes from the start of the text.
+    pub offset: usize,
+}
+
+/// The part of a text where a value is written, from its first character to right after its last
+/// one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// The start of the span, e.g. `12:5`.
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start.line, self.start.column)
+    }
+}
+
+/// The spans of the values of a JSON or YAML text by JSON Pointer, to show where the pointers of
+/// diagnostics, like [ExampleError](crate::ExampleError), [LintIssue](crate::LintIssue) or
+/// [Change](crate::Change), are in a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    values: BTreeMap<String, Span>,
+    keys: BTreeMap<String, Span>,
+}
+
+impl SourceMap {
+    /// Records the spans of the values of a JSON text, failing if it is not valid JSON.
+    pub fn from_json(text: &str) -> Result<SourceMap, serde_json::Error> {
+        serde_json::from_str::<IgnoredAny>(text)?;
+        let mut scanner = Scanner {
+            cursor: Cursor::new(text),
+            map: SourceMap::default(),
+        };
+        scanner.value(String::new());
+        Ok(scanner.map)
+    }
+
+    /// Records the spans of the values of the first document of a YAML text.
+    ///
+    /// The text is not checked, as the crate does not parse YAML: the spans of an invalid text
+    /// are a best effort. Block and flow collections, plain, quoted and block scalars, comments,
+    /// anchors and tags are supported, the spans starting after the anchors and tags, while
+    /// aliases are recorded as scalars and complex keys are not supported.
+    pub fn from_yaml(text: &str) -> SourceMap {
+        let mut scanner = YamlScanner {
+            cursor: Cursor::new(text),
+            map: SourceMap::default(),
+            end: Cursor::new(text).position(),
+        };
+        scanner.document();
+        scanner.map
+    }
+
+    /// The span of the value a pointer points to.
+    pub fn span(&self, pointer: &str) -> Option<Span> {
+        self.values.get(pointer).copied()
+    }
+
+    /// The span of the key of the value a pointer points to, when its parent is an object.
+    pub fn key_span(&self, pointer: &str) -> Option<Span> {
+        self.keys.get(pointer).copied()
+    }
+
+    /// The span of the value a pointer points to, or of its closest ancestor when it does not
+    /// exist in the text, e.g. for a missing required property. Returns the pointer of the value
+    /// found along with its span.
+    pub fn nearest<'a>(&self, mut pointer: &'a str) -> Option<(&'a str, Span)> {
+        loop {
+            if let Some(span) = self.span(pointer) {
+                return Some((pointer, span));
+            }
+            pointer = &pointer[..pointer.rfind('/')?];
+        }
+    }
+}
+
+impl OpenAPIV3 {
+    /// Parses a JSON document along with the spans of its values.
+    pub fn from_json_with_source_map(
+        text: &str,
+    ) -> Result<(OpenAPIV3, SourceMap), serde_json::Error> {
+        let spec = serde_json::from_str(text)?;
+        Ok((spec, SourceMap::from_json(text)?))
+    }
+}
+
+/// A place in a text which keeps track of its line and column as it moves forward.
+#[derive(Clone)]
+struct Cursor<'a> {
+    text: &'a str,
+    offset: usize,
+    line: usize,
+    /// The offset of the start of the current line.
+    line_start: usize,
+    /// The offset up to which the characters of the current line are counted in `column`, so
+    /// that columns are not counted from the start of the line every time.
+    counted: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Cursor<'a> {
+        Cursor {
+            text,
+            offset: 0,
+            line: 1,
+            line_start: 0,
+            counted: 0,
+            column: 1,
+        }
+    }
+
+    fn position(&mut self) -> Position {
+        if self.counted < self.line_start {
+            self.counted = self.line_start;
+            self.column = 1;
+        }
+        self.column += self.text[self.counted..self.offset].chars().count();
+        self.counted = self.offset;
+        Position {
+            line: self.line,
+            column: self.column,
+            offset: self.offset,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.offset..]
+    }
+
+    /// Moves forward by a number of bytes, which must end on a character boundary.
+    fn advance(&mut self, bytes: usize) {
+        let end = (self.offset + bytes).min(self.text.len());
+        for (index, byte) in self.text.as_bytes()[self.offset..end].iter().enumerate() {
+            if *byte == b'\n' {
+                self.line += 1;
+                self.line_start = self.offset + index + 1;
+            }
+        }
+        self.offset = end;
+    }
+
+    /// Moves to the end of the current line, before its line break.
+    fn skip_line(&mut self) {
+        let length = self.rest().find('\n').unwrap_or(self.rest().len());
+        self.advance(length);
+    }
+
+    /// The offset from the start of the line, which is the indentation at the start of a line.
+    fn indent(&self) -> usize {
+        self.offset - self.line_start
+    }
+}
+
+/// Walks a valid JSON text.
+struct Scanner<'a> {
+    cursor: Cursor<'a>,
+    map: SourceMap,
+}
+
+impl Scanner<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\r' | b'\n') = self.cursor.peek() {
+            self.cursor.advance(1);
+        }
+    }
+
+    fn value(&mut self, pointer: String) {
+        self.skip_whitespace();
+        let start = self.cursor.position();
+        match self.cursor.peek() {
+            Some(b'{') => self.object(&pointer),
+            Some(b'[') => self.array(&pointer),
+            Some(b'"') => {
+                self.string();
+            }
+            _ => {
+                while self
+                    .cursor
+                    .peek()
+                    .is_some_and(|byte| !b",]} \t\r\n".contains(&byte))
+                {
+                    self.cursor.advance(1);
+                }
+            }
+        }
+        let end = self.cursor.position();
+        self.map.values.insert(pointer, Span { start, end });
+    }
+
+    fn object(&mut self, pointer: &str) {
+        self.cursor.advance(1);
+        loop {
+            self.skip_whitespace();
+            match self.cursor.peek() {
+                Some(b'}') => break,
+                Some(b',') => {
+                    self.cursor.advance(1);
+                    continue;
+                }
+                _ => {}
+            }
+            let start = self.cursor.position();
+            let key = self.string();
+            let child = pointer::join(pointer, key);
+            let end = self.cursor.position();
+            self.map.keys.insert(child.clone(), Span { start, end });
+            self.skip_whitespace();
+            // the colon
+            self.cursor.advance(1);
+            self.value(child);
+        }
+        self.cursor.advance(1);
+    }
+
+    fn array(&mut self, pointer: &str) {
+        self.cursor.advance(1);
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            match self.cursor.peek() {
+                Some(b']') => break,
+                Some(b',') => {
+                    self.cursor.advance(1);
+                    continue;
+                }
+                _ => {}
+            }
+            self.value(pointer::join(pointer, index.to_string()));
+            index += 1;
+        }
+        self.cursor.advance(1);
+    }
+
+    /// Reads a string, which cannot span several lines in JSON.
+    fn string(&mut self) -> String {
+        let start = self.cursor.offset;
+        self.cursor.advance(1);
+        while let Some(byte) = self.cursor.peek() {
+            self.cursor.advance(1);
+            match byte {
+                b'\\' => self.cursor.advance(1),
+                b'"' => break,
+                _ => {}
+            }
+        }
+        let text = &self.cursor.text[start..self.cursor.offset];
+        serde_json::from_str(text).expect("the text is valid JSON")
+    }
+}
+
+/// Walks a YAML text, following the indentation of its block collections.
+struct YamlScanner<'a> {
+    cursor: Cursor<'a>,
+    map: SourceMap,
+    /// The end of the last value recorded, which ends the collections holding it.
+    end: Position,
+}
+
+impl YamlScanner<'_> {
+    fn record(&mut self, pointer: String, start: Position, end: Position) {
+        self.map.values.insert(pointer, Span { start, end });
+        self.end = end;
+    }
+
+    fn document(&mut self) {
+        self.skip_space();
+        while self.cursor.indent() == 0 && self.cursor.rest().starts_with('%') {
+            self.cursor.skip_line();
+            self.skip_space();
+        }
+        if self.marker("---") {
+            self.cursor.advance(3);
+        }
+        self.node(String::new(), -1);
+    }
+
+    /// Whether the text continues with a document marker, like `---` or `...`, at the start of
+    /// a line.
+    fn marker(&self, marker: &str) -> bool {
+        let rest = self.cursor.rest();
+        self.cursor.indent() == 0
+            && rest.starts_with(marker)
+            && rest.as_bytes()[marker.len()..]
+                .first()
+                .is_none_or(u8::is_ascii_whitespace)
+    }
+
+    fn at_end(&self) -> bool {
+        self.cursor.peek().is_none() || self.marker("---") || self.marker("...")
+    }
+
+    /// Skips the spaces and the comment of the current line.
+    fn skip_inline(&mut self) {
+        while let Some(b' ' | b'\t' | b'\r') = self.cursor.peek() {
+            self.cursor.advance(1);
+        }
+        if self.cursor.peek() == Some(b'#') {
+            self.cursor.skip_line();
+        }
+    }
+
+    /// Skips the spaces, comments and line breaks up to the next value.
+    fn skip_space(&mut self) {
+        loop {
+            self.skip_inline();
+            match self.cursor.peek() {
+                Some(b'\n') => self.cursor.advance(1),
+                _ => return,
+            }
+        }
+    }
+
+    /// Skips the anchors and tags of a value.
+    fn skip_properties(&mut self) {
+        while let Some(b'&' | b'!') = self.cursor.peek() {
+            while self
+                .cursor
+                .peek()
+                .is_some_and(|byte| !byte.is_ascii_whitespace())
+            {
+                self.cursor.advance(1);
+            }
+            self.skip_inline();
+        }
+    }
+
+    /// Whether the text continues with an entry of a block sequence.
+    fn at_entry(&self) -> bool {
+        let rest = self.cursor.rest().as_bytes();
+        rest.first() == Some(&b'-') && rest.get(1).is_none_or(u8::is_ascii_whitespace)
+    }
+
+    /// The length of the key of a block mapping the current line starts with, up to its colon.
+    fn key_length(&self) -> Option<usize> {
+        let line = self.cursor.rest();
+        let bytes = &line.as_bytes()[..line.find('\n').unwrap_or(line.len())];
+        let mut index = match bytes.first() {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let mut index = 1;
+                loop {
+                    match *bytes.get(index)? {
+                        b'\\' if quote == b'"' => index += 2,
+                        b'\'' if quote == b'\'' && bytes.get(index + 1) == Some(&b'\'') => {
+                            index += 2
+                        }
+                        byte if byte == quote => break index + 1,
+                        _ => index += 1,
+                    }
+                }
+            }
+            Some(b'[' | b'{' | b'#') | None => return None,
+            Some(_) => 0,
+        };
+        while index < bytes.len() {
+            match bytes[index] {
+                b':' if bytes.get(index + 1).is_none_or(u8::is_ascii_whitespace) => {
+                    return Some(index)
+                }
+                b'#' if index > 0 && bytes[index - 1].is_ascii_whitespace() => return None,
+                _ => index += 1,
+            }
+        }
+        None
+    }
+
+    /// A value more indented than its parent, if any.
+    fn node(&mut self, pointer: String, parent: isize) {
+        self.skip_space();
+        if self.at_end() || self.cursor.indent() as isize <= parent {
+            return;
+        }
+        self.skip_properties();
+        if self.cursor.peek() == Some(b'\n') {
+            // the properties are on their own line
+            return self.node(pointer, parent);
+        }
+        let indent = self.cursor.indent();
+        if self.at_entry() {
+            self.sequence(pointer, indent);
+        } else if self.key_length().is_some() {
+            self.mapping(pointer, indent);
+        } else {
+            self.inline(pointer, parent);
+        }
+    }
+
+    /// A value starting on the current line which is not a block collection.
+    fn inline(&mut self, pointer: String, parent: isize) {
+        let start = self.cursor.position();
+        match self.cursor.peek() {
+            Some(b'[' | b'{') => self.flow(pointer),
+            Some(b'|' | b'>') => self.block_scalar(pointer, start, parent),
+            Some(quote @ (b'"' | b'\'')) => {
+                self.quoted(quote);
+                let end = self.cursor.position();
+                self.record(pointer, start, end);
+            }
+            _ => {
+                let mut end = self.plain_line();
+                // the following lines of a plain scalar are more indented than its parent
+                loop {
+                    let line = self.cursor.clone();
+                    self.skip_space();
+                    if self.at_end()
+                        || self.cursor.indent() as isize <= parent
+                        || self.key_length().is_some()
+                    {
+                        self.cursor = line;
+                        break;
+                    }
+                    end = self.plain_line();
+                }
+                self.record(pointer, start, end);
+            }
+        }
+    }
+
+    /// Reads a plain scalar up to the end of the line or a comment, returning the end of its
+    /// last character.
+    fn plain_line(&mut self) -> Position {
+        let mut end = self.cursor.position();
+        while let Some(byte) = self.cursor.peek() {
+            match byte {
+                b'\n' => break,
+                b'#' if self.cursor.offset > 0
+                    && self.cursor.text.as_bytes()[self.cursor.offset - 1]
+                        .is_ascii_whitespace() =>
+                {
+                    break
+                }
+                b' ' | b'\t' | b'\r' => self.cursor.advance(1),
+                _ => {
+                    let length = self.cursor.rest().chars().next().map_or(1, char::len_utf8);
+                    self.cursor.advance(length);
+                    end = self.cursor.position();
+                }
+            }
+        }
+        self.skip_inline();
+        end
+    }
+
+    /// Reads a quoted scalar, which may span several lines.
+    fn quoted(&mut self, quote: u8) {
+        self.cursor.advance(1);
+        while let Some(byte) = self.cursor.peek() {
+            let length = self.cursor.rest().chars().next().map_or(1, char::len_utf8);
+            self.cursor.advance(length);
+            match byte {
+                b'\\' if quote == b'"' => {
+                    let length = self.cursor.rest().chars().next().map_or(0, char::len_utf8);
+                    self.cursor.advance(length);
+                }
+                b'\'' if quote == b'\'' && self.cursor.peek() == Some(b'\'') => {
+                    self.cursor.advance(1)
+                }
+                byte if byte == quote => break,
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads a `|` or `>` scalar, made of the following lines more indented than its parent.
+    fn block_scalar(&mut self, pointer: String, start: Position, parent: isize) {
+        self.cursor.skip_line();
+        let mut end = self.cursor.position();
+        loop {
+            let line = self.cursor.clone();
+            // the blank lines belong to the scalar when more lines follow
+            while self.cursor.peek() == Some(b'\n') {
+                self.cursor.advance(1);
+                while let Some(b' ' | b'\t' | b'\r') = self.cursor.peek() {
+                    self.cursor.advance(1);
+                }
+            }
+            if self.cursor.peek().is_none() || self.cursor.indent() as isize <= parent {
+                self.cursor = line;
+                break;
+            }
+            let content = self.cursor.rest();
+            let length = content.find('\n').unwrap_or(content.len());
+            let trimmed = content[..length].trim_end().len();
+            self.cursor.advance(trimmed);
+            end = self.cursor.position();
+            self.cursor.advance(length - trimmed);
+        }
+        self.record(pointer, start, end);
+    }
+
+    /// Reads a block mapping, whose keys are at the given indentation.
+    fn mapping(&mut self, pointer: String, indent: usize) {
+        let start = self.cursor.position();
+        while let Some(length) = self.key_length() {
+            let key_start = self.cursor.position();
+            let key = self.cursor.rest()[..length].trim_end();
+            let key = unquote(key);
+            let child = pointer::join(&pointer, key);
+            self.cursor.advance(length);
+            let key_end = self.cursor.position();
+            self.map.keys.insert(
+                child.clone(),
+                Span {
+                    start: key_start,
+                    end: key_end,
+                },
+            );
+            // the colon
+            self.cursor.advance(1);
+            self.value(child, indent);
+
+            self.skip_space();
+            if self.at_end() || self.cursor.indent() != indent {
+                break;
+            }
+        }
+        let end = self.end;
+        self.record(pointer, start, end);
+    }
+
+    /// Reads the value of a key of a block mapping, which is `null` when it is missing.
+    fn value(&mut self, pointer: String, indent: usize) {
+        let after = self.cursor.position();
+        self.skip_inline();
+        self.skip_properties();
+        if !matches!(self.cursor.peek(), Some(b'\n') | None) {
+            return self.inline(pointer, indent as isize);
+        }
+        self.skip_space();
+        if !self.at_end() && self.cursor.indent() == indent && self.at_entry() {
+            // the entries of a sequence may be as indented as the key
+            self.sequence(pointer.clone(), indent);
+        } else if !self.at_end() && self.cursor.indent() > indent {
+            self.node(pointer.clone(), indent as isize);
+        }
+        if !self.map.values.contains_key(&pointer) {
+            self.record(pointer, after, after);
+        }
+    }
+
+    /// Reads a block sequence, whose entries are at the given indentation.
+    fn sequence(&mut self, pointer: String, indent: usize) {
+        let start = self.cursor.position();
+        let mut index = 0;
+        while self.at_entry() {
+            let child = pointer::join(&pointer, index.to_string());
+            self.cursor.advance(1);
+            let after = self.cursor.position();
+            self.skip_inline();
+            self.node(child.clone(), indent as isize);
+            if !self.map.values.contains_key(&child) {
+                self.record(child, after, after);
+            }
+            index += 1;
+
+            self.skip_space();
+            if self.at_end() || self.cursor.indent() != indent {
+                break;
+            }
+        }
+        let end = self.end;
+        self.record(pointer, start, end);
+    }
+
+    /// Reads a `[...]` or `{...}` collection.
+    fn flow(&mut self, pointer: String) {
+        let start = self.cursor.position();
+        let mapping = self.cursor.peek() == Some(b'{');
+        self.cursor.advance(1);
+        let mut index = 0;
+        loop {
+            self.skip_space();
+            let before = self.cursor.offset;
+            match self.cursor.peek() {
+                None => break,
+                Some(b']' | b'}') => {
+                    self.cursor.advance(1);
+                    break;
+                }
+                Some(b',') => self.cursor.advance(1),
+                Some(_) if mapping => {
+                    let key_start = self.cursor.position();
+                    let (key, key_end) = self.flow_scalar();
+                    let child = pointer::join(&pointer, key);
+                    self.map.keys.insert(
+                        child.clone(),
+                        Span {
+                            start: key_start,
+                            end: key_end,
+                        },
+                    );
+                    self.skip_space();
+                    if self.cursor.peek() == Some(b':') {
+                        self.cursor.advance(1);
+                        self.flow_node(child);
+                    } else {
+                        self.record(child, key_end, key_end);
+                    }
+                }
+                Some(_) => {
+                    self.flow_node(pointer::join(&pointer, index.to_string()));
+                    index += 1;
+                }
+            }
+            if self.cursor.offset == before {
+                // an unexpected character
+                self.cursor.advance(1);
+            }
+        }
+        let end = self.cursor.position();
+        self.record(pointer, start, end);
+    }
+
+    fn flow_node(&mut self, pointer: String) {
+        self.skip_space();
+        self.skip_properties();
+        self.skip_space();
+        let start = self.cursor.position();
+        match self.cursor.peek() {
+            Some(b'[' | b'{') => self.flow(pointer),
+            _ => {
+                let (_, end) = self.flow_scalar();
+                self.record(pointer, start, end);
+            }
+        }
+    }
+
+    /// Reads a quoted or plain scalar of a flow collection, returning its text and end.
+    fn flow_scalar(&mut self) -> (String, Position) {
+        let start = self.cursor.offset;
+        if let Some(quote @ (b'"' | b'\'')) = self.cursor.peek() {
+            self.quoted(quote);
+            let text = unquote(&self.cursor.text[start..self.cursor.offset]);
+            return (text, self.cursor.position());
+        }
+        let bytes = self.cursor.text.as_bytes();
+        let mut end = self.cursor.offset;
+        while let Some(&byte) = bytes.get(end) {
+            let next = bytes.get(end + 1);
+            match byte {
+                // plain scalars may span several lines in flow collections
+                b',' | b'[' | b']' | b'{' | b'}' => break,
+                b':' if next
+                    .is_none_or(|byte| byte.is_ascii_whitespace() || b",]}".contains(byte)) =>
+                {
+                    break
+                }
+                b'#' if end > start && bytes[end - 1].is_ascii_whitespace() => break,
+                _ => end += 1,
+            }
+        }
+        let text = self.cursor.text[start..end].trim_end();
+        self.cursor.advance(text.len());
+        let position = self.cursor.position();
+        self.cursor.advance(end - start - text.len());
+        let text = match text.contains('\n') {
+            true => text.split_whitespace().collect::<Vec<_>>().join(" "),
+            false => text.to_string(),
+        };
+        (text, position)
+    }
+}
+/// The text of a YAML scalar, whose escapes are read as JSON escapes in double quotes.
+fn unquote(text: &str) -> String {
+    let inner = |quote| {
+        let text = text.strip_prefix(quote).unwrap_or(text);
+        text.strip_suffix(quote).unwrap_or(text)
+    };
+    match text.as_bytes().first() {
+        Some(b'"') => serde_json::from_str(text).unwrap_or_else(|_| inner('"').to_string()),
+        Some(b'\'') => inner('\'').replace("''", "'"),
+        _ => text.to_string(),
+    }
+}
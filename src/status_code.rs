@@ -0,0 +1,128 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The key of an entry in a [Responses](crate::Responses) object. It is either a specific HTTP
+/// status code such as `200`, or a range of codes such as `2XX`, which covers all response codes
+/// between `200` and `299`. Only the ranges `1XX` to `5XX` are allowed by the specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusCode {
+    /// A specific HTTP status code between `100` and `599`.
+    Code(u16),
+    /// A range of status codes, holding the class digit between `1` and `5`.
+    Range(u8),
+}
+
+impl StatusCode {
+    /// Creates a key for a specific status code, returning `None` if it is outside `100..=599`.
+    pub fn code(code: u16) -> Option<StatusCode> {
+        (100..=599)
+            .contains(&code)
+            .then_some(StatusCode::Code(code))
+    }
+
+    /// Creates a key for a range of status codes, returning `None` if the class is outside `1..=5`.
+    pub fn range(class: u8) -> Option<StatusCode> {
+        (1..=5).contains(&class).then_some(StatusCode::Range(class))
+    }
+
+    /// The class digit of the status code, e.g. `4` for both `404` and `4XX`.
+    pub fn class(&self) -> u8 {
+        match self {
+            StatusCode::Code(code) => (code / 100) as u8,
+            StatusCode::Range(class) => *class,
+        }
+    }
+
+    /// Whether this key is a range of status codes such as `4XX`.
+    pub fn is_range(&self) -> bool {
+        matches!(self, StatusCode::Range(_))
+    }
+
+    /// Whether the given status code is covered by this key.
+    pub fn matches(&self, status: u16) -> bool {
+        match self {
+            StatusCode::Code(code) => *code == status,
+            StatusCode::Range(class) => status / 100 == *class as u16,
+        }
+    }
+}
+
+impl Ord for StatusCode {
+    /// Orders codes the way they are conventionally written: `200`, `201`, `2XX`, `400`, `4XX`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |status: &StatusCode| match status {
+            StatusCode::Code(code) => (status.class(), false, *code),
+            StatusCode::Range(_) => (status.class(), true, 0),
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for StatusCode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusCode::Code(code) => write!(f, "{code}"),
+            StatusCode::Range(class) => write!(f, "{class}XX"),
+        }
+    }
+}
+
+/// The error returned when a string is not a valid response status code key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidStatusCode(pub String);
+
+impl fmt::Display for InvalidStatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid status code `{}`, expected a code between 100 and 599 or a range between 1XX and 5XX",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidStatusCode {}
+
+impl FromStr for StatusCode {
+    type Err = InvalidStatusCode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidStatusCode(s.to_string());
+        let bytes = s.as_bytes();
+        if bytes.len() != 3 || !bytes[0].is_ascii_digit() {
+            return Err(invalid());
+        }
+        if &bytes[1..] == b"XX" {
+            return StatusCode::range(bytes[0] - b'0').ok_or_else(invalid);
+        }
+        if !bytes.iter().all(u8::is_ascii_digit) {
+            return Err(invalid());
+        }
+        s.parse()
+            .ok()
+            .and_then(StatusCode::code)
+            .ok_or_else(invalid)
+    }
+}
+
+impl Serialize for StatusCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for StatusCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,325 @@
+use serde_json::{Map, Value};
+
+use crate::form::{coerce, take_property};
+use crate::{Components, Parameter, ParameterIn, Style, Type};
+
+/// Characters which are kept as they are by [percent_encode] when reserved characters are
+/// allowed, as defined by [RFC3986](https://www.rfc-editor.org/rfc/rfc3986#section-2.2).
+const RESERVED: &[u8] = b":/?#[]@!$&'()*+,;=";
+
+/// Percent-encodes everything except unreserved characters, and the reserved characters too if
+/// `allow_reserved` is set.
+pub(crate) fn percent_encode(value: &str, allow_reserved: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || b"-._~".contains(&byte)
+            || (allow_reserved && RESERVED.contains(&byte))
+        {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Decodes `%XX` sequences, leaving invalid ones as they are. The result is decoded lossily as
+/// UTF-8.
+pub(crate) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Renders a primitive as text. Nested arrays and objects, which have no defined serialization
+/// within a parameter, are rendered as JSON.
+pub(crate) fn primitive_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        Value::Bool(_) | Value::Number(_) | Value::Array(_) | Value::Object(_) => value.to_string(),
+    }
+}
+
+impl Style {
+    /// The default value of `explode`, which is `true` for the `form` style only.
+    pub fn default_explode(&self) -> bool {
+        matches!(self, Style::Form)
+    }
+
+    /// Serializes a value as one or more `name=value` pairs of a query string or an
+    /// `application/x-www-form-urlencoded` body, percent-encoded and joined with `&`.
+    ///
+    /// `spaceDelimited` and `pipeDelimited` only differ from `form` for non-exploded values, and
+    /// `deepObject` only applies to objects. Styles which are not defined for query strings, like
+    /// `simple`, fall back to `form`.
+    pub fn serialize_query(
+        &self,
+        name: &str,
+        value: &Value,
+        explode: bool,
+        allow_reserved: bool,
+    ) -> String {
+        let encode = |value: &str| percent_encode(value, allow_reserved);
+        let pair = |key: &str, value: &Value| {
+            format!("{}={}", encode(key), encode(&primitive_to_string(value)))
+        };
+        let delimiter = match self {
+            Style::SpaceDelimited => "%20",
+            Style::PipeDelimited => "|",
+            _ => ",",
+        };
+        match value {
+            Value::Object(object) if matches!(self, Style::DeepObject) => {
+                let mut pairs = vec![];
+                deep_object_pairs(&encode(name), object, &mut pairs);
+                pairs
+                    .into_iter()
+                    .map(|(key, value)| format!("{key}={}", encode(&primitive_to_string(value))))
+                    .collect::<Vec<_>>()
+                    .join("&")
+            }
+            Value::Array(items) if explode => items
+                .iter()
+                .map(|item| pair(name, item))
+                .collect::<Vec<_>>()
+                .join("&"),
+            Value::Object(object) if explode => object
+                .iter()
+                .map(|(key, value)| pair(key, value))
+                .collect::<Vec<_>>()
+                .join("&"),
+            Value::Array(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| encode(&primitive_to_string(item)))
+                    .collect::<Vec<_>>();
+                format!("{}={}", encode(name), items.join(delimiter))
+            }
+            Value::Object(object) => {
+                let items = object
+                    .iter()
+                    .flat_map(|(key, value)| [encode(key), encode(&primitive_to_string(value))])
+                    .collect::<Vec<_>>();
+                format!("{}={}", encode(name), items.join(delimiter))
+            }
+            value => pair(name, value),
+        }
+    }
+
+    /// Serializes the value of a path parameter with the `simple`, `label` or `matrix` style,
+    /// percent-encoded. Other styles fall back to `simple`.
+    pub fn serialize_path(&self, name: &str, value: &Value, explode: bool) -> String {
+        self.serialize_delimited(name, value, explode, &|value| percent_encode(value, false))
+    }
+
+    /// Serializes the value of a header parameter, which is not percent-encoded. Headers always
+    /// have the `simple` style.
+    pub fn serialize_header(&self, name: &str, value: &Value, explode: bool) -> String {
+        self.serialize_delimited(name, value, explode, &str::to_string)
+    }
+
+    fn serialize_delimited(
+        &self,
+        name: &str,
+        value: &Value,
+        explode: bool,
+        encode: &dyn Fn(&str) -> String,
+    ) -> String {
+        let text = |value: &Value| encode(&primitive_to_string(value));
+        let (prefix, separator) = match (self, explode) {
+            (Style::Label, true) => (".".to_string(), ".".to_string()),
+            (Style::Label, false) => (".".to_string(), ",".to_string()),
+            (Style::Matrix, true) => (format!(";{}=", encode(name)), format!(";{}=", encode(name))),
+            (Style::Matrix, false) => (format!(";{}=", encode(name)), ",".to_string()),
+            _ => (String::new(), ",".to_string()),
+        };
+        match value {
+            Value::Array(items) => {
+                let items = items.iter().map(text).collect::<Vec<_>>();
+                format!("{prefix}{}", items.join(&separator))
+            }
+            Value::Object(object) if explode => {
+                let (prefix, separator) = match self {
+                    Style::Label => (".", "."),
+                    Style::Matrix => (";", ";"),
+                    _ => ("", ","),
+                };
+                let entries = object
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", encode(key), text(value)))
+                    .collect::<Vec<_>>();
+                format!("{prefix}{}", entries.join(separator))
+            }
+            Value::Object(object) => {
+                let entries = object
+                    .iter()
+                    .flat_map(|(key, value)| [encode(key), text(value)])
+                    .collect::<Vec<_>>();
+                format!("{prefix}{}", entries.join(","))
+            }
+            Value::String(value) if value.is_empty() && matches!(self, Style::Matrix) => {
+                format!(";{}", encode(name))
+            }
+            value => format!("{prefix}{}", text(value)),
+        }
+    }
+}
+
+/// Flattens an object into `name[key][nested]` pairs. Brackets are left unencoded, as in the
+/// examples of the specification.
+fn deep_object_pairs<'a>(
+    prefix: &str,
+    object: &'a serde_json::Map<String, Value>,
+    pairs: &mut Vec<(String, &'a Value)>,
+) {
+    for (key, value) in object {
+        let key = format!("{prefix}[{}]", percent_encode(key, false));
+        match value {
+            Value::Object(nested) => deep_object_pairs(&key, nested, pairs),
+            value => pairs.push((key, value)),
+        }
+    }
+}
+
+impl Parameter {
+    /// The `style` of the parameter, which defaults to `form` for query and cookie parameters and
+    /// to `simple` for path and header parameters.
+    pub fn style_or_default(&self) -> Style {
+        self.style.clone().unwrap_or(match self._in {
+            ParameterIn::Query | ParameterIn::Cookie => Style::Form,
+            ParameterIn::Path | ParameterIn::Header => Style::Simple,
+        })
+    }
+
+    /// The `explode` of the parameter, which defaults to `true` for the `form` style only.
+    pub fn explode_or_default(&self) -> bool {
+        self.explode
+            .unwrap_or_else(|| self.style_or_default().default_explode())
+    }
+
+    /// Parses the value of the parameter from a request and converts it to the type of its
+    /// schema. `raw` is the whole query string for query parameters, and the value of the path
+    /// parameter, header or cookie otherwise. Returns `None` when a query parameter is absent.
+    /// Parameters described with `content` are parsed as JSON, falling back to a string.
+    pub fn parse(&self, raw: &str, components: Option<&Components>) -> Option<Value> {
+        let schema = self
+            .schema
+            .as_ref()
+            .and_then(|schema| schema.resolve(components));
+        let style = self.style_or_default();
+        let explode = self.explode_or_default();
+        if self._in == ParameterIn::Query {
+            let raw = raw.strip_prefix('?').unwrap_or(raw);
+            let mut pairs = url::form_urlencoded::parse(raw.as_bytes())
+                .into_owned()
+                .map(Some)
+                .collect::<Vec<_>>();
+            return match (&self.content, schema) {
+                (Some(_), _) => {
+                    let value = pairs
+                        .into_iter()
+                        .flatten()
+                        .find(|(key, _)| *key == self.name)?
+                        .1;
+                    Some(serde_json::from_str(&value).unwrap_or(Value::String(value)))
+                }
+                (None, schema) => take_property(
+                    &mut pairs,
+                    &self.name,
+                    schema,
+                    &style,
+                    explode,
+                    &[],
+                    components,
+                ),
+            };
+        }
+        if self.content.is_some() {
+            let raw = percent_decode(raw);
+            return Some(serde_json::from_str(&raw).unwrap_or(Value::String(raw)));
+        }
+        let raw = match style {
+            Style::Label => raw.strip_prefix('.').unwrap_or(raw),
+            Style::Matrix => raw
+                .strip_prefix(';')
+                .and_then(|raw| raw.strip_prefix(self.name.as_str()))
+                .map(|raw| raw.strip_prefix('=').unwrap_or(raw))
+                .unwrap_or(raw),
+            _ => raw,
+        };
+        let delimiter = match (&style, explode) {
+            (Style::Label, true) => ".".to_string(),
+            (Style::Matrix, true) => format!(";{}=", self.name),
+            _ => ",".to_string(),
+        };
+        let decode = |value: &str| match self._in {
+            ParameterIn::Header => value.trim().to_string(),
+            _ => percent_decode(value),
+        };
+        match schema.and_then(|schema| schema._type.as_ref()) {
+            Some(Type::Array) => {
+                let items = schema
+                    .and_then(|schema| schema.items.as_deref())
+                    .and_then(|items| items.resolve(components));
+                let items = raw
+                    .split(delimiter.as_str())
+                    .filter(|item| !item.is_empty())
+                    .map(|item| coerce(&decode(item), items))
+                    .collect();
+                Some(Value::Array(items))
+            }
+            Some(Type::Object) => {
+                let schema = schema?;
+                let separator = match style {
+                    Style::Label => '.',
+                    Style::Matrix => ';',
+                    _ => ',',
+                };
+                let entries = match explode {
+                    true => raw
+                        .split(separator)
+                        .filter_map(|entry| entry.split_once('='))
+                        .map(|(key, value)| (decode(key), decode(value)))
+                        .collect::<Vec<_>>(),
+                    false => raw
+                        .split(',')
+                        .collect::<Vec<_>>()
+                        .chunks(2)
+                        .map(|chunk| (decode(chunk[0]), decode(chunk.get(1).unwrap_or(&""))))
+                        .collect(),
+                };
+                let object = entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let property = schema
+                            .property(&key, components)
+                            .and_then(|property| property.resolve(components));
+                        let value = coerce(&value, property);
+                        (key, value)
+                    })
+                    .collect::<Map<_, _>>();
+                Some(Value::Object(object))
+            }
+            _ => Some(coerce(&decode(raw), schema)),
+        }
+    }
+}
@@ -0,0 +1,490 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::{
+    AdditionalProperties, Components, Format, Map, Reference, Referenceable, Schema, Type,
+};
+
+/// A Rust type described by a [Schema], to write documents from code. It is usually derived with
+/// `#[derive(ToSchema)]`, which is available with the `derive` feature and follows the `serde`
+/// attributes of the type.
+///
+/// Named types are registered in `components/schemas` and referenced, which also allows recursive
+/// types. Types with the same name share the same component, so the names must be unique.
+pub trait ToSchema {
+    /// The name of the schema in `components/schemas`, for types which are referenced rather than
+    /// inlined.
+    fn schema_name() -> Option<String> {
+        None
+    }
+
+    /// The schema of the type, registering the schemas of the named types it uses.
+    fn schema(components: &mut Components) -> Schema;
+
+    /// Whether a property of this type may be absent, which is the case for `Option`.
+    fn optional() -> bool {
+        false
+    }
+
+    /// A reference to the schema for named types, which is registered if needed, or the schema
+    /// itself.
+    fn referenceable(components: &mut Components) -> Referenceable<Schema> {
+        let Some(name) = Self::schema_name() else {
+            return Referenceable::Data(Self::schema(components));
+        };
+        let schemas = components.schemas.get_or_insert_with(Map::new);
+        if !schemas.contains_key(&name) {
+            // a placeholder, so that recursive types are referenced rather than expanded forever
+            schemas.insert(name.clone(), Referenceable::Data(Schema::default()));
+            let schema = Self::schema(components);
+            components
+                .schemas
+                .get_or_insert_with(Map::new)
+                .insert(name.clone(), Referenceable::Data(schema));
+        }
+        Referenceable::Reference(Reference::component::<Schema>(&name))
+    }
+}
+
+impl Components {
+    /// Registers the schema of a type and the ones it uses in `schemas`. Returns a reference to
+    /// it, or the schema itself for types which are not named.
+    pub fn register_schema<T: ToSchema + ?Sized>(&mut self) -> Referenceable<Schema> {
+        T::referenceable(self)
+    }
+}
+
+fn typed(_type: Type, format: Option<Format>) -> Schema {
+    Schema {
+        _type: Some(_type),
+        format,
+        ..Schema::default()
+    }
+}
+
+fn integer(format: Format, minimum: Option<i64>, maximum: Option<i64>) -> Schema {
+    Schema {
+        minimum: minimum.map(Into::into),
+        maximum: maximum.map(Into::into),
+        ..typed(Type::Integer, Some(format))
+    }
+}
+
+macro_rules! impl_integer {
+    ($($t:ty => $format:ident, $minimum:expr, $maximum:expr;)+) => {
+        $(
+        impl ToSchema for $t {
+            fn schema(_: &mut Components) -> Schema {
+                integer(Format::$format, $minimum, $maximum)
+            }
+        }
+        )+
+    };
+}
+
+impl_integer! {
+    i8 => Int32, Some(i8::MIN.into()), Some(i8::MAX.into());
+    i16 => Int32, Some(i16::MIN.into()), Some(i16::MAX.into());
+    i32 => Int32, None, None;
+    i64 => Int64, None, None;
+    isize => Int64, None, None;
+    u8 => Int32, Some(0), Some(u8::MAX.into());
+    u16 => Int32, Some(0), Some(u16::MAX.into());
+    u32 => Int64, Some(0), Some(u32::MAX.into());
+    u64 => Int64, Some(0), None;
+    usize => Int64, Some(0), None;
+}
+
+impl ToSchema for bool {
+    fn schema(_: &mut Components) -> Schema {
+        typed(Type::Boolean, None)
+    }
+}
+
+impl ToSchema for f32 {
+    fn schema(_: &mut Components) -> Schema {
+        typed(Type::Number, Some(Format::Float))
+    }
+}
+
+impl ToSchema for f64 {
+    fn schema(_: &mut Components) -> Schema {
+        typed(Type::Number, Some(Format::Double))
+    }
+}
+
+impl ToSchema for str {
+    fn schema(_: &mut Components) -> Schema {
+        typed(Type::String, None)
+    }
+}
+
+impl ToSchema for String {
+    fn schema(_: &mut Components) -> Schema {
+        typed(Type::String, None)
+    }
+}
+
+impl ToSchema for char {
+    fn schema(_: &mut Components) -> Schema {
+        Schema {
+            min_length: Some(1),
+            max_length: Some(1),
+            ..typed(Type::String, None)
+        }
+    }
+}
+
+/// Any value.
+impl ToSchema for Value {
+    fn schema(_: &mut Components) -> Schema {
+        Schema::default()
+    }
+}
+
+/// `null`, which is how `serde` serializes `()`.
+impl ToSchema for () {
+    fn schema(_: &mut Components) -> Schema {
+        __private::null()
+    }
+}
+
+impl<T: ToSchema> ToSchema for Option<T> {
+    fn schema(components: &mut Components) -> Schema {
+        match T::referenceable(components) {
+            Referenceable::Data(schema) => Schema {
+                nullable: Some(true),
+                ..schema
+            },
+            reference => Schema {
+                all_of: Some(vec![reference]),
+                nullable: Some(true),
+                ..Schema::default()
+            },
+        }
+    }
+
+    fn optional() -> bool {
+        true
+    }
+}
+
+macro_rules! impl_wrapper {
+    ($($t:ident),+) => {
+        $(
+        impl<T: ToSchema + ?Sized> ToSchema for $t<T> {
+            fn schema_name() -> Option<String> {
+                T::schema_name()
+            }
+
+            fn schema(components: &mut Components) -> Schema {
+                T::schema(components)
+            }
+
+            fn optional() -> bool {
+                T::optional()
+            }
+        }
+        )+
+    };
+}
+
+impl_wrapper!(Box, Rc, Arc);
+
+impl<T: ToSchema + ?Sized> ToSchema for &T {
+    fn schema_name() -> Option<String> {
+        T::schema_name()
+    }
+
+    fn schema(components: &mut Components) -> Schema {
+        T::schema(components)
+    }
+
+    fn optional() -> bool {
+        T::optional()
+    }
+}
+
+impl<T: ToSchema + ToOwned + ?Sized> ToSchema for Cow<'_, T> {
+    fn schema_name() -> Option<String> {
+        T::schema_name()
+    }
+
+    fn schema(components: &mut Components) -> Schema {
+        T::schema(components)
+    }
+
+    fn optional() -> bool {
+        T::optional()
+    }
+}
+
+fn array<T: ToSchema>(components: &mut Components, unique_items: bool) -> Schema {
+    Schema {
+        items: Some(Box::new(T::referenceable(components))),
+        unique_items: unique_items.then_some(true),
+        ..typed(Type::Array, None)
+    }
+}
+
+macro_rules! impl_array {
+    ($($t:ident => $unique:literal),+) => {
+        $(
+        impl<T: ToSchema> ToSchema for $t<T> {
+            fn schema(components: &mut Components) -> Schema {
+                array::<T>(components, $unique)
+            }
+        }
+        )+
+    };
+}
+
+impl_array!(Vec => false, VecDeque => false, LinkedList => false, BTreeSet => true, HashSet => true);
+
+impl<T: ToSchema> ToSchema for [T] {
+    fn schema(components: &mut Components) -> Schema {
+        array::<T>(components, false)
+    }
+}
+
+impl<T: ToSchema, const N: usize> ToSchema for [T; N] {
+    fn schema(components: &mut Components) -> Schema {
+        Schema {
+            min_items: Some(N as u64),
+            max_items: Some(N as u64),
+            ..array::<T>(components, false)
+        }
+    }
+}
+
+/// Maps are objects, so their keys are expected to serialize as strings.
+impl<K, V: ToSchema> ToSchema for BTreeMap<K, V> {
+    fn schema(components: &mut Components) -> Schema {
+        map::<V>(components)
+    }
+}
+
+/// Maps are objects, so their keys are expected to serialize as strings.
+impl<K, V: ToSchema, S> ToSchema for HashMap<K, V, S> {
+    fn schema(components: &mut Components) -> Schema {
+        map::<V>(components)
+    }
+}
+
+fn map<V: ToSchema>(components: &mut Components) -> Schema {
+    let values = V::referenceable(components);
+    Schema {
+        additional_properties: Some(AdditionalProperties::Schema(Box::new(values))),
+        ..typed(Type::Object, None)
+    }
+}
+
+/// Functions used by the code generated by `#[derive(ToSchema)]` and `#[operation]`.
+#[doc(hidden)]
+pub mod __private {
+    use serde_json::Value;
+
+    use crate::{Components, Map, Operation, Referenceable, Responses, Schema, ToSchema, Type};
+
+    /// An operation without parameters nor request body.
+    pub fn operation(
+        operation_id: &str,
+        tags: &[&str],
+        summary: Option<&str>,
+        description: Option<&str>,
+        deprecated: bool,
+        responses: Responses,
+    ) -> Operation {
+        Operation {
+            tags: (!tags.is_empty()).then(|| tags.iter().map(|tag| tag.to_string()).collect()),
+            summary: summary.map(str::to_string),
+            description: description.map(str::to_string),
+            external_docs: None,
+            operation_id: Some(operation_id.to_string()),
+            parameters: None,
+            request_body: None,
+            responses,
+            callbacks: None,
+            deprecated: deprecated.then_some(true),
+            security: None,
+            servers: None,
+            extras: None,
+        }
+    }
+
+    /// A property of an object, with its name and whether it is required.
+    pub struct Property {
+        pub name: &'static str,
+        pub schema: Referenceable<Schema>,
+        pub required: bool,
+    }
+
+    /// The schema of a field, with its documentation.
+    pub fn field<T: ToSchema + ?Sized>(
+        components: &mut Components,
+        description: Option<&str>,
+        deprecated: bool,
+    ) -> Referenceable<Schema> {
+        describe(T::referenceable(components), description, deprecated)
+    }
+
+    /// Adds a description to a schema, wrapping references in an `allOf` since their siblings
+    /// are ignored.
+    pub fn describe(
+        schema: Referenceable<Schema>,
+        description: Option<&str>,
+        deprecated: bool,
+    ) -> Referenceable<Schema> {
+        if description.is_none() && !deprecated {
+            return schema;
+        }
+        let description = description.map(str::to_string);
+        let deprecated = deprecated.then_some(true);
+        match schema {
+            Referenceable::Data(schema) => Referenceable::Data(Schema {
+                description: description.or(schema.description),
+                deprecated: deprecated.or(schema.deprecated),
+                ..schema
+            }),
+            reference => Referenceable::Data(Schema {
+                all_of: Some(vec![reference]),
+                description,
+                deprecated,
+                ..Schema::default()
+            }),
+        }
+    }
+
+    /// A schema which is not a reference, wrapping references in an `allOf`.
+    pub fn inline(schema: Referenceable<Schema>) -> Schema {
+        match schema {
+            Referenceable::Data(schema) => schema,
+            reference => all_of(vec![reference]),
+        }
+    }
+
+    /// An object with the given properties.
+    pub fn object(properties: Vec<Property>) -> Schema {
+        let required = properties
+            .iter()
+            .filter(|property| property.required)
+            .map(|property| property.name.to_string())
+            .collect::<Vec<_>>();
+        Schema {
+            _type: Some(Type::Object),
+            properties: Some(
+                properties
+                    .into_iter()
+                    .map(|property| (property.name.to_string(), property.schema))
+                    .collect(),
+            ),
+            required: (!required.is_empty()).then_some(required),
+            ..Schema::default()
+        }
+    }
+
+    /// Adds the properties of the schema of a flattened field to an object.
+    pub fn flatten<T: ToSchema + ?Sized>(object: &mut Schema, components: &mut Components) {
+        let flattened = T::schema(components);
+        if let Some(properties) = flattened.properties {
+            object
+                .properties
+                .get_or_insert_with(Map::new)
+                .extend(properties);
+        }
+        if let Some(required) = flattened.required.filter(|_| !T::optional()) {
+            object
+                .required
+                .get_or_insert_with(Vec::new)
+                .extend(required);
+        }
+        if flattened.additional_properties.is_some() {
+            object.additional_properties = flattened.additional_properties;
+        }
+    }
+
+    /// Adds the required property holding the tag of an internally tagged enum to an object.
+    pub fn add_tag(object: &mut Schema, tag: &str, value: &str) {
+        let mut properties = Map::new();
+        properties.insert(tag.to_string(), Referenceable::Data(constant(value)));
+        properties.extend(object.properties.take().unwrap_or_default());
+        object.properties = Some(properties);
+        object
+            .required
+            .get_or_insert_with(Vec::new)
+            .insert(0, tag.to_string());
+    }
+
+    /// A string which can only have the given value, e.g. the tag of a variant.
+    pub fn constant(value: &str) -> Schema {
+        Schema {
+            _type: Some(Type::String),
+            _enum: Some(vec![Value::String(value.to_string())]),
+            ..Schema::default()
+        }
+    }
+
+    /// A string enumeration.
+    pub fn enumeration(values: &[&str]) -> Schema {
+        Schema {
+            _type: Some(Type::String),
+            _enum: Some(
+                values
+                    .iter()
+                    .map(|value| Value::String(value.to_string()))
+                    .collect(),
+            ),
+            ..Schema::default()
+        }
+    }
+
+    /// `null`, which has no type in OpenAPI 3.0.
+    pub fn null() -> Schema {
+        Schema {
+            nullable: Some(true),
+            _enum: Some(vec![Value::Null]),
+            ..Schema::default()
+        }
+    }
+
+    /// An array of a fixed number of items of various types.
+    pub fn tuple(items: Vec<Referenceable<Schema>>) -> Schema {
+        let count = items.len() as u64;
+        Schema {
+            _type: Some(Type::Array),
+            items: Some(Box::new(match items.len() {
+                1 => items.into_iter().next().expect("there is one item"),
+                _ => Referenceable::Data(Schema {
+                    any_of: Some(items),
+                    ..Schema::default()
+                }),
+            })),
+            min_items: Some(count),
+            max_items: Some(count),
+            ..Schema::default()
+        }
+    }
+
+    /// Either of the given schemas. Internally tagged enums have no discriminator, as the
+    /// implicit mapping of a discriminator points to components while the variants are inline:
+    /// the constant of the tag of each variant tells them apart.
+    pub fn one_of(variants: Vec<Referenceable<Schema>>) -> Schema {
+        Schema {
+            one_of: Some(variants),
+            ..Schema::default()
+        }
+    }
+
+    /// The intersection of the given schemas, e.g. the variant of an internally tagged enum
+    /// holding a named type.
+    pub fn all_of(schemas: Vec<Referenceable<Schema>>) -> Schema {
+        Schema {
+            all_of: Some(schemas),
+            ..Schema::default()
+        }
+    }
+}
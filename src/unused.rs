@@ -0,0 +1,193 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::{pointer, OpenAPIV3};
+
+/// A component which is neither referenced from the paths, security requirements, webhooks and
+/// extensions of a document, nor from the components they use.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UnusedComponent {
+    /// The field of [Components](crate::Components) holding the component, e.g. `schemas`.
+    pub kind: String,
+    pub name: String,
+}
+
+impl fmt::Display for UnusedComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = pointer::join("#/components", &self.kind);
+        write!(f, "{} is never used", pointer::join(&kind, &self.name))
+    }
+}
+
+impl OpenAPIV3 {
+    /// The components which are never used, sorted by kind and name. Schemas are used by
+    /// references and discriminator mappings, and security schemes by security requirements. The
+    /// schemas which extend a used schema with a discriminator through `allOf` are used too, as
+    /// they are its implicit subtypes.
+    pub fn unused_components(&self) -> Vec<UnusedComponent> {
+        let document = self.to_value();
+        components(&document)
+            .difference(&used_components(&document, vec![]))
+            .map(|(kind, name)| UnusedComponent {
+                kind: kind.clone(),
+                name: name.clone(),
+            })
+            .collect()
+    }
+
+    /// Removes the components which are never used, returning them.
+    pub fn remove_unused_components(&mut self) -> Vec<UnusedComponent> {
+        let unused = self.unused_components();
+        if unused.is_empty() {
+            return unused;
+        }
+        let mut document = self.to_value();
+        let used = used_components(&document, vec![]);
+        retain_components(&mut document, &used);
+        *self = serde_json::from_value(document)
+            .expect("the document without unused components is valid");
+        unused
+    }
+}
+
+/// Removes the components of a serialized document which are not in `used`.
+pub(crate) fn retain_components(document: &mut Value, used: &BTreeSet<(String, String)>) {
+    if let Some(kinds) = document
+        .get_mut("components")
+        .and_then(Value::as_object_mut)
+    {
+        for (kind, entries) in kinds.iter_mut().filter(|(kind, _)| !kind.starts_with("x-")) {
+            if let Some(entries) = entries.as_object_mut() {
+                entries.retain(|name, _| used.contains(&(kind.clone(), name.clone())));
+            }
+        }
+    }
+}
+
+/// The components of a serialized document, by kind and name.
+pub(crate) fn components(document: &Value) -> BTreeSet<(String, String)> {
+    document
+        .get("components")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter(|(kind, _)| !kind.starts_with("x-"))
+        .flat_map(|(kind, entries)| {
+            entries
+                .as_object()
+                .into_iter()
+                .flat_map(Map::keys)
+                .map(move |name| (kind.clone(), name.clone()))
+        })
+        .collect()
+}
+
+/// The components used by a serialized document, directly or through other components, along
+/// with the given components and the ones they use.
+pub(crate) fn used_components(
+    document: &Value,
+    mut pending: Vec<(String, String)>,
+) -> BTreeSet<(String, String)> {
+    for (key, value) in document.as_object().into_iter().flatten() {
+        if key != "components" {
+            references(value, Some(key), &mut pending);
+        }
+    }
+    // the extensions of the components are not components themselves
+    for (key, value) in document
+        .get("components")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| key.starts_with("x-"))
+    {
+        references(value, Some(key), &mut pending);
+    }
+    let mut used = BTreeSet::new();
+    while let Some((kind, name)) = pending.pop() {
+        if let Some(component) = document
+            .get("components")
+            .and_then(|components| components.get(&kind)?.get(&name))
+        {
+            if !used.contains(&(kind.clone(), name.clone())) {
+                references(component, None, &mut pending);
+                if kind == "schemas" && component.get("discriminator").is_some() {
+                    pending.extend(subtypes(document, &name));
+                }
+            }
+        }
+        used.insert((kind, name));
+    }
+    used
+}
+
+/// The schemas which extend a schema with `allOf`, as they are its implicit subtypes when it has
+/// a discriminator.
+fn subtypes(document: &Value, name: &str) -> Vec<(String, String)> {
+    let supertype = ("schemas".to_string(), name.to_string());
+    document
+        .get("components")
+        .and_then(|components| components.get("schemas")?.as_object())
+        .into_iter()
+        .flatten()
+        .filter(|(_, schema)| {
+            schema
+                .get("allOf")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|schema| schema.get("$ref")?.as_str())
+                .any(|reference| component(reference).as_ref() == Some(&supertype))
+        })
+        .map(|(name, _)| ("schemas".to_string(), name.clone()))
+        .collect()
+}
+
+/// Collects the components referenced by a serialized value: the targets of references and
+/// discriminator mappings, and the security schemes of security requirements.
+fn references(value: &Value, key: Option<&str>, found: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+                found.extend(component(reference));
+            }
+            if key == Some("mapping") {
+                for target in object.values().filter_map(Value::as_str) {
+                    match target.contains('/') {
+                        true => found.extend(component(target)),
+                        false => found.push(("schemas".to_string(), target.to_string())),
+                    }
+                }
+            }
+            for (key, value) in object {
+                references(value, Some(key), found);
+            }
+        }
+        Value::Array(array) if key == Some("security") => {
+            for requirement in array.iter().filter_map(Value::as_object) {
+                found.extend(
+                    requirement
+                        .keys()
+                        .map(|name| ("securitySchemes".to_string(), name.clone())),
+                );
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                references(value, None, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The kind and name of the component a local reference points to, or into.
+fn component(reference: &str) -> Option<(String, String)> {
+    let mut tokens = reference.strip_prefix("#/components/")?.split('/');
+    let unescape = |token: &str| token.replace("~1", "/").replace("~0", "~");
+    let kind = unescape(tokens.next()?);
+    let name = unescape(tokens.next()?);
+    Some((kind, name))
+}
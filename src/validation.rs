@@ -0,0 +1,578 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::{Mutex, PoisonError};
+
+use email_address::EmailAddress;
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+use url::Url;
+
+use crate::pointer;
+use crate::{
+    AdditionalProperties, Any, Components, Direction, Format, Referenceable, Schema, Type,
+};
+
+/// The depth at which validation stops, which guards against recursive schemas applied to
+/// themselves through `allOf`.
+const MAX_DEPTH: usize = 128;
+
+/// The compiled `pattern`s of the schemas, so that each is compiled once rather than for every
+/// validated string. The patterns which are not valid for the regex crate are kept as `None`.
+static PATTERNS: Mutex<BTreeMap<String, Option<Regex>>> = Mutex::new(BTreeMap::new());
+
+fn compiled(pattern: &str) -> Option<Regex> {
+    let mut patterns = PATTERNS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(regex) = patterns.get(pattern) {
+        return regex.clone();
+    }
+    let regex = Regex::new(pattern).ok();
+    patterns.insert(pattern.to_string(), regex.clone());
+    regex
+}
+
+/// A reason why a value does not conform to a [Schema].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The JSON Pointer to the invalid part of the value, empty for the value itself.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Schema {
+    /// Validates a value against the schema, using the components to resolve references.
+    ///
+    /// Formats are only checked for strings and integers, and `readOnly` and `writeOnly` are not
+    /// taken into account, see [Schema::validate_for].
+    pub fn validate(
+        &self,
+        value: &Any,
+        components: Option<&Components>,
+    ) -> Result<(), Vec<ValidationError>> {
+        Validator::run(components, None, |validator| {
+            validator.schema(self, value, "", 0)
+        })
+    }
+
+    /// Validates a value sent in the given direction against the schema, so that required
+    /// `readOnly` properties may be left out of requests and required `writeOnly` properties may
+    /// be left out of responses.
+    pub fn validate_for(
+        &self,
+        value: &Any,
+        direction: Direction,
+        components: Option<&Components>,
+    ) -> Result<(), Vec<ValidationError>> {
+        Validator::run(components, Some(direction), |validator| {
+            validator.schema(self, value, "", 0)
+        })
+    }
+}
+
+impl Referenceable<Schema> {
+    /// Validates a value against the schema, or against the schema the reference points to.
+    pub fn validate(
+        &self,
+        value: &Any,
+        components: Option<&Components>,
+    ) -> Result<(), Vec<ValidationError>> {
+        Validator::run(components, None, |validator| {
+            validator.referenceable(self, value, "", 0)
+        })
+    }
+
+    /// Validates a value sent in the given direction, see [Schema::validate_for].
+    pub fn validate_for(
+        &self,
+        value: &Any,
+        direction: Direction,
+        components: Option<&Components>,
+    ) -> Result<(), Vec<ValidationError>> {
+        Validator::run(components, Some(direction), |validator| {
+            validator.referenceable(self, value, "", 0)
+        })
+    }
+}
+
+struct Validator<'a> {
+    components: Option<&'a Components>,
+    direction: Option<Direction>,
+    errors: Vec<ValidationError>,
+}
+
+impl<'a> Validator<'a> {
+    fn run(
+        components: Option<&'a Components>,
+        direction: Option<Direction>,
+        validate: impl FnOnce(&mut Validator<'a>),
+    ) -> Result<(), Vec<ValidationError>> {
+        let mut validator = Validator {
+            components,
+            direction,
+            errors: vec![],
+        };
+        validate(&mut validator);
+        match validator.errors.is_empty() {
+            true => Ok(()),
+            false => Err(validator.errors),
+        }
+    }
+
+    fn error(&mut self, pointer: &str, message: impl Into<String>) {
+        self.errors.push(ValidationError {
+            pointer: pointer.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// Whether the value is valid against the schema, without recording errors.
+    fn is_valid(&self, schema: &Referenceable<Schema>, value: &Value, depth: usize) -> bool {
+        let mut validator = Validator {
+            components: self.components,
+            direction: self.direction,
+            errors: vec![],
+        };
+        validator.referenceable(schema, value, "", depth);
+        validator.errors.is_empty()
+    }
+
+    fn referenceable(
+        &mut self,
+        schema: &Referenceable<Schema>,
+        value: &Value,
+        pointer: &str,
+        depth: usize,
+    ) {
+        match schema.resolve(self.components) {
+            Some(schema) => self.schema(schema, value, pointer, depth),
+            None => {
+                if let Referenceable::Reference(reference) = schema {
+                    self.error(
+                        pointer,
+                        format!("cannot resolve reference `{}`", reference._ref),
+                    );
+                }
+            }
+        }
+    }
+
+    fn schema(&mut self, schema: &Schema, value: &Value, pointer: &str, depth: usize) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        if let Some(values) = &schema._enum {
+            if !(values.contains(value) || value.is_null() && schema.is_nullable()) {
+                self.error(pointer, format!("{value} is not one of the allowed values"));
+            }
+        }
+        if value.is_null() {
+            if schema._type.is_some() && !schema.is_nullable() {
+                self.error(pointer, "null is not allowed");
+            }
+            return;
+        }
+        if let Some(_type) = &schema._type {
+            if !has_type(value, _type) {
+                self.error(
+                    pointer,
+                    format!("expected {}, found {}", type_name(_type), value_name(value)),
+                );
+                return;
+            }
+        }
+        match value {
+            Value::Number(number) => self.number(schema, number, pointer),
+            Value::String(string) => self.string(schema, string, pointer),
+            Value::Array(items) => self.array(schema, items, pointer, depth),
+            Value::Object(object) => self.object(schema, object, pointer, depth),
+            Value::Null | Value::Bool(_) => {}
+        }
+        self.composition(schema, value, pointer, depth);
+    }
+
+    fn number(&mut self, schema: &Schema, number: &Number, pointer: &str) {
+        let Some(value) = number.as_f64() else {
+            return;
+        };
+        if let Some(multiple_of) = schema.multiple_of.as_ref().and_then(Number::as_f64) {
+            let quotient = value / multiple_of;
+            if multiple_of > 0.0 && (quotient - quotient.round()).abs() > 1e-9 {
+                self.error(
+                    pointer,
+                    format!("{number} is not a multiple of {multiple_of}"),
+                );
+            }
+        }
+        if let Some(maximum) = schema.maximum.as_ref().and_then(Number::as_f64) {
+            if schema.exclusive_maximum.unwrap_or(false) && value >= maximum {
+                self.error(pointer, format!("{number} is not less than {maximum}"));
+            } else if value > maximum {
+                self.error(pointer, format!("{number} is greater than {maximum}"));
+            }
+        }
+        if let Some(minimum) = schema.minimum.as_ref().and_then(Number::as_f64) {
+            if schema.exclusive_minimum.unwrap_or(false) && value <= minimum {
+                self.error(pointer, format!("{number} is not greater than {minimum}"));
+            } else if value < minimum {
+                self.error(pointer, format!("{number} is less than {minimum}"));
+            }
+        }
+        let in_range = match &schema.format {
+            Some(Format::Int32) => (i32::MIN as f64..=i32::MAX as f64).contains(&value),
+            Some(Format::Int64) => {
+                number.is_i64()
+                    || (!number.is_u64() && (i64::MIN as f64..=i64::MAX as f64).contains(&value))
+            }
+            _ => true,
+        };
+        if !in_range {
+            self.error(pointer, format!("{number} is out of range for its format"));
+        }
+    }
+
+    fn string(&mut self, schema: &Schema, string: &str, pointer: &str) {
+        let length = string.chars().count() as u64;
+        if let Some(max_length) = schema.max_length {
+            if length > max_length {
+                self.error(pointer, format!("longer than {max_length} characters"));
+            }
+        }
+        if let Some(min_length) = schema.min_length {
+            if length < min_length {
+                self.error(pointer, format!("shorter than {min_length} characters"));
+            }
+        }
+        if let Some(pattern) = &schema.pattern {
+            // patterns which are not valid for the regex crate are ignored rather than reported
+            if let Some(regex) = compiled(pattern) {
+                if !regex.is_match(string) {
+                    self.error(pointer, format!("does not match the pattern `{pattern}`"));
+                }
+            }
+        }
+        if let Some(format) = &schema.format {
+            if !format.is_valid(string) {
+                self.error(
+                    pointer,
+                    format!("`{string}` is not a valid {}", format_name(format)),
+                );
+            }
+        }
+    }
+
+    fn array(&mut self, schema: &Schema, items: &[Value], pointer: &str, depth: usize) {
+        let count = items.len() as u64;
+        if let Some(max_items) = schema.max_items {
+            if count > max_items {
+                self.error(pointer, format!("more than {max_items} items"));
+            }
+        }
+        if let Some(min_items) = schema.min_items {
+            if count < min_items {
+                self.error(pointer, format!("fewer than {min_items} items"));
+            }
+        }
+        if schema.unique_items.unwrap_or(false) {
+            for (index, item) in items.iter().enumerate() {
+                if items[..index].contains(item) {
+                    self.error(&pointer::join(pointer, index.to_string()), "duplicate item");
+                }
+            }
+        }
+        if let Some(item_schema) = &schema.items {
+            for (index, item) in items.iter().enumerate() {
+                let pointer = pointer::join(pointer, index.to_string());
+                self.referenceable(item_schema, item, &pointer, depth + 1);
+            }
+        }
+    }
+
+    fn object(
+        &mut self,
+        schema: &Schema,
+        object: &Map<String, Value>,
+        pointer: &str,
+        depth: usize,
+    ) {
+        let count = object.len() as u64;
+        if let Some(max_properties) = schema.max_properties {
+            if count > max_properties {
+                self.error(pointer, format!("more than {max_properties} properties"));
+            }
+        }
+        if let Some(min_properties) = schema.min_properties {
+            if count < min_properties {
+                self.error(pointer, format!("fewer than {min_properties} properties"));
+            }
+        }
+        for required in schema.required.iter().flatten() {
+            if !object.contains_key(required) && !self.is_omitted(schema, required) {
+                self.error(pointer, format!("missing required property `{required}`"));
+            }
+        }
+        for (name, value) in object {
+            let property_pointer = pointer::join(pointer, name);
+            match schema
+                .properties
+                .as_ref()
+                .and_then(|properties| properties.get(name))
+            {
+                Some(property) => self.referenceable(property, value, &property_pointer, depth + 1),
+                None => match &schema.additional_properties {
+                    Some(AdditionalProperties::Allowed(false)) => {
+                        self.error(&property_pointer, "additional property is not allowed")
+                    }
+                    Some(AdditionalProperties::Schema(additional)) => {
+                        self.referenceable(additional, value, &property_pointer, depth + 1)
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    /// Whether a property is not expected in the direction of the value.
+    fn is_omitted(&self, schema: &Schema, name: &str) -> bool {
+        let Some(direction) = self.direction else {
+            return false;
+        };
+        let Some(property) = schema
+            .property(name, self.components)
+            .and_then(|property| property.resolve(self.components))
+        else {
+            return false;
+        };
+        match direction {
+            Direction::Request => property.read_only.unwrap_or(false),
+            Direction::Response => property.write_only.unwrap_or(false),
+        }
+    }
+
+    fn composition(&mut self, schema: &Schema, value: &Value, pointer: &str, depth: usize) {
+        for all_of in schema.all_of.iter().flatten() {
+            self.referenceable(all_of, value, pointer, depth + 1);
+        }
+        if let Some(any_of) = &schema.any_of {
+            if !any_of
+                .iter()
+                .any(|schema| self.is_valid(schema, value, depth + 1))
+            {
+                self.error(pointer, "does not match any of the schemas in `anyOf`");
+            }
+        }
+        if let Some(one_of) = &schema.one_of {
+            match self.discriminated(schema, value) {
+                Some(Ok(selected)) => self.referenceable(&selected, value, pointer, depth + 1),
+                Some(Err(message)) => self.error(pointer, message),
+                None => {
+                    let matches = one_of
+                        .iter()
+                        .filter(|schema| self.is_valid(schema, value, depth + 1))
+                        .count();
+                    if matches != 1 {
+                        self.error(
+                            pointer,
+                            format!("matches {matches} of the schemas in `oneOf` instead of one"),
+                        );
+                    }
+                }
+            }
+        }
+        if let Some(not) = &schema.not {
+            if self.is_valid(not, value, depth + 1) {
+                self.error(pointer, "matches the schema in `not`");
+            }
+        }
+    }
+
+    /// The schema selected by the discriminator of a `oneOf`, if there is a discriminator.
+    fn discriminated(
+        &self,
+        schema: &Schema,
+        value: &Value,
+    ) -> Option<Result<Referenceable<Schema>, String>> {
+        let discriminator = schema.discriminator.as_ref()?;
+        let property = &discriminator.property_name;
+        let Some(name) = value.get(property).and_then(Value::as_str) else {
+            return Some(Err(format!("missing discriminator property `{property}`")));
+        };
+        Some(Ok(discriminator.schema_for(name)))
+    }
+}
+
+impl Format {
+    /// Whether a string conforms to the format. Formats which are not checked, or which do not
+    /// apply to strings, accept any value.
+    pub fn is_valid(&self, value: &str) -> bool {
+        match self {
+            Format::DateTime => is_date_time(value),
+            Format::Date => is_date(value),
+            Format::Time => is_time(value),
+            Format::Email => EmailAddress::is_valid(value),
+            Format::Ipv4 => value.parse::<Ipv4Addr>().is_ok(),
+            Format::Ipv6 => value.parse::<Ipv6Addr>().is_ok(),
+            Format::Uri => Url::parse(value).is_ok(),
+            Format::Uuid => is_uuid(value),
+            Format::Hostname => is_hostname(value),
+            Format::Regex => Regex::new(value).is_ok(),
+            _ => true,
+        }
+    }
+}
+
+fn has_type(value: &Value, _type: &Type) -> bool {
+    match _type {
+        Type::Null => value.is_null(),
+        Type::Boolean => value.is_boolean(),
+        Type::Object => value.is_object(),
+        Type::Array => value.is_array(),
+        Type::Number => value.is_number(),
+        Type::Integer => match value {
+            Value::Number(number) => {
+                number.is_i64()
+                    || number.is_u64()
+                    || number.as_f64().is_some_and(|n| n.fract() == 0.0)
+            }
+            _ => false,
+        },
+        Type::String => value.is_string(),
+        Type::Other(_) => true,
+    }
+}
+
+fn type_name(_type: &Type) -> &str {
+    match _type {
+        Type::Null => "null",
+        Type::Boolean => "boolean",
+        Type::Object => "object",
+        Type::Array => "array",
+        Type::Integer => "integer",
+        Type::Number => "number",
+        Type::String => "string",
+        Type::Other(other) => other,
+    }
+}
+
+fn value_name(value: &Value) -> &str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn format_name(format: &Format) -> String {
+    match serde_json::to_value(format) {
+        Ok(Value::String(name)) => name,
+        _ => format!("{format:?}"),
+    }
+}
+
+fn digits(value: &str, count: usize) -> Option<u32> {
+    (value.len() == count && value.bytes().all(|byte| byte.is_ascii_digit()))
+        .then(|| value.parse().ok())
+        .flatten()
+}
+
+/// A full-date as defined by [RFC3339](https://www.rfc-editor.org/rfc/rfc3339#section-5.6).
+fn is_date(value: &str) -> bool {
+    let mut parts = value.split('-');
+    let (Some(year), Some(month), Some(day), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let (Some(year), Some(month), Some(day)) = (digits(year, 4), digits(month, 2), digits(day, 2))
+    else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// A full-time as defined by [RFC3339](https://www.rfc-editor.org/rfc/rfc3339#section-5.6),
+/// i.e. including the offset.
+fn is_time(value: &str) -> bool {
+    let (time, offset) = match value.find(['Z', 'z', '+', '-']) {
+        Some(index) => value.split_at(index),
+        None => return false,
+    };
+    let time = time.split_once('.').map_or(time, |(time, fraction)| {
+        match !fraction.is_empty() && fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+            true => time,
+            false => "",
+        }
+    });
+    let mut parts = time.split(':');
+    let valid_time = matches!(
+        (
+            parts.next().and_then(|hour| digits(hour, 2)),
+            parts.next().and_then(|minute| digits(minute, 2)),
+            parts.next().and_then(|second| digits(second, 2)),
+            parts.next(),
+        ),
+        (Some(0..=23), Some(0..=59), Some(0..=60), None)
+    );
+    let valid_offset = match offset {
+        "Z" | "z" => true,
+        offset => match offset[1..].split_once(':') {
+            Some((hour, minute)) => {
+                matches!(digits(hour, 2), Some(0..=23)) && matches!(digits(minute, 2), Some(0..=59))
+            }
+            None => false,
+        },
+    };
+    valid_time && valid_offset
+}
+
+fn is_date_time(value: &str) -> bool {
+    match value.split_once(['T', 't', ' ']) {
+        Some((date, time)) => is_date(date) && is_time(time),
+        None => false,
+    }
+}
+
+fn is_uuid(value: &str) -> bool {
+    let groups = value.split('-').map(str::len).collect::<Vec<_>>();
+    groups == [8, 4, 4, 4, 12]
+        && value
+            .bytes()
+            .all(|byte| byte == b'-' || byte.is_ascii_hexdigit())
+}
+
+fn is_hostname(value: &str) -> bool {
+    let value = value.strip_suffix('.').unwrap_or(value);
+    !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+        })
+}